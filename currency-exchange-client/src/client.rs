@@ -64,6 +64,9 @@ pub struct AddCurrencyArgs {
 pub struct ShowBuyOrdersArgs {
     #[arg(long)]
     pub orders: i64,
+    /// open, partially_filled, filled, cancelled or expired; live orders when omitted
+    #[arg(long)]
+    pub status: Option<String>,
     #[arg(long)]
     pub auth_token: String,
 }
@@ -73,6 +76,9 @@ pub struct ShowBuyOrdersArgs {
 pub struct ShowSellOrdersArgs {
     #[arg(long)]
     pub orders: i64,
+    /// open, partially_filled, filled, cancelled or expired; live orders when omitted
+    #[arg(long)]
+    pub status: Option<String>,
     #[arg(long)]
     pub auth_token: String,
 }
@@ -89,7 +95,30 @@ pub struct CreateBuyOrderArgs {
     #[arg(long)]
     pub sell_currency_id: i32,
     #[arg(long)]
-    pub expiry_days: i32,
+    pub limit_price: f64,
+    #[arg(long)]
+    pub expiry_days: Option<i32>,
+    ///
+    /// gtc, gtd, ioc or fok, GTD when an expiry is given and GTC otherwise
+    #[arg(long, value_parser = ["gtc", "gtd", "ioc", "fok"])]
+    pub time_in_force: Option<String>,
+    ///
+    /// RFC 3339 expiry timestamp for GTD orders
+    #[arg(long)]
+    pub expires_at: Option<String>,
+    ///
+    /// Slice shown in the order book, makes the order an iceberg order
+    #[arg(long)]
+    pub display_amount: Option<i32>,
+    ///
+    /// Id that makes resubmitting the same order return the original one
+    #[arg(long)]
+    pub client_order_id: Option<String>,
+    ///
+    /// What happens when the order would match one of your own: cancel_newest, cancel_oldest,
+    /// cancel_both or decrement, your account setting when omitted
+    #[arg(long, value_parser = ["cancel_newest", "cancel_oldest", "cancel_both", "decrement"])]
+    pub self_trade_prevention: Option<String>,
     #[arg(long)]
     pub auth_token: String,
 }
@@ -106,24 +135,236 @@ pub struct CreateSellOrderArgs {
     #[arg(long)]
     pub buy_currency_id: i32,
     #[arg(long)]
-    pub expiry_days: i32,
+    pub limit_price: f64,
+    #[arg(long)]
+    pub expiry_days: Option<i32>,
+    ///
+    /// gtc, gtd, ioc or fok, GTD when an expiry is given and GTC otherwise
+    #[arg(long, value_parser = ["gtc", "gtd", "ioc", "fok"])]
+    pub time_in_force: Option<String>,
+    ///
+    /// RFC 3339 expiry timestamp for GTD orders
+    #[arg(long)]
+    pub expires_at: Option<String>,
+    ///
+    /// Slice shown in the order book, makes the order an iceberg order
+    #[arg(long)]
+    pub display_amount: Option<i32>,
+    ///
+    /// Id that makes resubmitting the same order return the original one
+    #[arg(long)]
+    pub client_order_id: Option<String>,
+    ///
+    /// What happens when the order would match one of your own: cancel_newest, cancel_oldest,
+    /// cancel_both or decrement, your account setting when omitted
+    #[arg(long, value_parser = ["cancel_newest", "cancel_oldest", "cancel_both", "decrement"])]
+    pub self_trade_prevention: Option<String>,
     #[arg(long)]
     pub auth_token: String,
 }
 
 #[derive(Parser, Serialize, Clone, Debug)]
 #[command(version, about, long_about = None)]
-pub struct BuyCurrencyArgs {
+pub struct MarketOrderArgs {
+    #[arg(long)]
+    pub buy_currency_id: i32,
+    #[arg(long)]
+    pub sell_currency_id: i32,
+    /// Amount of the bought currency for a market buy, of the sold currency for a market sell
+    #[arg(long)]
+    pub amount: i32,
+    /// Furthest any fill may be from the best price, in percent
+    #[arg(long)]
+    pub max_slippage_percent: Option<f64>,
+    /// Most of the sold currency to pay, market buys only
+    #[arg(long)]
+    pub max_spend: Option<i32>,
+    ///
+    /// What happens when the order would match one of your own: cancel_newest, cancel_oldest,
+    /// cancel_both or decrement, your account setting when omitted
+    #[arg(long, value_parser = ["cancel_newest", "cancel_oldest", "cancel_both", "decrement"])]
+    pub self_trade_prevention: Option<String>,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct CreateStopOrderArgs {
+    #[arg(long, value_parser = ["buy", "sell"])]
+    pub side: String,
+    #[arg(long)]
+    pub base_currency_id: i32,
+    #[arg(long)]
+    pub quote_currency_id: i32,
+    /// Amount of the base currency
+    #[arg(long)]
+    pub amount: i32,
+    /// Last trade price that sets the order off
+    #[arg(long)]
+    pub stop_price: f64,
+    /// Limit price of the order placed once triggered, a market order when omitted
+    #[arg(long)]
+    pub limit_price: Option<f64>,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowStopOrdersArgs {
+    #[arg(long, value_parser = ["pending", "triggered", "cancelled", "rejected"])]
+    pub status: Option<String>,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct CancelStopOrderArgs {
+    #[arg(long)]
+    pub order_id: i32,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct CreateOcoOrderArgs {
+    #[arg(long, value_parser = ["buy", "sell"])]
+    pub side: String,
+    #[arg(long)]
+    pub base_currency_id: i32,
+    #[arg(long)]
+    pub quote_currency_id: i32,
+    /// Amount of the base currency
+    #[arg(long)]
+    pub amount: i32,
+    /// Price of the limit leg, above the stop for sells and below it for buys
+    #[arg(long)]
+    pub limit_price: f64,
+    /// Last trade price that sets the stop leg off
+    #[arg(long)]
+    pub stop_price: f64,
+    /// Limit price of the order the stop leg places, a market order when omitted
+    #[arg(long)]
+    pub stop_limit_price: Option<f64>,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct CancelOrderGroupArgs {
+    #[arg(long)]
+    pub order_group_id: i32,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct PlaceOrderBatchArgs {
+    ///
+    /// JSON file with the batch: `atomic` and a list of `items` tagged by `action`
+    /// (create_buy, create_sell, cancel_buy, cancel_sell)
+    #[arg(long)]
+    pub batch_file: String,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowMyOrdersArgs {
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowClientOrderArgs {
+    #[arg(long)]
+    pub client_order_id: String,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct CancelMyOrdersArgs {
+    /// Currency pair as BASE/QUOTE, every pair when omitted
+    #[arg(long)]
+    pub pair: Option<String>,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct HaltTradingArgs {
+    #[arg(long)]
+    pub base: String,
+    #[arg(long)]
+    pub quote: String,
+    ///
+    /// `cancel` also cancels every open order on the pair, `freeze` leaves them resting
+    #[arg(long, value_parser = ["cancel", "freeze"])]
+    pub mode: String,
     #[arg(long)]
-    pub sum: i32,
+    pub reason: Option<String>,
     #[arg(long)]
-    pub rate: f32,
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ResumeTradingArgs {
+    #[arg(long)]
+    pub base: String,
+    #[arg(long)]
+    pub quote: String,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowTradingSettingsArgs {
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct UpdateTradingSettingsArgs {
+    ///
+    /// Mode for your orders that do not choose their own
+    #[arg(long, value_parser = ["cancel_newest", "cancel_oldest", "cancel_both", "decrement"])]
+    pub self_trade_prevention: String,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowPreventedTradesArgs {
     #[arg(long)]
-    pub order_issuer_id: i32,
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct BuyCurrencyArgs {
     #[arg(long)]
-    pub incoming_currency_id: i32,
+    pub sell_order_id: i32,
     #[arg(long)]
-    pub outgoing_currency_id: i32,
+    pub amount: i32,
+    ///
+    /// What happens when the order would match one of your own: cancel_newest, cancel_oldest,
+    /// cancel_both or decrement, your account setting when omitted
+    #[arg(long, value_parser = ["cancel_newest", "cancel_oldest", "cancel_both", "decrement"])]
+    pub self_trade_prevention: Option<String>,
     #[arg(long)]
     pub auth_token: String,
 }
@@ -132,15 +373,160 @@ pub struct BuyCurrencyArgs {
 #[command(version, about, long_about = None)]
 pub struct SellCurrencyArgs {
     #[arg(long)]
-    pub sum: i32,
+    pub buy_order_id: i32,
+    #[arg(long)]
+    pub amount: i32,
+    ///
+    /// What happens when the order would match one of your own: cancel_newest, cancel_oldest,
+    /// cancel_both or decrement, your account setting when omitted
+    #[arg(long, value_parser = ["cancel_newest", "cancel_oldest", "cancel_both", "decrement"])]
+    pub self_trade_prevention: Option<String>,
     #[arg(long)]
-    pub rate: f32,
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowOrderFillsArgs {
+    #[arg(long)]
+    pub order_id: i32,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowOrderBookArgs {
+    /// Base currency code, e.g. EUR
+    #[arg(long)]
+    pub base: String,
+    /// Quote currency code, e.g. USD
+    #[arg(long)]
+    pub quote: String,
+    /// Price levels per side, 20 when omitted
+    #[arg(long)]
+    pub depth: Option<i64>,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowTradesArgs {
+    /// Base currency code, e.g. EUR
+    #[arg(long)]
+    pub base: String,
+    /// Quote currency code, e.g. USD
+    #[arg(long)]
+    pub quote: String,
+    #[command(flatten)]
+    pub page: TradePageArgs,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowMyTradesArgs {
+    #[command(flatten)]
+    pub page: TradePageArgs,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+pub struct TradePageArgs {
+    /// Earliest execution time (RFC 3339), inclusive
+    #[arg(long)]
+    pub from: Option<String>,
+    /// Latest execution time (RFC 3339), exclusive
+    #[arg(long)]
+    pub to: Option<String>,
+    /// `next_cursor` of the previous page
+    #[arg(long)]
+    pub cursor: Option<i32>,
+    /// Trades per page, 50 when omitted
+    #[arg(long)]
+    pub limit: Option<i64>,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowCandlesArgs {
+    /// Base currency code, e.g. EUR
+    #[arg(long)]
+    pub base: String,
+    /// Quote currency code, e.g. USD
+    #[arg(long)]
+    pub quote: String,
+    #[arg(long, value_parser = ["1m", "5m", "1h", "1d"])]
+    pub interval: String,
+    /// Earliest bucket start (RFC 3339), inclusive
+    #[arg(long)]
+    pub from: Option<String>,
+    /// Latest bucket start (RFC 3339), exclusive
+    #[arg(long)]
+    pub to: Option<String>,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct BackfillCandlesArgs {
+    /// Base currency code, e.g. EUR
+    #[arg(long)]
+    pub base: String,
+    /// Quote currency code, e.g. USD
+    #[arg(long)]
+    pub quote: String,
+    /// Range start (RFC 3339)
+    #[arg(long)]
+    pub from: String,
+    /// Range end (RFC 3339)
+    #[arg(long)]
+    pub to: String,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct ShowTickerArgs {
+    /// Base currency code, every pair when omitted
+    #[arg(long, requires = "quote")]
+    pub base: Option<String>,
+    /// Quote currency code, every pair when omitted
+    #[arg(long, requires = "base")]
+    pub quote: Option<String>,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct WatchUserDataArgs {
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct CancelOrderArgs {
+    #[arg(long, value_parser = ["buy", "sell"])]
+    pub side: String,
+    #[arg(long)]
+    pub order_id: i32,
+    #[arg(long)]
+    pub auth_token: String,
+}
+
+#[derive(Parser, Serialize, Clone, Debug)]
+#[command(version, about, long_about = None)]
+pub struct AmendOrderArgs {
+    #[arg(long, value_parser = ["buy", "sell"])]
+    pub side: String,
+    #[arg(long)]
+    pub order_id: i32,
     #[arg(long)]
-    pub order_issuer_id: i32,
+    pub amount: Option<i32>,
     #[arg(long)]
-    pub incoming_currency_id: i32,
+    pub limit_price: Option<f64>,
+    /// RFC 3339 timestamp, e.g. 2030-01-31T12:00:00Z
     #[arg(long)]
-    pub outgoing_currency_id: i32,
+    pub expires_at: Option<String>,
     #[arg(long)]
     pub auth_token: String,
 }
@@ -194,6 +580,71 @@ pub enum ApiCommands {
         #[command(flatten)]
         args: CreateSellOrderArgs
     },
+    MarketBuy {
+        #[command(flatten)]
+        args: MarketOrderArgs
+    },
+    MarketSell {
+        #[command(flatten)]
+        args: MarketOrderArgs
+    },
+    CreateStopOrder {
+        #[command(flatten)]
+        args: CreateStopOrderArgs
+    },
+    StopOrders {
+        #[command(flatten)]
+        args: ShowStopOrdersArgs
+    },
+    CancelStopOrder {
+        #[command(flatten)]
+        args: CancelStopOrderArgs
+    },
+    CreateOcoOrder {
+        #[command(flatten)]
+        args: CreateOcoOrderArgs
+    },
+    CancelOrderGroup {
+        #[command(flatten)]
+        args: CancelOrderGroupArgs
+    },
+    PlaceOrderBatch {
+        #[command(flatten)]
+        args: PlaceOrderBatchArgs
+    },
+    MyOrders {
+        #[command(flatten)]
+        args: ShowMyOrdersArgs
+    },
+    ClientOrder {
+        #[command(flatten)]
+        args: ShowClientOrderArgs
+    },
+    CancelMyOrders {
+        #[command(flatten)]
+        args: CancelMyOrdersArgs
+    },
+    HaltTrading {
+        #[command(flatten)]
+        args: HaltTradingArgs
+    },
+    ResumeTrading {
+        #[command(flatten)]
+        args: ResumeTradingArgs
+    },
+    TradingHalts,
+    TradingSettings {
+        #[command(flatten)]
+        args: ShowTradingSettingsArgs
+    },
+    UpdateTradingSettings {
+        #[command(flatten)]
+        args: UpdateTradingSettingsArgs
+    },
+    PreventedTrades {
+        #[command(flatten)]
+        args: ShowPreventedTradesArgs
+    },
     BuyCurrency {
         #[command(flatten)]
         args: BuyCurrencyArgs
@@ -202,6 +653,50 @@ pub enum ApiCommands {
         #[command(flatten)]
         args: SellCurrencyArgs
     },
+    BuyOrderFills {
+        #[command(flatten)]
+        args: ShowOrderFillsArgs
+    },
+    SellOrderFills {
+        #[command(flatten)]
+        args: ShowOrderFillsArgs
+    },
+    OrderBook {
+        #[command(flatten)]
+        args: ShowOrderBookArgs
+    },
+    Trades {
+        #[command(flatten)]
+        args: ShowTradesArgs
+    },
+    MyTrades {
+        #[command(flatten)]
+        args: ShowMyTradesArgs
+    },
+    Candles {
+        #[command(flatten)]
+        args: ShowCandlesArgs
+    },
+    BackfillCandles {
+        #[command(flatten)]
+        args: BackfillCandlesArgs
+    },
+    Ticker {
+        #[command(flatten)]
+        args: ShowTickerArgs
+    },
+    Watch {
+        #[command(flatten)]
+        args: WatchUserDataArgs
+    },
+    CancelOrder {
+        #[command(flatten)]
+        args: CancelOrderArgs
+    },
+    AmendOrder {
+        #[command(flatten)]
+        args: AmendOrderArgs
+    },
     ListCurrencies {
         #[command(flatten)]
         args: ListCurrenciesArgs
@@ -1,4 +1,4 @@
-use crate::client_methods::{add_currency_to_wallet, buy_currency, create_buy_order, create_new_currency, create_new_wallet, create_sell_order, create_user, display_buy_orders, display_currencies, display_sell_orders, login_user, sell_currency};
+use crate::client_methods::{add_currency_to_wallet, amend_order, backfill_candles, buy_currency, cancel_my_orders, cancel_order, cancel_order_group, cancel_stop_order, create_buy_order, create_new_currency, create_new_wallet, create_oco_order, create_sell_order, create_stop_order, create_user, display_buy_order_fills, display_buy_orders, display_candles, display_client_order, display_currencies, display_my_orders, display_my_trades, display_order_book, display_prevented_trades, display_sell_order_fills, display_sell_orders, display_stop_orders, display_ticker, display_trades, display_trading_halts, display_trading_settings, halt_trading, login_user, market_buy, market_sell, place_order_batch, resume_trading, sell_currency, update_trading_settings, watch_user_data};
 use clap::Parser;
 use currency_exchange_client::client::{ApiCommands, CliCommands, UserCommands};
 
@@ -17,6 +17,32 @@ mod api_endpoints {
     pub const CREATE_SELL_ORDER: &str = "/api/v1/orders/sell/new";
     pub const BUY_CURRENCY: &str = "/api/v1/orders/buy/execute";
     pub const SELL_CURRENCY: &str = "/api/v1/orders/sell/execute";
+    pub const BUY_ORDER_FILLS: &str = "/api/v1/orders/buy/{id}/fills";
+    pub const SELL_ORDER_FILLS: &str = "/api/v1/orders/sell/{id}/fills";
+    pub const ORDER_BOOK: &str = "/api/v1/orderbook/{base}/{quote}";
+    pub const TRADES: &str = "/api/v1/trades/{base}/{quote}";
+    pub const MY_TRADES: &str = "/api/v1/me/trades";
+    pub const CANDLES: &str = "/api/v1/candles/{base}/{quote}";
+    pub const BACKFILL_CANDLES: &str = "/api/v1/candles/{base}/{quote}/backfill";
+    pub const TICKERS: &str = "/api/v1/ticker";
+    pub const TICKER: &str = "/api/v1/ticker/{base}/{quote}";
+    pub const CANCEL_ORDER: &str = "/api/v1/orders/{side}/{id}";
+    pub const AMEND_ORDER: &str = "/api/v1/orders/{side}/{id}";
+    pub const USER_DATA_STREAM: &str = "/api/v1/ws/me";
+    pub const MARKET_BUY: &str = "/api/v1/orders/buy/market";
+    pub const MARKET_SELL: &str = "/api/v1/orders/sell/market";
+    pub const STOP_ORDERS: &str = "/api/v1/orders/stop";
+    pub const CREATE_STOP_ORDER: &str = "/api/v1/orders/stop/new";
+    pub const CANCEL_STOP_ORDER: &str = "/api/v1/orders/stop/{id}";
+    pub const PLACE_ORDER_BATCH: &str = "/api/v1/orders/batch";
+    pub const CREATE_OCO_ORDER: &str = "/api/v1/orders/oco/new";
+    pub const CANCEL_ORDER_GROUP: &str = "/api/v1/orders/oco/{id}";
+    pub const MY_ORDERS: &str = "/api/v1/me/orders";
+    pub const MY_ORDER_BY_CLIENT_ID: &str = "/api/v1/me/orders/by-client-id/{client_order_id}";
+    pub const TRADING_HALTS: &str = "/api/v1/halts";
+    pub const TRADING_HALT: &str = "/api/v1/admin/halts/{base}/{quote}";
+    pub const TRADING_SETTINGS: &str = "/api/v1/me/settings";
+    pub const PREVENTED_TRADES: &str = "/api/v1/me/prevented-trades";
 }
 
 ///
@@ -58,33 +84,90 @@ mod password_encoder {
 ///
 /// CLI methods module
 mod client_methods {
-    use crate::api_endpoints::{ADD_CURRENCY, BUY_CURRENCY, BUY_ORDERS, CREATE_BUY_ORDER, CREATE_CURRENCY, CREATE_SELL_ORDER, CREATE_WALLET, CURRENCY_LIST, LOGIN, SELL_CURRENCY, SELL_ORDERS, SIGNUP};
+    use crate::api_endpoints::{ADD_CURRENCY, AMEND_ORDER, BACKFILL_CANDLES, BUY_CURRENCY, CANDLES, BUY_ORDERS, BUY_ORDER_FILLS, CANCEL_ORDER, CANCEL_ORDER_GROUP, CANCEL_STOP_ORDER, CREATE_BUY_ORDER, CREATE_CURRENCY, CREATE_OCO_ORDER, CREATE_SELL_ORDER, CREATE_STOP_ORDER, CREATE_WALLET, CURRENCY_LIST, LOGIN, MARKET_BUY, MARKET_SELL, MY_ORDERS, MY_ORDER_BY_CLIENT_ID, MY_TRADES, ORDER_BOOK, PLACE_ORDER_BATCH, PREVENTED_TRADES, SELL_CURRENCY, SELL_ORDERS, SELL_ORDER_FILLS, SIGNUP, STOP_ORDERS, TICKER, TICKERS, TRADES, TRADING_HALT, TRADING_HALTS, TRADING_SETTINGS, USER_DATA_STREAM};
     use crate::password_encoder::encode_password;
     use crate::url_builder::{build_user_api_base_url, build_login_base_url, build_orders_api_base_url};
     use currency_exchange_client::client::AddCurrencyArgs;
+    use currency_exchange_client::client::AmendOrderArgs;
+    use currency_exchange_client::client::BackfillCandlesArgs;
     use currency_exchange_client::client::BuyCurrencyArgs;
+    use currency_exchange_client::client::CancelMyOrdersArgs;
+    use currency_exchange_client::client::CancelOrderArgs;
+    use currency_exchange_client::client::CancelOrderGroupArgs;
+    use currency_exchange_client::client::CancelStopOrderArgs;
     use currency_exchange_client::client::CreateBuyOrderArgs;
     use currency_exchange_client::client::CreateCurrencyArgs;
+    use currency_exchange_client::client::CreateOcoOrderArgs;
+    use currency_exchange_client::client::PlaceOrderBatchArgs;
     use currency_exchange_client::client::CreateSellOrderArgs;
+    use currency_exchange_client::client::CreateStopOrderArgs;
     use currency_exchange_client::client::CreateUserArgs;
     use currency_exchange_client::client::CreateWalletArgs;
     use currency_exchange_client::client::ListCurrenciesArgs;
+    use currency_exchange_client::client::HaltTradingArgs;
     use currency_exchange_client::client::LoginUserArgs;
+    use currency_exchange_client::client::MarketOrderArgs;
+    use currency_exchange_client::client::ResumeTradingArgs;
     use currency_exchange_client::client::SellCurrencyArgs;
     use currency_exchange_client::client::ShowBuyOrdersArgs;
+    use currency_exchange_client::client::ShowOrderBookArgs;
+    use currency_exchange_client::client::ShowCandlesArgs;
+    use currency_exchange_client::client::ShowClientOrderArgs;
+    use currency_exchange_client::client::ShowMyOrdersArgs;
+    use currency_exchange_client::client::ShowMyTradesArgs;
+    use currency_exchange_client::client::ShowOrderFillsArgs;
+    use currency_exchange_client::client::ShowPreventedTradesArgs;
+    use currency_exchange_client::client::ShowTickerArgs;
+    use currency_exchange_client::client::ShowTradesArgs;
+    use currency_exchange_client::client::ShowTradingSettingsArgs;
+    use currency_exchange_client::client::TradePageArgs;
     use currency_exchange_client::client::ShowSellOrdersArgs;
+    use currency_exchange_client::client::ShowStopOrdersArgs;
+    use currency_exchange_client::client::UpdateTradingSettingsArgs;
+    use currency_exchange_client::client::WatchUserDataArgs;
     use currency_exchange_client::client_env_parser::ClientEnvParser;
     use currency_exchange_data::datasource::api_models::AddCurrencyRequest;
+    use currency_exchange_data::datasource::api_models::AmendOrderRequest;
+    use currency_exchange_data::datasource::api_models::BackfillCandlesRequest;
+    use currency_exchange_data::datasource::api_models::BuyCurrencyRequest;
     use currency_exchange_data::datasource::api_models::CreateBuyOrderRequest;
     use currency_exchange_data::datasource::api_models::CreateCurrencyRequest;
+    use currency_exchange_data::datasource::api_models::CreateOcoOrderRequest;
     use currency_exchange_data::datasource::api_models::CreateSellOrderRequest;
+    use currency_exchange_data::datasource::api_models::CreateStopOrderRequest;
     use currency_exchange_data::datasource::api_models::CreateUserRequest;
     use currency_exchange_data::datasource::api_models::CreateUserResponse;
     use currency_exchange_data::datasource::api_models::CreateWalletRequest;
-    use currency_exchange_data::datasource::api_models::ExchangeCurrencyRequest;
     use currency_exchange_data::datasource::api_models::LoginRequest;
-    use reqwest::Client;
-    use currency_exchange_data::datasource::models::{BuyOrder, Currency, CurrencyAmount, SellOrder, Wallet};
+    use currency_exchange_data::datasource::api_models::MarketOrderRequest;
+    use currency_exchange_data::datasource::api_models::SellCurrencyRequest;
+    use currency_exchange_data::datasource::api_models::TradingHaltRequest;
+    use currency_exchange_data::datasource::api_models::TradingSettingsRequest;
+    use futures_util::StreamExt;
+    use reqwest::{Client, StatusCode};
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::Message;
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+    use currency_exchange_data::datasource::models::{BuyOrder, Candle, ClientOrder, Currency, CurrencyExchangeRecord, Fill, MarketOrderResult, OrderBook, OrderSide, PreventedTrade, SelfTradePrevention, SellOrder, StopOrder, Ticker, TimeInForce, Trade, TradePage, TradingHalt, TradingHaltMode, TradingSettings, UserOrders, UserTrade, Wallet};
+
+    fn parse_time_in_force(value: &str) -> TimeInForce {
+        match value {
+            "gtd" => TimeInForce::Gtd,
+            "ioc" => TimeInForce::Ioc,
+            "fok" => TimeInForce::Fok,
+            _ => TimeInForce::Gtc,
+        }
+    }
+
+    fn parse_self_trade_prevention(value: &str) -> SelfTradePrevention {
+        match value {
+            "cancel_oldest" => SelfTradePrevention::CancelOldest,
+            "cancel_both" => SelfTradePrevention::CancelBoth,
+            "decrement" => SelfTradePrevention::Decrement,
+            _ => SelfTradePrevention::CancelNewest,
+        }
+    }
 
     ///
     /// Executes login using provided args from clap
@@ -272,7 +355,10 @@ mod client_methods {
         let token = args.auth_token;
         let network_client = Client::new();
         let parser = ClientEnvParser::new();
-        let params = [("count", orders_limit.to_string())];
+        let mut params = vec![("count", orders_limit.to_string())];
+        if let Some(status) = args.status {
+            params.push(("status", status));
+        }
         let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), BUY_ORDERS);
         let url_with_params = reqwest::Url::parse_with_params(&url, &params).unwrap();
         let res = network_client.get(url_with_params)
@@ -301,7 +387,10 @@ mod client_methods {
         let token = args.auth_token;
         let network_client = Client::new();
         let parser = ClientEnvParser::new();
-        let params = [("count", orders_limit.to_string())];
+        let mut params = vec![("count", orders_limit.to_string())];
+        if let Some(status) = args.status {
+            params.push(("status", status));
+        }
         let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), SELL_ORDERS);
         let url_with_params = reqwest::Url::parse_with_params(&url, &params).unwrap();
         let res = network_client.get(url_with_params)
@@ -330,7 +419,14 @@ mod client_methods {
         let buy_amount = args.buy_amount;
         let buy_currency_id = args.buy_currency_id;
         let sell_currency_id = args.sell_currency_id;
+        let limit_price = args.limit_price;
         let expiry_days = args.expiry_days;
+        let time_in_force = args.time_in_force.as_deref().map(parse_time_in_force);
+        let display_amount = args.display_amount;
+        let client_order_id = args.client_order_id;
+        let self_trade_prevention = args.self_trade_prevention.as_deref().map(parse_self_trade_prevention);
+        let expires_at = args.expires_at
+            .map(|expires_at| OffsetDateTime::parse(&expires_at, &Rfc3339).expect("expires-at must be an RFC 3339 timestamp"));
         let token = args.auth_token;
         let parser = ClientEnvParser::new();
         let client = Client::new();
@@ -340,8 +436,12 @@ mod client_methods {
             buy_amount,
             buy_currency_id,
             sell_currency_id,
+            limit_price,
             expiry_days,
-        );
+        ).with_time_in_force(time_in_force, expires_at)
+            .with_display_amount(display_amount)
+            .with_client_order_id(client_order_id)
+            .with_self_trade_prevention(self_trade_prevention);
         let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), CREATE_BUY_ORDER);
         let res = client.post(url)
             .header("Authorization", format!("Bearer {}", token))
@@ -370,7 +470,14 @@ mod client_methods {
         let sell_amount = args.sell_amount;
         let buy_currency_id = args.buy_currency_id;
         let sell_currency_id = args.sell_currency_id;
+        let limit_price = args.limit_price;
         let expiry_days = args.expiry_days;
+        let time_in_force = args.time_in_force.as_deref().map(parse_time_in_force);
+        let display_amount = args.display_amount;
+        let client_order_id = args.client_order_id;
+        let self_trade_prevention = args.self_trade_prevention.as_deref().map(parse_self_trade_prevention);
+        let expires_at = args.expires_at
+            .map(|expires_at| OffsetDateTime::parse(&expires_at, &Rfc3339).expect("expires-at must be an RFC 3339 timestamp"));
         let token = args.auth_token;
         let parser = ClientEnvParser::new();
         let client = Client::new();
@@ -378,10 +485,14 @@ mod client_methods {
         let create_buy_order_req = CreateSellOrderRequest::new(
             user_id,
             sell_amount,
-            buy_currency_id,
             sell_currency_id,
+            buy_currency_id,
+            limit_price,
             expiry_days,
-        );
+        ).with_time_in_force(time_in_force, expires_at)
+            .with_display_amount(display_amount)
+            .with_client_order_id(client_order_id)
+            .with_self_trade_prevention(self_trade_prevention);
         let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), CREATE_SELL_ORDER);
         let res = client.post(url)
             .header("Authorization", format!("Bearer {}", token))
@@ -399,119 +510,1044 @@ mod client_methods {
     }
 
     ///
-    /// Executes buy currency using provided args from clap
+    /// Executes market buy or sell using provided args from clap
     /// # Arguments
     ///
-    /// * `args`: Buy currency arguments from clap
+    /// * `args`: Market order arguments from clap
+    /// * `path`: market buy or market sell endpoint
     ///
     /// returns: ()
-    pub async fn buy_currency(args: BuyCurrencyArgs) {
-        let sum = args.sum;
-        let rate = args.rate;
-        let order_issuer_id = args.order_issuer_id;
-        let incoming_currency_id = args.incoming_currency_id;
-        let outgoing_currency_id = args.outgoing_currency_id;
+    async fn execute_market_order(args: MarketOrderArgs, path: &str) {
+        let token = args.auth_token;
+        let parser = ClientEnvParser::new();
+        let client = Client::new();
+        let market_order_req = MarketOrderRequest {
+            buy_currency_id: args.buy_currency_id,
+            sell_currency_id: args.sell_currency_id,
+            amount: args.amount,
+            max_slippage_percent: args.max_slippage_percent,
+            max_spend: args.max_spend,
+            self_trade_prevention: args.self_trade_prevention.as_deref().map(parse_self_trade_prevention),
+        };
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = client.post(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&market_order_req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<MarketOrderResult>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to execute market order {:?}", res);
+        }
+    }
+
+    pub async fn market_buy(args: MarketOrderArgs) {
+        execute_market_order(args, MARKET_BUY).await;
+    }
+
+    pub async fn market_sell(args: MarketOrderArgs) {
+        execute_market_order(args, MARKET_SELL).await;
+    }
+
+    ///
+    /// Executes create stop order using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Create stop order arguments from clap
+    ///
+    /// returns: ()
+    pub async fn create_stop_order(args: CreateStopOrderArgs) {
+        let token = args.auth_token;
+        let parser = ClientEnvParser::new();
+        let client = Client::new();
+        let side = if args.side == "sell" { OrderSide::Sell } else { OrderSide::Buy };
+        let stop_order_req = CreateStopOrderRequest {
+            side,
+            base_currency_id: args.base_currency_id,
+            quote_currency_id: args.quote_currency_id,
+            amount: args.amount,
+            stop_price: args.stop_price,
+            limit_price: args.limit_price,
+        };
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), CREATE_STOP_ORDER);
+        let res = client.post(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&stop_order_req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to create stop order {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display stop orders using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display stop orders arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_stop_orders(args: ShowStopOrdersArgs) {
         let token = args.auth_token;
-        let env_parser = ClientEnvParser::new();
         let network_client = Client::new();
-        let req = ExchangeCurrencyRequest::new(
-            sum, rate, order_issuer_id, incoming_currency_id, outgoing_currency_id,
-        );
-        let url = format!("{}://{}{}", env_parser.parse_link_host(), build_orders_api_base_url(&env_parser), BUY_CURRENCY);
-        let res = network_client.put(url)
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), STOP_ORDERS);
+        let params: Vec<(&str, String)> = args.status.into_iter().map(|status| ("status", status)).collect();
+        let url_with_params = reqwest::Url::parse_with_params(&url, &params).unwrap();
+        let res = network_client.get(url_with_params)
             .header("Authorization", format!("Bearer {}", token))
-            .json(&req)
             .send()
             .await;
         if res.is_ok() {
-            let result = res.unwrap().json::<CurrencyAmount>().await;
-            if result.is_ok() {
-                println!("{:?}", result.unwrap())
+            let json = res.unwrap().json::<Vec<StopOrder>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
             }
         } else {
-            println!("Failed to buy currency {:?}", res);
+            println!("Failed to find stop orders {:?}", res);
         }
     }
 
     ///
-    /// Executes sell currency using provided args from clap
+    /// Executes cancel stop order using provided args from clap
     /// # Arguments
     ///
-    /// * `args`: Sell currency arguments from clap
+    /// * `args`: Cancel stop order arguments from clap
     ///
     /// returns: ()
-    pub async fn sell_currency(args: SellCurrencyArgs) {
-        let sum = args.sum;
-        let rate = args.rate;
-        let order_issuer_id = args.order_issuer_id;
-        let incoming_currency_id = args.incoming_currency_id;
-        let outgoing_currency_id = args.outgoing_currency_id;
+    pub async fn cancel_stop_order(args: CancelStopOrderArgs) {
         let token = args.auth_token;
-        let env_parser = ClientEnvParser::new();
         let network_client = Client::new();
-        let req = ExchangeCurrencyRequest::new(
-            sum, rate, order_issuer_id, incoming_currency_id, outgoing_currency_id,
-        );
-        let url = format!("{}://{}{}", env_parser.parse_link_host(), build_orders_api_base_url(&env_parser), SELL_CURRENCY);
-        let res = network_client.put(url)
+        let parser = ClientEnvParser::new();
+        let path = CANCEL_STOP_ORDER.replace("{id}", &args.order_id.to_string());
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.delete(url)
             .header("Authorization", format!("Bearer {}", token))
-            .json(&req)
             .send()
             .await;
         if res.is_ok() {
-            let result = res.unwrap().json::<CurrencyAmount>().await;
-            if result.is_ok() {
-                println!("{:?}", result.unwrap())
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
             }
         } else {
-            println!("Failed to buy currency {:?}", res);
+            println!("Failed to cancel stop order {:?}", res);
         }
     }
-}
 
-fn main() {
-    let cli = CliCommands::parse();
-    let runtime = tokio::runtime::Runtime::new().unwrap();
-    runtime.block_on(async move {
-        match cli {
-            CliCommands::Users { command } => match command {
-                UserCommands::Create {args} => {
-                    create_user(args).await;
-                }
-                UserCommands::Login {args} => {
-                    login_user(args).await;
-                }
+    ///
+    /// Executes create one-cancels-other order using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Create OCO order arguments from clap
+    ///
+    /// returns: ()
+    pub async fn create_oco_order(args: CreateOcoOrderArgs) {
+        let token = args.auth_token;
+        let parser = ClientEnvParser::new();
+        let client = Client::new();
+        let side = if args.side == "sell" { OrderSide::Sell } else { OrderSide::Buy };
+        let oco_order_req = CreateOcoOrderRequest {
+            side,
+            base_currency_id: args.base_currency_id,
+            quote_currency_id: args.quote_currency_id,
+            amount: args.amount,
+            limit_price: args.limit_price,
+            stop_price: args.stop_price,
+            stop_limit_price: args.stop_limit_price,
+        };
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), CREATE_OCO_ORDER);
+        let res = client.post(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&oco_order_req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
             }
-            CliCommands::Api { command } => match command {
-                ApiCommands::ListCurrencies {args} => {
-                    display_currencies(args).await;
-                }
-                ApiCommands::CreateCurrency {args} => {
-                    create_new_currency(args).await;
-                }
-                ApiCommands::CreateWallet {args} => {
-                    create_new_wallet(args).await;
-                }
-                ApiCommands::AddCurrencyForWallet {args} => {
-                    add_currency_to_wallet(args).await;
-                }
-                ApiCommands::BuyOrders {args} => {
-                    display_buy_orders(args).await;
-                }
-                ApiCommands::SellOrders {args} => {
-                    display_sell_orders(args).await;
-                }
-                ApiCommands::CreateBuyOrder {args} => {
-                    create_buy_order(args).await;
-                }
-                ApiCommands::CreateSellOrder {args} => {
-                    create_sell_order(args).await;
-                }
-                ApiCommands::BuyCurrency {args} => {
-                    buy_currency(args).await;
-                }
-                ApiCommands::SellCurrency {args} => {
-                    sell_currency(args).await;
+        } else {
+            println!("Failed to create OCO order {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes place order batch using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Place order batch arguments from clap
+    ///
+    /// returns: ()
+    pub async fn place_order_batch(args: PlaceOrderBatchArgs) {
+        let token = args.auth_token;
+        let parser = ClientEnvParser::new();
+        let client = Client::new();
+        let batch = std::fs::read_to_string(&args.batch_file).expect("Cannot read batch file");
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), PLACE_ORDER_BATCH);
+        let res = client.post(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .body(batch)
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to place order batch {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes cancel order group using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Cancel order group arguments from clap
+    ///
+    /// returns: ()
+    pub async fn cancel_order_group(args: CancelOrderGroupArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = CANCEL_ORDER_GROUP.replace("{id}", &args.order_group_id.to_string());
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.delete(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to cancel order group {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display my orders using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display my orders arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_my_orders(args: ShowMyOrdersArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), MY_ORDERS);
+        let res = network_client.get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<UserOrders>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find orders {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display order by client order id using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display client order arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_client_order(args: ShowClientOrderArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = MY_ORDER_BY_CLIENT_ID.replace("{client_order_id}", &args.client_order_id);
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<ClientOrder>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find order {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes cancel of all my open orders using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Cancel my orders arguments from clap
+    ///
+    /// returns: ()
+    pub async fn cancel_my_orders(args: CancelMyOrdersArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), MY_ORDERS);
+        let mut req = network_client.delete(url)
+            .header("Authorization", format!("Bearer {}", token));
+        if let Some(pair) = args.pair {
+            req = req.query(&[("pair", pair)]);
+        }
+        let res = req.send().await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to cancel orders {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes halt trading on a currency pair using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Halt trading arguments from clap
+    ///
+    /// returns: ()
+    pub async fn halt_trading(args: HaltTradingArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let mode = if args.mode == "freeze" { TradingHaltMode::Freeze } else { TradingHaltMode::Cancel };
+        let halt_req = TradingHaltRequest {
+            mode,
+            reason: args.reason,
+        };
+        let path = TRADING_HALT
+            .replace("{base}", &args.base)
+            .replace("{quote}", &args.quote);
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.post(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&halt_req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to halt trading {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes resume trading on a currency pair using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Resume trading arguments from clap
+    ///
+    /// returns: ()
+    pub async fn resume_trading(args: ResumeTradingArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = TRADING_HALT
+            .replace("{base}", &args.base)
+            .replace("{quote}", &args.quote);
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.delete(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to resume trading {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display of the active trading halts
+    ///
+    /// returns: ()
+    pub async fn display_trading_halts() {
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), TRADING_HALTS);
+        let res = network_client.get(url)
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<Vec<TradingHalt>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find trading halts {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display of my trading settings using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Show trading settings arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_trading_settings(args: ShowTradingSettingsArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), TRADING_SETTINGS);
+        let res = network_client.get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<TradingSettings>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find trading settings {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes update of my trading settings using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Update trading settings arguments from clap
+    ///
+    /// returns: ()
+    pub async fn update_trading_settings(args: UpdateTradingSettingsArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let settings_req = TradingSettingsRequest {
+            self_trade_prevention: parse_self_trade_prevention(&args.self_trade_prevention),
+        };
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), TRADING_SETTINGS);
+        let res = network_client.put(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&settings_req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<TradingSettings>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to update trading settings {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display of my prevented self-trades using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Show prevented trades arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_prevented_trades(args: ShowPreventedTradesArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), PREVENTED_TRADES);
+        let res = network_client.get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<Vec<PreventedTrade>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find prevented trades {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes buy currency using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Buy currency arguments from clap
+    ///
+    /// returns: ()
+    pub async fn buy_currency(args: BuyCurrencyArgs) {
+        let sell_order_id = args.sell_order_id;
+        let amount = args.amount;
+        let token = args.auth_token;
+        let env_parser = ClientEnvParser::new();
+        let network_client = Client::new();
+        let req = BuyCurrencyRequest::new(sell_order_id, amount)
+            .with_self_trade_prevention(args.self_trade_prevention.as_deref().map(parse_self_trade_prevention));
+        let url = format!("{}://{}{}", env_parser.parse_link_host(), build_orders_api_base_url(&env_parser), BUY_CURRENCY);
+        let res = network_client.put(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let res = res.unwrap();
+            if res.status() == StatusCode::CONFLICT {
+                let prevented = res.json::<PreventedTrade>().await;
+                if prevented.is_ok() {
+                    println!("Self-trade prevented {:?}", prevented.unwrap())
+                }
+                return;
+            }
+            let result = res.json::<CurrencyExchangeRecord>().await;
+            if result.is_ok() {
+                println!("{:?}", result.unwrap())
+            }
+        } else {
+            println!("Failed to buy currency {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes sell currency using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Sell currency arguments from clap
+    ///
+    /// returns: ()
+    pub async fn sell_currency(args: SellCurrencyArgs) {
+        let buy_order_id = args.buy_order_id;
+        let amount = args.amount;
+        let token = args.auth_token;
+        let env_parser = ClientEnvParser::new();
+        let network_client = Client::new();
+        let req = SellCurrencyRequest::new(buy_order_id, amount)
+            .with_self_trade_prevention(args.self_trade_prevention.as_deref().map(parse_self_trade_prevention));
+        let url = format!("{}://{}{}", env_parser.parse_link_host(), build_orders_api_base_url(&env_parser), SELL_CURRENCY);
+        let res = network_client.put(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let res = res.unwrap();
+            if res.status() == StatusCode::CONFLICT {
+                let prevented = res.json::<PreventedTrade>().await;
+                if prevented.is_ok() {
+                    println!("Self-trade prevented {:?}", prevented.unwrap())
+                }
+                return;
+            }
+            let result = res.json::<CurrencyExchangeRecord>().await;
+            if result.is_ok() {
+                println!("{:?}", result.unwrap())
+            }
+        } else {
+            println!("Failed to buy currency {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display buy order fills using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display order fills arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_buy_order_fills(args: ShowOrderFillsArgs) {
+        let path = BUY_ORDER_FILLS.replace("{id}", &args.order_id.to_string());
+        display_order_fills(path, args.auth_token).await;
+    }
+
+    ///
+    /// Executes display sell order fills using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display order fills arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_sell_order_fills(args: ShowOrderFillsArgs) {
+        let path = SELL_ORDER_FILLS.replace("{id}", &args.order_id.to_string());
+        display_order_fills(path, args.auth_token).await;
+    }
+
+    ///
+    /// Executes display order book using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display order book arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_order_book(args: ShowOrderBookArgs) {
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = ORDER_BOOK
+            .replace("{base}", &args.base)
+            .replace("{quote}", &args.quote);
+        let mut params = vec![];
+        if let Some(depth) = args.depth {
+            params.push(("depth", depth.to_string()));
+        }
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let url_with_params = reqwest::Url::parse_with_params(&url, &params).unwrap();
+        let res = network_client.get(url_with_params)
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<OrderBook>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find order book {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display trades using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display trades arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_trades(args: ShowTradesArgs) {
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = TRADES
+            .replace("{base}", &args.base)
+            .replace("{quote}", &args.quote);
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let url_with_params = reqwest::Url::parse_with_params(&url, &trade_page_params(args.page)).unwrap();
+        let res = network_client.get(url_with_params)
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<TradePage<Trade>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find trades {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display own trades using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display own trades arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_my_trades(args: ShowMyTradesArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), MY_TRADES);
+        let url_with_params = reqwest::Url::parse_with_params(&url, &trade_page_params(args.page)).unwrap();
+        let res = network_client.get(url_with_params)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<TradePage<UserTrade>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find trades {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display candles using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display candles arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_candles(args: ShowCandlesArgs) {
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = CANDLES
+            .replace("{base}", &args.base)
+            .replace("{quote}", &args.quote);
+        let mut params = vec![("interval", args.interval)];
+        if let Some(from) = args.from {
+            params.push(("from", from));
+        }
+        if let Some(to) = args.to {
+            params.push(("to", to));
+        }
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let url_with_params = reqwest::Url::parse_with_params(&url, &params).unwrap();
+        let res = network_client.get(url_with_params)
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<Vec<Candle>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find candles {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes candle backfill using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Backfill candles arguments from clap
+    ///
+    /// returns: ()
+    pub async fn backfill_candles(args: BackfillCandlesArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let from = OffsetDateTime::parse(&args.from, &Rfc3339).expect("from must be an RFC 3339 timestamp");
+        let to = OffsetDateTime::parse(&args.to, &Rfc3339).expect("to must be an RFC 3339 timestamp");
+        let path = BACKFILL_CANDLES
+            .replace("{base}", &args.base)
+            .replace("{quote}", &args.quote);
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.post(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&BackfillCandlesRequest { from, to })
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to backfill candles {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes display ticker using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Display ticker arguments from clap
+    ///
+    /// returns: ()
+    pub async fn display_ticker(args: ShowTickerArgs) {
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let base_url = format!("{}://{}", parser.parse_link_host(), build_orders_api_base_url(&parser));
+        match (args.base, args.quote) {
+            (Some(base), Some(quote)) => {
+                let path = TICKER
+                    .replace("{base}", &base)
+                    .replace("{quote}", &quote);
+                let res = network_client.get(format!("{}{}", base_url, path))
+                    .send()
+                    .await;
+                if res.is_ok() {
+                    let json = res.unwrap().json::<Ticker>().await;
+                    if json.is_ok() {
+                        println!("{:?}", json.unwrap());
+                    }
+                } else {
+                    println!("Failed to find ticker {:?}", res);
+                }
+            }
+            _ => {
+                let res = network_client.get(format!("{}{}", base_url, TICKERS))
+                    .send()
+                    .await;
+                if res.is_ok() {
+                    let json = res.unwrap().json::<Vec<Ticker>>().await;
+                    if json.is_ok() {
+                        println!("{:?}", json.unwrap());
+                    }
+                } else {
+                    println!("Failed to find tickers {:?}", res);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Executes watch using provided args from clap, printing every message of the user stream
+    /// # Arguments
+    ///
+    /// * `args`: Watch arguments from clap
+    ///
+    /// returns: ()
+    pub async fn watch_user_data(args: WatchUserDataArgs) {
+        let token = args.auth_token;
+        let parser = ClientEnvParser::new();
+        let scheme = if parser.parse_link_host() == "https" { "wss" } else { "ws" };
+        let url = format!("{}://{}{}", scheme, build_orders_api_base_url(&parser), USER_DATA_STREAM);
+        let mut request = url.into_client_request().expect("Invalid user stream url");
+        request.headers_mut().insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+        match tokio_tungstenite::connect_async(request).await {
+            Ok((mut stream, _)) => {
+                while let Some(message) = stream.next().await {
+                    match message {
+                        Ok(Message::Text(text)) => println!("{}", text),
+                        Ok(Message::Close(_)) => break,
+                        Ok(_) => {}
+                        Err(e) => {
+                            println!("User stream failed {:?}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(e) => println!("Failed to open user stream {:?}", e),
+        }
+    }
+
+    fn trade_page_params(page: TradePageArgs) -> Vec<(&'static str, String)> {
+        let mut params = vec![];
+        if let Some(from) = page.from {
+            params.push(("from", from));
+        }
+        if let Some(to) = page.to {
+            params.push(("to", to));
+        }
+        if let Some(cursor) = page.cursor {
+            params.push(("cursor", cursor.to_string()));
+        }
+        if let Some(limit) = page.limit {
+            params.push(("limit", limit.to_string()));
+        }
+        params
+    }
+
+    ///
+    /// Executes cancel order using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Cancel order arguments from clap
+    ///
+    /// returns: ()
+    pub async fn cancel_order(args: CancelOrderArgs) {
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let path = CANCEL_ORDER
+            .replace("{side}", &args.side)
+            .replace("{id}", &args.order_id.to_string());
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.delete(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to cancel order {:?}", res);
+        }
+    }
+
+    ///
+    /// Executes amend order using provided args from clap
+    /// # Arguments
+    ///
+    /// * `args`: Amend order arguments from clap
+    ///
+    /// returns: ()
+    pub async fn amend_order(args: AmendOrderArgs) {
+        let expires_at = match args.expires_at.as_deref().map(|s| OffsetDateTime::parse(s, &Rfc3339)) {
+            Some(Ok(expires_at)) => Some(expires_at),
+            Some(Err(e)) => {
+                println!("Invalid expiry {:?}", e);
+                return;
+            }
+            None => None,
+        };
+        let token = args.auth_token;
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let req = AmendOrderRequest::new(args.amount, args.limit_price, expires_at);
+        let path = AMEND_ORDER
+            .replace("{side}", &args.side)
+            .replace("{id}", &args.order_id.to_string());
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.patch(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&req)
+            .send()
+            .await;
+        if res.is_ok() {
+            let body = res.unwrap().text().await;
+            if body.is_ok() {
+                println!("{}", body.unwrap());
+            }
+        } else {
+            println!("Failed to amend order {:?}", res);
+        }
+    }
+
+    async fn display_order_fills(path: String, token: String) {
+        let network_client = Client::new();
+        let parser = ClientEnvParser::new();
+        let url = format!("{}://{}{}", parser.parse_link_host(), build_orders_api_base_url(&parser), path);
+        let res = network_client.get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await;
+        if res.is_ok() {
+            let json = res.unwrap().json::<Vec<Fill>>().await;
+            if json.is_ok() {
+                println!("{:?}", json.unwrap());
+            }
+        } else {
+            println!("Failed to find order fills {:?}", res);
+        }
+    }
+}
+
+fn main() {
+    let cli = CliCommands::parse();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async move {
+        match cli {
+            CliCommands::Users { command } => match command {
+                UserCommands::Create {args} => {
+                    create_user(args).await;
+                }
+                UserCommands::Login {args} => {
+                    login_user(args).await;
+                }
+            }
+            CliCommands::Api { command } => match command {
+                ApiCommands::ListCurrencies {args} => {
+                    display_currencies(args).await;
+                }
+                ApiCommands::CreateCurrency {args} => {
+                    create_new_currency(args).await;
+                }
+                ApiCommands::CreateWallet {args} => {
+                    create_new_wallet(args).await;
+                }
+                ApiCommands::AddCurrencyForWallet {args} => {
+                    add_currency_to_wallet(args).await;
+                }
+                ApiCommands::BuyOrders {args} => {
+                    display_buy_orders(args).await;
+                }
+                ApiCommands::SellOrders {args} => {
+                    display_sell_orders(args).await;
+                }
+                ApiCommands::CreateBuyOrder {args} => {
+                    create_buy_order(args).await;
+                }
+                ApiCommands::CreateSellOrder {args} => {
+                    create_sell_order(args).await;
+                }
+                ApiCommands::MarketBuy {args} => {
+                    market_buy(args).await;
+                }
+                ApiCommands::MarketSell {args} => {
+                    market_sell(args).await;
+                }
+                ApiCommands::CreateStopOrder {args} => {
+                    create_stop_order(args).await;
+                }
+                ApiCommands::StopOrders {args} => {
+                    display_stop_orders(args).await;
+                }
+                ApiCommands::CancelStopOrder {args} => {
+                    cancel_stop_order(args).await;
+                }
+                ApiCommands::CreateOcoOrder {args} => {
+                    create_oco_order(args).await;
+                }
+                ApiCommands::PlaceOrderBatch {args} => {
+                    place_order_batch(args).await;
+                }
+                ApiCommands::CancelOrderGroup {args} => {
+                    cancel_order_group(args).await;
+                }
+                ApiCommands::MyOrders {args} => {
+                    display_my_orders(args).await;
+                }
+                ApiCommands::ClientOrder {args} => {
+                    display_client_order(args).await;
+                }
+                ApiCommands::CancelMyOrders {args} => {
+                    cancel_my_orders(args).await;
+                }
+                ApiCommands::HaltTrading {args} => {
+                    halt_trading(args).await;
+                }
+                ApiCommands::ResumeTrading {args} => {
+                    resume_trading(args).await;
+                }
+                ApiCommands::TradingHalts => {
+                    display_trading_halts().await;
+                }
+                ApiCommands::TradingSettings {args} => {
+                    display_trading_settings(args).await;
+                }
+                ApiCommands::UpdateTradingSettings {args} => {
+                    update_trading_settings(args).await;
+                }
+                ApiCommands::PreventedTrades {args} => {
+                    display_prevented_trades(args).await;
+                }
+                ApiCommands::BuyCurrency {args} => {
+                    buy_currency(args).await;
+                }
+                ApiCommands::SellCurrency {args} => {
+                    sell_currency(args).await;
+                }
+                ApiCommands::BuyOrderFills {args} => {
+                    display_buy_order_fills(args).await;
+                }
+                ApiCommands::SellOrderFills {args} => {
+                    display_sell_order_fills(args).await;
+                }
+                ApiCommands::OrderBook {args} => {
+                    display_order_book(args).await;
+                }
+                ApiCommands::Trades {args} => {
+                    display_trades(args).await;
+                }
+                ApiCommands::MyTrades {args} => {
+                    display_my_trades(args).await;
+                }
+                ApiCommands::Candles {args} => {
+                    display_candles(args).await;
+                }
+                ApiCommands::BackfillCandles {args} => {
+                    backfill_candles(args).await;
+                }
+                ApiCommands::Ticker {args} => {
+                    display_ticker(args).await;
+                }
+                ApiCommands::Watch {args} => {
+                    watch_user_data(args).await;
+                }
+                ApiCommands::CancelOrder {args} => {
+                    cancel_order(args).await;
+                }
+                ApiCommands::AmendOrder {args} => {
+                    amend_order(args).await;
                 }
             }
         }
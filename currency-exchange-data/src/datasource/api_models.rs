@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use time::OffsetDateTime;
+use time::Duration;
+use crate::datasource::models::{stop_crossed, CandleInterval, OrderSide, OrderStatus, SelfTradePrevention, StopOrderStatus, TimeInForce, TradingHaltMode};
 
 #[derive(Serialize, Deserialize)]
 pub struct CreateUserRequest {
@@ -64,40 +66,310 @@ pub struct BalanceRequest {
     pub currency_code: String,
 }
 
+///
+/// `time_in_force` defaults to GTD when an expiry is given and to GTC otherwise.
+/// GTD orders expire at `expires_at`, or `expiry_days` from now when no timestamp is given.
+/// A `display_amount` makes it an iceberg order showing only that much of its size at a time.
+/// Resubmitting a `client_order_id` the caller already used returns the original order.
+/// Without a `self_trade_prevention` mode the order takes the one set for the caller's account
 #[derive(Serialize, Deserialize)]
 pub struct CreateBuyOrderRequest {
     pub issuer_id: i32,
     pub buy_amount: i32,
     pub buy_currency_id: i32,
     pub sell_currency_id: i32,
-    pub expiry_days: i32,
+    pub limit_price: f64,
+    #[serde(default)]
+    pub expiry_days: Option<i32>,
+    #[serde(default)]
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub display_amount: Option<i32>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub self_trade_prevention: Option<SelfTradePrevention>,
 }
+
+///
+/// `time_in_force` defaults to GTD when an expiry is given and to GTC otherwise.
+/// GTD orders expire at `expires_at`, or `expiry_days` from now when no timestamp is given.
+/// A `display_amount` makes it an iceberg order showing only that much of its size at a time.
+/// Resubmitting a `client_order_id` the caller already used returns the original order.
+/// Without a `self_trade_prevention` mode the order takes the one set for the caller's account
 #[derive(Serialize, Deserialize)]
 pub struct CreateSellOrderRequest {
     pub issuer_id: i32,
     pub sell_amount: i32,
     pub sell_currency_id: i32,
     pub buy_currency_id: i32,
-    pub expiry_days: i32,
+    pub limit_price: f64,
+    #[serde(default)]
+    pub expiry_days: Option<i32>,
+    #[serde(default)]
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub display_amount: Option<i32>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+///
+/// Time in force and expiry an order request resolves to, or why the combination is invalid
+pub fn order_expiry(
+    time_in_force: Option<TimeInForce>,
+    expires_at: Option<OffsetDateTime>,
+    expiry_days: Option<i32>,
+    now: OffsetDateTime
+) -> Result<(TimeInForce, Option<OffsetDateTime>), &'static str> {
+    if expiry_days.is_some_and(|days| days <= 0) {
+        return Err("Expiry days must be positive");
+    }
+    let expiry = expires_at.or(expiry_days.map(|days| now + Duration::days(days as i64)));
+    let time_in_force = time_in_force.unwrap_or(if expiry.is_some() { TimeInForce::Gtd } else { TimeInForce::Gtc });
+    match (time_in_force, expiry) {
+        (TimeInForce::Gtd, None) => Err("GTD orders need expires_at or expiry_days"),
+        (TimeInForce::Gtd, Some(expiry)) if expiry <= now => Err("Expiry must be in the future"),
+        (TimeInForce::Gtd, Some(expiry)) => Ok((TimeInForce::Gtd, Some(expiry))),
+        (_, Some(_)) => Err("Only GTD orders take an expiry"),
+        (time_in_force, None) => Ok((time_in_force, None)),
+    }
+}
+
+///
+/// Iceberg orders rest on the book and show a slice smaller than their full size
+pub fn check_display_amount(
+    display_amount: Option<i32>,
+    amount: i32,
+    time_in_force: Option<TimeInForce>
+) -> Result<(), &'static str> {
+    let Some(display_amount) = display_amount else {
+        return Ok(());
+    };
+    if display_amount <= 0 {
+        Err("Display amount must be positive")
+    } else if display_amount >= amount {
+        Err("Display amount must be smaller than the order amount")
+    } else if time_in_force.is_some_and(|time_in_force| !time_in_force.rests()) {
+        Err("Only GTC and GTD orders can be iceberg orders")
+    } else {
+        Ok(())
+    }
+}
+
+///
+/// Client order ids are 1 to 64 ASCII letters, digits, `-`, `_`, `.` or `:` so they fit in a URL path
+pub fn check_client_order_id(client_order_id: Option<&str>) -> Result<(), &'static str> {
+    let Some(client_order_id) = client_order_id else {
+        return Ok(());
+    };
+    if client_order_id.is_empty() || client_order_id.len() > 64 {
+        Err("Client order id must be 1 to 64 characters long")
+    } else if !client_order_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')) {
+        Err("Client order id may only contain ASCII letters, digits, '-', '_', '.' and ':'")
+    } else {
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct BuyOrderQueryParams {
-    pub count: i64
+    pub count: i64,
+    pub status: Option<OrderStatus>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SellOrderQueryParams {
-    pub count: i64
+    pub count: i64,
+    pub status: Option<OrderStatus>,
+}
+
+pub const DEFAULT_ORDER_BOOK_DEPTH: i64 = 20;
+pub const MAX_ORDER_BOOK_DEPTH: i64 = 100;
+
+///
+/// `depth` is the number of price levels per side
+#[derive(Serialize, Deserialize)]
+pub struct OrderBookQueryParams {
+    pub depth: Option<i64>,
+}
+
+pub const DEFAULT_TRADE_PAGE_SIZE: i64 = 50;
+pub const MAX_TRADE_PAGE_SIZE: i64 = 500;
+
+///
+/// `from` is inclusive and `to` exclusive. `cursor` is the `next_cursor` of the previous page
+#[derive(Serialize, Deserialize, Default)]
+pub struct TradeQueryParams {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    pub cursor: Option<i32>,
+    pub limit: Option<i64>,
+}
+
+pub const MAX_CANDLES: i64 = 1000;
+
+///
+/// `from` is inclusive and `to` exclusive, both compared with the bucket start.
+/// At most MAX_CANDLES of the most recent buckets in range are returned
+#[derive(Serialize, Deserialize)]
+pub struct CandleQueryParams {
+    pub interval: CandleInterval,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+}
+
+pub const MAX_CANDLE_BACKFILL_DAYS: i64 = 31;
+
+///
+/// Every closed bucket of every interval overlapping `from`..`to` is rebuilt from recorded exchanges.
+/// Buckets still open are left to live trading
+#[derive(Serialize, Deserialize)]
+pub struct BackfillCandlesRequest {
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackfillCandlesResponse {
+    pub candles_written: u64,
+}
+
+///
+/// Fields left out keep their current value. `amount` is the new total size of the order
+#[derive(Serialize, Deserialize)]
+pub struct AmendOrderRequest {
+    pub amount: Option<i32>,
+    pub limit_price: Option<f64>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+///
+/// Takes liquidity at the best available prices instead of resting on the book.
+/// `amount` is in the currency bought for a market buy and in the currency sold for a market sell.
+/// `max_slippage_percent` bounds how far any fill may be from the best price when the order arrives,
+/// `max_spend` caps the total paid in `sell_currency_id` and only applies to market buys.
+/// Without a `self_trade_prevention` mode the account setting applies
+#[derive(Serialize, Deserialize)]
+pub struct MarketOrderRequest {
+    pub buy_currency_id: i32,
+    pub sell_currency_id: i32,
+    pub amount: i32,
+    pub max_slippage_percent: Option<f64>,
+    pub max_spend: Option<i32>,
+    #[serde(default)]
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+///
+/// Stop order on `base_currency_id`/`quote_currency_id` for `amount` of the base currency.
+/// With a `limit_price` it places a GTC limit order once triggered, otherwise it executes as a market order
+#[derive(Serialize, Deserialize)]
+pub struct CreateStopOrderRequest {
+    pub side: OrderSide,
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub amount: i32,
+    pub stop_price: f64,
+    pub limit_price: Option<f64>,
+}
+
+///
+/// `pair` limits a mass cancel to one currency pair given as `BASE/QUOTE` codes, e.g. `EUR/USD`
+#[derive(Serialize, Deserialize)]
+pub struct MassCancelQueryParams {
+    pub pair: Option<String>,
+}
+
+///
+/// Base and quote currency codes of a `BASE/QUOTE` pair
+pub fn parse_currency_pair(pair: &str) -> Result<(&str, &str), &'static str> {
+    match pair.split_once('/') {
+        Some((base, quote)) if !base.is_empty() && !quote.is_empty() && !quote.contains('/') => Ok((base, quote)),
+        _ => Err("Currency pair must look like BASE/QUOTE"),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TradingHaltRequest {
+    pub mode: TradingHaltMode,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+///
+/// One operation of an order batch, tagged by `action`
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BatchOrderItem {
+    CreateBuy(CreateBuyOrderRequest),
+    CreateSell(CreateSellOrderRequest),
+    CancelBuy { buy_order_id: i32 },
+    CancelSell { sell_order_id: i32 },
+}
+
+///
+/// Orders to place and cancel in one request, in the given order.
+/// An `atomic` batch applies every item or none of them, otherwise each item succeeds or fails on its own
+#[derive(Serialize, Deserialize)]
+pub struct BatchOrderRequest {
+    #[serde(default)]
+    pub atomic: bool,
+    pub items: Vec<BatchOrderItem>,
 }
 
+///
+/// One-cancels-other pair on `base_currency_id`/`quote_currency_id` for `amount` of the base currency:
+/// a GTC limit order at `limit_price` and a stop order at `stop_price`, both on `side`.
+/// The stop places a limit order at `stop_limit_price` when given and a market order otherwise
 #[derive(Serialize, Deserialize)]
-pub struct ExchangeCurrencyRequest {
-    pub sum: i32,
-    pub rate: f32,
-    pub order_issuer_id: i32,
-    pub incoming_currency_id: i32,
-    pub outgoing_currency_id: i32,
+pub struct CreateOcoOrderRequest {
+    pub side: OrderSide,
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub amount: i32,
+    pub limit_price: f64,
+    pub stop_price: f64,
+    pub stop_limit_price: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StopOrderQueryParams {
+    pub status: Option<StopOrderStatus>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BuyCurrencyRequest {
+    pub sell_order_id: i32,
+    pub amount: i32,
+    #[serde(default)]
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SellCurrencyRequest {
+    pub buy_order_id: i32,
+    pub amount: i32,
+    #[serde(default)]
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TradingSettingsRequest {
+    pub self_trade_prevention: SelfTradePrevention,
 }
 
 impl CreateUserResponse {
@@ -190,55 +462,543 @@ impl AddCurrencyRequest {
 }
 
 impl CreateBuyOrderRequest {
-    pub fn new<I: Into<i32>>(
+    pub fn new<I: Into<i32>, F: Into<f64>>(
         issuer_id: I,
         buy_amount: I,
         buy_currency_id: I,
         sell_currency_id: I,
-        expiry_days: I
+        limit_price: F,
+        expiry_days: Option<i32>
     ) -> Self {
         Self {
             issuer_id: issuer_id.into(),
             buy_amount: buy_amount.into(),
             buy_currency_id: buy_currency_id.into(),
             sell_currency_id: sell_currency_id.into(),
-            expiry_days: expiry_days.into()
+            limit_price: limit_price.into(),
+            expiry_days,
+            time_in_force: None,
+            expires_at: None,
+            display_amount: None,
+            client_order_id: None,
+            self_trade_prevention: None
         }
     }
+
+    pub fn with_time_in_force(self, time_in_force: Option<TimeInForce>, expires_at: Option<OffsetDateTime>) -> Self {
+        Self { time_in_force, expires_at, ..self }
+    }
+
+    pub fn with_display_amount(self, display_amount: Option<i32>) -> Self {
+        Self { display_amount, ..self }
+    }
+
+    pub fn with_client_order_id(self, client_order_id: Option<String>) -> Self {
+        Self { client_order_id, ..self }
+    }
+
+    pub fn with_self_trade_prevention(self, self_trade_prevention: Option<SelfTradePrevention>) -> Self {
+        Self { self_trade_prevention, ..self }
+    }
+
+    pub fn expiry(&self, now: OffsetDateTime) -> Result<(TimeInForce, Option<OffsetDateTime>), &'static str> {
+        order_expiry(self.time_in_force, self.expires_at, self.expiry_days, now)
+    }
+
+    pub fn check_display_amount(&self) -> Result<(), &'static str> {
+        check_display_amount(self.display_amount, self.buy_amount, self.time_in_force)
+    }
+
+    pub fn check_client_order_id(&self) -> Result<(), &'static str> {
+        check_client_order_id(self.client_order_id.as_deref())
+    }
+
+    ///
+    /// Every check a new limit order has to pass before it is stored
+    pub fn check(&self, now: OffsetDateTime) -> Result<(), &'static str> {
+        if self.buy_amount <= 0 {
+            return Err("Amount must be positive");
+        }
+        if self.limit_price <= 0.0 {
+            return Err("Limit price must be positive");
+        }
+        self.expiry(now)?;
+        self.check_display_amount()?;
+        self.check_client_order_id()
+    }
 }
 
 impl CreateSellOrderRequest {
-    pub fn new<I: Into<i32>>(
+    pub fn new<I: Into<i32>, F: Into<f64>>(
         issuer_id: I,
         sell_amount: I,
         sell_currency_id: I,
         buy_currency_id: I,
-        expiry_days: I
+        limit_price: F,
+        expiry_days: Option<i32>
     ) -> Self {
         Self {
             issuer_id: issuer_id.into(),
             sell_amount: sell_amount.into(),
             sell_currency_id: sell_currency_id.into(),
             buy_currency_id: buy_currency_id.into(),
-            expiry_days: expiry_days.into()
+            limit_price: limit_price.into(),
+            expiry_days,
+            time_in_force: None,
+            expires_at: None,
+            display_amount: None,
+            client_order_id: None,
+            self_trade_prevention: None
         }
     }
+
+    pub fn with_time_in_force(self, time_in_force: Option<TimeInForce>, expires_at: Option<OffsetDateTime>) -> Self {
+        Self { time_in_force, expires_at, ..self }
+    }
+
+    pub fn with_display_amount(self, display_amount: Option<i32>) -> Self {
+        Self { display_amount, ..self }
+    }
+
+    pub fn with_client_order_id(self, client_order_id: Option<String>) -> Self {
+        Self { client_order_id, ..self }
+    }
+
+    pub fn with_self_trade_prevention(self, self_trade_prevention: Option<SelfTradePrevention>) -> Self {
+        Self { self_trade_prevention, ..self }
+    }
+
+    pub fn expiry(&self, now: OffsetDateTime) -> Result<(TimeInForce, Option<OffsetDateTime>), &'static str> {
+        order_expiry(self.time_in_force, self.expires_at, self.expiry_days, now)
+    }
+
+    pub fn check_display_amount(&self) -> Result<(), &'static str> {
+        check_display_amount(self.display_amount, self.sell_amount, self.time_in_force)
+    }
+
+    pub fn check_client_order_id(&self) -> Result<(), &'static str> {
+        check_client_order_id(self.client_order_id.as_deref())
+    }
+
+    ///
+    /// Every check a new limit order has to pass before it is stored
+    pub fn check(&self, now: OffsetDateTime) -> Result<(), &'static str> {
+        if self.sell_amount <= 0 {
+            return Err("Amount must be positive");
+        }
+        if self.limit_price <= 0.0 {
+            return Err("Limit price must be positive");
+        }
+        self.expiry(now)?;
+        self.check_display_amount()?;
+        self.check_client_order_id()
+    }
 }
 
-impl ExchangeCurrencyRequest {
-    pub fn new<I: Into<i32>, F: Into<f32>>(
-        sum: I,
-        rate: F,
-        order_issuer_id: I,
-        incoming_currency_id: I,
-        outgoing_currency_id: I,
-    ) -> Self {
+impl BuyCurrencyRequest {
+    pub fn new<I: Into<i32>>(sell_order_id: I, amount: I) -> Self {
+        Self {
+            sell_order_id: sell_order_id.into(),
+            amount: amount.into(),
+            self_trade_prevention: None
+        }
+    }
+
+    pub fn with_self_trade_prevention(self, self_trade_prevention: Option<SelfTradePrevention>) -> Self {
+        Self { self_trade_prevention, ..self }
+    }
+}
+
+impl SellCurrencyRequest {
+    pub fn new<I: Into<i32>>(buy_order_id: I, amount: I) -> Self {
         Self {
-            sum: sum.into(),
-            rate: rate.into(),
-            order_issuer_id: order_issuer_id.into(),
-            incoming_currency_id: incoming_currency_id.into(),
-            outgoing_currency_id: outgoing_currency_id.into()
+            buy_order_id: buy_order_id.into(),
+            amount: amount.into(),
+            self_trade_prevention: None
+        }
+    }
+
+    pub fn with_self_trade_prevention(self, self_trade_prevention: Option<SelfTradePrevention>) -> Self {
+        Self { self_trade_prevention, ..self }
+    }
+}
+
+impl AmendOrderRequest {
+    pub fn new(amount: Option<i32>, limit_price: Option<f64>, expires_at: Option<OffsetDateTime>) -> Self {
+        Self { amount, limit_price, expires_at }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.amount.is_none() && self.limit_price.is_none() && self.expires_at.is_none()
+    }
+
+    ///
+    /// Growing the order or repricing it sends it to the back of its price level,
+    /// shrinking it or moving the expiry keeps its place
+    pub fn loses_priority(&self, current_amount: i32, current_price: f64) -> bool {
+        self.amount.is_some_and(|amount| amount > current_amount)
+            || self.limit_price.is_some_and(|price| price != current_price)
+    }
+}
+
+impl MarketOrderRequest {
+    ///
+    /// Worst price a fill may have, given the best price on the opposite side of the book.
+    /// Unbounded when no slippage limit was requested
+    pub fn price_bound(&self, best_price: f64, side: OrderSide) -> Option<f64> {
+        self.max_slippage_percent.map(|percent| match side {
+            OrderSide::Buy => best_price * (1.0 + percent / 100.0),
+            OrderSide::Sell => best_price * (1.0 - percent / 100.0),
+        })
+    }
+}
+
+impl CreateStopOrderRequest {
+    ///
+    /// Rejects malformed stops and stops the last trade price has already set off
+    pub fn check(&self, last_price: Option<f64>) -> Result<(), &'static str> {
+        let message = if self.amount <= 0 {
+            "Amount must be positive"
+        } else if self.base_currency_id == self.quote_currency_id {
+            "Base and quote currency must differ"
+        } else if self.stop_price <= 0.0 {
+            "Stop price must be positive"
+        } else if self.limit_price.is_some_and(|price| price <= 0.0) {
+            "Limit price must be positive"
+        } else if last_price.is_some_and(|price| stop_crossed(self.side, self.stop_price, price)) {
+            "Stop price has already been reached"
+        } else {
+            return Ok(());
+        };
+        Err(message)
+    }
+}
+
+impl BatchOrderItem {
+    ///
+    /// Orders created by `issuer_id` must be issued by that user and valid on their own
+    pub fn check(&self, issuer_id: i32, now: OffsetDateTime) -> Result<(), &'static str> {
+        match self {
+            BatchOrderItem::CreateBuy(request) if request.issuer_id != issuer_id => Err("Invalid user id"),
+            BatchOrderItem::CreateBuy(request) => request.check(now),
+            BatchOrderItem::CreateSell(request) if request.issuer_id != issuer_id => Err("Invalid user id"),
+            BatchOrderItem::CreateSell(request) => request.check(now),
+            BatchOrderItem::CancelBuy { .. } | BatchOrderItem::CancelSell { .. } => Ok(()),
+        }
+    }
+}
+
+impl BatchOrderRequest {
+    pub fn check(&self, max_batch_size: usize) -> Result<(), String> {
+        if self.items.is_empty() {
+            Err("Batch must contain at least one item".to_string())
+        } else if self.items.len() > max_batch_size {
+            Err(format!("Batch of {} items exceeds the limit of {}", self.items.len(), max_batch_size))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl CreateOcoOrderRequest {
+    ///
+    /// Sell pairs take profit above the stop and buy pairs below it, the stop may not have been reached yet
+    pub fn check(&self, last_price: Option<f64>) -> Result<(), &'static str> {
+        self.stop_leg().check(last_price)?;
+        let message = if self.limit_price <= 0.0 {
+            "Limit price must be positive"
+        } else if self.side == OrderSide::Sell && self.limit_price <= self.stop_price {
+            "Sell limit price must be above the stop price"
+        } else if self.side == OrderSide::Buy && self.limit_price >= self.stop_price {
+            "Buy limit price must be below the stop price"
+        } else {
+            return Ok(());
+        };
+        Err(message)
+    }
+
+    pub fn stop_leg(&self) -> CreateStopOrderRequest {
+        CreateStopOrderRequest {
+            side: self.side,
+            base_currency_id: self.base_currency_id,
+            quote_currency_id: self.quote_currency_id,
+            amount: self.amount,
+            stop_price: self.stop_price,
+            limit_price: self.stop_limit_price,
+        }
+    }
+}
+
+impl OrderBookQueryParams {
+    pub fn new(depth: Option<i64>) -> Self {
+        Self { depth }
+    }
+
+    ///
+    /// Requested depth clamped to 1..=MAX_ORDER_BOOK_DEPTH
+    pub fn depth(&self) -> i64 {
+        self.depth
+            .unwrap_or(DEFAULT_ORDER_BOOK_DEPTH)
+            .clamp(1, MAX_ORDER_BOOK_DEPTH)
+    }
+}
+
+impl BackfillCandlesRequest {
+    ///
+    /// The range must be non-empty and span at most MAX_CANDLE_BACKFILL_DAYS
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.from >= self.to {
+            Err("Range start must be before its end")
+        } else if self.to - self.from > Duration::days(MAX_CANDLE_BACKFILL_DAYS) {
+            Err("Range may span at most 31 days")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl TradeQueryParams {
+    ///
+    /// Requested page size clamped to 1..=MAX_TRADE_PAGE_SIZE
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_TRADE_PAGE_SIZE)
+            .clamp(1, MAX_TRADE_PAGE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod api_models_spec {
+    use time::{Duration, OffsetDateTime};
+    use crate::datasource::api_models::{check_client_order_id, check_display_amount, order_expiry, parse_currency_pair, AmendOrderRequest, BackfillCandlesRequest, BatchOrderItem, BatchOrderRequest, CreateBuyOrderRequest, CreateOcoOrderRequest, CreateSellOrderRequest, CreateStopOrderRequest, MarketOrderRequest, OrderBookQueryParams, TradeQueryParams, DEFAULT_ORDER_BOOK_DEPTH, DEFAULT_TRADE_PAGE_SIZE, MAX_ORDER_BOOK_DEPTH, MAX_TRADE_PAGE_SIZE};
+    use crate::datasource::models::{OrderSide, TimeInForce};
+
+    fn buy_order(limit_price: f64) -> CreateBuyOrderRequest {
+        CreateBuyOrderRequest::new(1, 10, 1, 2, limit_price, None)
+    }
+
+    fn market_order(max_slippage_percent: Option<f64>) -> MarketOrderRequest {
+        MarketOrderRequest { buy_currency_id: 1, sell_currency_id: 2, amount: 100, max_slippage_percent, max_spend: None, self_trade_prevention: None }
+    }
+
+    fn batch(items: usize) -> BatchOrderRequest {
+        BatchOrderRequest {
+            atomic: false,
+            items: (0..items).map(|id| BatchOrderItem::CancelBuy { buy_order_id: id as i32 }).collect(),
+        }
+    }
+
+    fn stop_order(side: OrderSide, stop_price: f64) -> CreateStopOrderRequest {
+        CreateStopOrderRequest {
+            side,
+            base_currency_id: 1,
+            quote_currency_id: 2,
+            amount: 10,
+            stop_price,
+            limit_price: None,
+        }
+    }
+
+    fn oco_order(side: OrderSide, limit_price: f64, stop_price: f64) -> CreateOcoOrderRequest {
+        CreateOcoOrderRequest {
+            side,
+            base_currency_id: 1,
+            quote_currency_id: 2,
+            amount: 10,
+            limit_price,
+            stop_price,
+            stop_limit_price: None,
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_limit_backfill_range() {
+        let to = OffsetDateTime::now_utc();
+        let backfill = |days| BackfillCandlesRequest { from: to - Duration::days(days), to };
+        assert!(backfill(1).check().is_ok());
+        assert!(backfill(31).check().is_ok());
+        assert!(backfill(32).check().is_err());
+        assert!(backfill(0).check().is_err());
+        assert!(backfill(-1).check().is_err());
+    }
+
+    #[test]
+    fn should_keep_priority_when_reducing_size() {
+        let request = AmendOrderRequest::new(Some(40), None, None);
+        assert!(!request.loses_priority(50, 1.1));
+    }
+
+    #[test]
+    fn should_lose_priority_when_increasing_size_or_repricing() {
+        assert!(AmendOrderRequest::new(Some(60), None, None).loses_priority(50, 1.1));
+        assert!(AmendOrderRequest::new(None, Some(1.2), None).loses_priority(50, 1.1));
+        assert!(!AmendOrderRequest::new(Some(50), Some(1.1), None).loses_priority(50, 1.1));
+    }
+
+    #[test]
+    fn should_use_default_depth_when_missing() {
+        assert_eq!(OrderBookQueryParams::new(None).depth(), DEFAULT_ORDER_BOOK_DEPTH);
+    }
+
+    #[test]
+    fn should_clamp_depth() {
+        assert_eq!(OrderBookQueryParams::new(Some(0)).depth(), 1);
+        assert_eq!(OrderBookQueryParams::new(Some(10_000)).depth(), MAX_ORDER_BOOK_DEPTH);
+        assert_eq!(OrderBookQueryParams::new(Some(5)).depth(), 5);
+    }
+
+    #[test]
+    fn should_clamp_page_size() {
+        assert_eq!(TradeQueryParams::default().limit(), DEFAULT_TRADE_PAGE_SIZE);
+        let params = TradeQueryParams { limit: Some(100_000), ..Default::default() };
+        assert_eq!(params.limit(), MAX_TRADE_PAGE_SIZE);
+    }
+
+    #[test]
+    fn should_bound_price_away_from_best() {
+        let bound = market_order(Some(2.0)).price_bound(1.5, OrderSide::Buy).unwrap();
+        assert!((bound - 1.53).abs() < 1e-9);
+        let bound = market_order(Some(2.0)).price_bound(1.5, OrderSide::Sell).unwrap();
+        assert!((bound - 1.47).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_not_bound_price_without_slippage_limit() {
+        assert_eq!(market_order(None).price_bound(1.5, OrderSide::Buy), None);
+    }
+
+    #[test]
+    fn should_default_to_gtd_with_expiry_and_gtc_without() {
+        let now = OffsetDateTime::now_utc();
+        assert_eq!(order_expiry(None, None, Some(3), now), Ok((TimeInForce::Gtd, Some(now + Duration::days(3)))));
+        assert_eq!(order_expiry(None, None, None, now), Ok((TimeInForce::Gtc, None)));
+    }
+
+    #[test]
+    fn should_prefer_exact_timestamp_over_days() {
+        let now = OffsetDateTime::now_utc();
+        let expires_at = now + Duration::minutes(90);
+        assert_eq!(order_expiry(Some(TimeInForce::Gtd), Some(expires_at), Some(3), now), Ok((TimeInForce::Gtd, Some(expires_at))));
+    }
+
+    #[test]
+    fn should_reject_invalid_expiry() {
+        let now = OffsetDateTime::now_utc();
+        assert!(order_expiry(Some(TimeInForce::Gtd), None, None, now).is_err());
+        assert!(order_expiry(Some(TimeInForce::Gtd), Some(now - Duration::minutes(1)), None, now).is_err());
+        assert!(order_expiry(Some(TimeInForce::Ioc), None, Some(1), now).is_err());
+        assert!(order_expiry(None, None, Some(0), now).is_err());
+    }
+
+    #[test]
+    fn should_reject_non_positive_order_amounts() {
+        let now = OffsetDateTime::now_utc();
+        assert!(buy_order(1.5).check(now).is_ok());
+        assert!(CreateBuyOrderRequest::new(1, 0, 1, 2, 1.5, None).check(now).is_err());
+        assert!(CreateBuyOrderRequest::new(1, -10, 1, 2, 1.5, None).check(now).is_err());
+        assert!(CreateSellOrderRequest::new(1, 10, 1, 2, 1.5, None).check(now).is_ok());
+        assert!(CreateSellOrderRequest::new(1, 0, 1, 2, 1.5, None).check(now).is_err());
+        assert!(CreateSellOrderRequest::new(1, -10, 1, 2, 1.5, None).check(now).is_err());
+    }
+
+    #[test]
+    fn should_accept_display_slice_smaller_than_resting_order() {
+        assert!(check_display_amount(None, 100, Some(TimeInForce::Ioc)).is_ok());
+        assert!(check_display_amount(Some(10), 100, None).is_ok());
+        assert!(check_display_amount(Some(10), 100, Some(TimeInForce::Gtd)).is_ok());
+    }
+
+    #[test]
+    fn should_reject_invalid_display_slice() {
+        assert!(check_display_amount(Some(0), 100, None).is_err());
+        assert!(check_display_amount(Some(100), 100, None).is_err());
+        assert!(check_display_amount(Some(10), 100, Some(TimeInForce::Fok)).is_err());
+    }
+
+    #[test]
+    fn should_accept_missing_or_url_safe_client_order_ids() {
+        assert!(check_client_order_id(None).is_ok());
+        assert!(check_client_order_id(Some("bot-1:order_42")).is_ok());
+        assert!(check_client_order_id(Some(&"x".repeat(64))).is_ok());
+    }
+
+    #[test]
+    fn should_reject_empty_long_or_unsafe_client_order_ids() {
+        assert!(check_client_order_id(Some("")).is_err());
+        assert!(check_client_order_id(Some(&"x".repeat(65))).is_err());
+        assert!(check_client_order_id(Some("order 42")).is_err());
+        assert!(check_client_order_id(Some("bot/42")).is_err());
+        assert!(check_client_order_id(Some("zamówienie")).is_err());
+    }
+
+    #[test]
+    fn should_limit_batch_size() {
+        assert!(batch(0).check(10).is_err());
+        assert!(batch(10).check(10).is_ok());
+        assert!(batch(11).check(10).is_err());
+    }
+
+    #[test]
+    fn should_reject_batch_orders_of_other_users() {
+        let now = OffsetDateTime::now_utc();
+        let item = BatchOrderItem::CreateBuy(buy_order(1.5));
+        assert!(item.check(1, now).is_ok());
+        assert!(item.check(2, now).is_err());
+        let item = BatchOrderItem::CreateBuy(buy_order(0.0));
+        assert!(item.check(1, now).is_err());
+    }
+
+    #[test]
+    fn should_split_base_and_quote() {
+        assert_eq!(parse_currency_pair("EUR/USD"), Ok(("EUR", "USD")));
+    }
+
+    #[test]
+    fn should_reject_malformed_pairs() {
+        assert!(parse_currency_pair("EURUSD").is_err());
+        assert!(parse_currency_pair("EUR/").is_err());
+        assert!(parse_currency_pair("/USD").is_err());
+        assert!(parse_currency_pair("EUR/USD/GBP").is_err());
+    }
+
+    #[test]
+    fn should_accept_stops_away_from_last_price() {
+        assert!(stop_order(OrderSide::Buy, 1.2).check(Some(1.0)).is_ok());
+        assert!(stop_order(OrderSide::Sell, 0.8).check(Some(1.0)).is_ok());
+        assert!(stop_order(OrderSide::Sell, 0.8).check(None).is_ok());
+    }
+
+    #[test]
+    fn should_reject_stops_already_reached() {
+        assert!(stop_order(OrderSide::Buy, 1.0).check(Some(1.0)).is_err());
+        assert!(stop_order(OrderSide::Sell, 1.1).check(Some(1.0)).is_err());
+    }
+
+    #[test]
+    fn should_reject_malformed_stops() {
+        assert!(stop_order(OrderSide::Buy, 0.0).check(None).is_err());
+        let mut same_pair = stop_order(OrderSide::Buy, 1.2);
+        same_pair.quote_currency_id = 1;
+        assert!(same_pair.check(None).is_err());
+        let mut bad_limit = stop_order(OrderSide::Buy, 1.2);
+        bad_limit.limit_price = Some(-1.0);
+        assert!(bad_limit.check(None).is_err());
+    }
+
+    #[test]
+    fn should_accept_take_profit_beyond_stop_loss() {
+        assert!(oco_order(OrderSide::Sell, 1.2, 0.9).check(Some(1.0)).is_ok());
+        assert!(oco_order(OrderSide::Buy, 0.9, 1.2).check(Some(1.0)).is_ok());
+    }
+
+    #[test]
+    fn should_reject_oco_legs_on_the_wrong_side_of_each_other() {
+        assert!(oco_order(OrderSide::Sell, 0.9, 1.2).check(None).is_err());
+        assert!(oco_order(OrderSide::Buy, 1.2, 0.9).check(None).is_err());
+    }
+
+    #[test]
+    fn should_reject_oco_stop_already_reached() {
+        assert!(oco_order(OrderSide::Sell, 1.2, 0.9).check(Some(0.85)).is_err());
+    }
+}
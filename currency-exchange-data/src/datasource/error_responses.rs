@@ -26,11 +26,51 @@ pub struct CreateSellOrderResponse {
     message: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CreateStopOrderResponse {
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateOcoOrderResponse {
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OrderBatchFailedResponse {
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TradingHaltFailedResponse {
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TradingSettingsFailedResponse {
+    message: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct OrdersNotFoundResponse {
     message: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct ExchangeFailedResponse {
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OrderUpdateFailedResponse {
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CandleBackfillFailedResponse {
+    message: String,
+}
+
 impl WalletNotFoundResponse {
     pub fn new<S: Into<String>>(message: S) -> Self {
         Self { message: message.into()}
@@ -49,6 +89,18 @@ impl BalanceNotFoundResponse {
     }
 }
 
+impl CreateStopOrderResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
+impl CreateOcoOrderResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
 impl CreateBuyOrderResponse {
     pub fn new<S: Into<String>>(message: S) -> Self {
         Self { message: message.into()}
@@ -61,8 +113,44 @@ impl CreateSellOrderResponse {
     }
 }
 
+impl OrderBatchFailedResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
+impl TradingHaltFailedResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
+impl TradingSettingsFailedResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
 impl OrdersNotFoundResponse {
     pub fn new<S: Into<String>>(message: S) -> Self {
         Self { message: message.into()}
     }
+}
+
+impl ExchangeFailedResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
+impl OrderUpdateFailedResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
+}
+
+impl CandleBackfillFailedResponse {
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self { message: message.into()}
+    }
 }
\ No newline at end of file
@@ -12,4 +12,32 @@ pub enum DataError {
     WalletBalanceError(String),
     #[error("{0}")]
     CurrencyExchangeError(String),
+    #[error("{0}")]
+    OrderNotFoundError(String),
+    #[error("{0}")]
+    OrderStatusError(String),
+    #[error("{0}")]
+    OrderOwnershipError(String),
+    #[error("{0}")]
+    OrderAmendmentError(String),
+    #[error("{0}")]
+    OrderCreationError(String),
+    #[error("{0}")]
+    OpenOrderLimitError(String),
+    #[error("{0}")]
+    MarketDataError(String),
+    #[error("{0}")]
+    FillOrKillError(String),
+    #[error("{0}")]
+    DuplicateClientOrderIdError(String),
+    #[error("{0}")]
+    OrderBatchError(String),
+    #[error("{0}")]
+    TradingHaltedError(String),
+    #[error("{0}")]
+    TradingHaltNotFoundError(String),
+    #[error("{0}")]
+    TradingSettingsError(String),
+    #[error("{0}")]
+    InsufficientFundsError(String),
 }
\ No newline at end of file
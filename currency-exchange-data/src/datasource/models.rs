@@ -30,36 +30,296 @@ pub struct CurrencyAmount {
     pub amount: Option<i32>,
 }
 
-#[derive(Serialize, Deserialize, FromRow, Debug)]
+///
+/// Lifecycle of a buy or sell order. Filled, cancelled and expired are terminal
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(type_name = "order_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Expired,
+}
+
+///
+/// How long an order stays on the book. GTD orders expire at `expires_at`, GTC orders never do.
+/// IOC and FOK orders never rest: whatever does not fill on arrival is cancelled,
+/// and a FOK order fills completely or not at all
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(type_name = "time_in_force", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    Gtc,
+    Gtd,
+    Ioc,
+    Fok,
+}
+
+impl TimeInForce {
+    pub fn rests(&self) -> bool {
+        matches!(self, TimeInForce::Gtc | TimeInForce::Gtd)
+    }
+}
+
+///
+/// What happens when an order would trade against another order of the same user.
+/// The newest order is the one taking liquidity, the oldest the one resting on the book.
+/// `Decrement` shrinks both by the amount they would have traded, cancelling whichever runs out
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[sqlx(type_name = "self_trade_prevention", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTradePrevention {
+    #[default]
+    CancelNewest,
+    CancelOldest,
+    CancelBoth,
+    Decrement,
+}
+
+impl SelfTradePrevention {
+    ///
+    /// Whether the taking order stops matching once it meets its own resting order
+    pub fn cancels_taker(&self) -> bool {
+        matches!(self, SelfTradePrevention::CancelNewest | SelfTradePrevention::CancelBoth)
+    }
+
+    pub fn cancels_maker(&self) -> bool {
+        matches!(self, SelfTradePrevention::CancelOldest | SelfTradePrevention::CancelBoth)
+    }
+}
+
+///
+/// Side of the book an order rests on
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(type_name = "order_side", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+///
+/// Bucket width of a candle
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(type_name = "candle_interval")]
+pub enum CandleInterval {
+    #[sqlx(rename = "1m")]
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[sqlx(rename = "5m")]
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[sqlx(rename = "1h")]
+    #[serde(rename = "1h")]
+    OneHour,
+    #[sqlx(rename = "1d")]
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl OrderStatus {
+    ///
+    /// Whether the order can still be matched or filled
+    pub fn is_live(&self) -> bool {
+        matches!(self, OrderStatus::Open | OrderStatus::PartiallyFilled)
+    }
+
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        match self {
+            OrderStatus::Open => next != OrderStatus::Open,
+            OrderStatus::PartiallyFilled => next != OrderStatus::Open,
+            OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Expired => false,
+        }
+    }
+
+    ///
+    /// Status an order moves to once a fill leaves `remaining_amount` unfilled
+    pub fn after_fill(remaining_amount: i32) -> OrderStatus {
+        if remaining_amount > 0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Filled
+        }
+    }
+
+    ///
+    /// Status once self-trade prevention shrinks a live order to `remaining_amount` without filling it
+    pub fn after_decrement(&self, remaining_amount: i32) -> OrderStatus {
+        if remaining_amount > 0 {
+            *self
+        } else {
+            OrderStatus::Cancelled
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
 #[serde_as]
 pub struct BuyOrder {
     pub buy_order_id: Option<i32>,
     pub issuer_id: Option<i32>,
     pub buy_currency_amount: Option<i32>,
+    pub filled_amount: Option<i32>,
+    pub remaining_amount: Option<i32>,
+    pub status: Option<OrderStatus>,
     #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
     pub created_at: Option<OffsetDateTime>,
     #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
     pub updated_at: Option<OffsetDateTime>,
     #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
     pub expires_at: Option<OffsetDateTime>,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub priority_at: Option<OffsetDateTime>,
     pub buy_currency_id: Option<i32>,
     pub sell_currency_id: Option<i32>,
+    pub limit_price: Option<f64>,
+    pub time_in_force: Option<TimeInForce>,
+    pub order_group_id: Option<i32>,
+    ///
+    /// Slice size shown in the book for iceberg orders, `None` for fully displayed ones
+    pub display_amount: Option<i32>,
+    ///
+    /// Part of the current iceberg slice that has not filled yet
+    pub visible_amount: Option<i32>,
+    ///
+    /// Caller-chosen id, unique per user across buy and sell orders
+    pub client_order_id: Option<String>,
+    ///
+    /// Applied when the order takes liquidity from another order of its issuer
+    pub self_trade_prevention: Option<SelfTradePrevention>,
 }
 
-#[derive(Serialize, Deserialize, FromRow, Debug)]
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
 #[serde_as]
 pub struct SellOrder {
     pub sell_order_id: Option<i32>,
     pub issuer_id: Option<i32>,
     pub sell_currency_amount: Option<i32>,
+    pub filled_amount: Option<i32>,
+    pub remaining_amount: Option<i32>,
+    pub status: Option<OrderStatus>,
     #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
     pub created_at: Option<OffsetDateTime>,
     #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
     pub updated_at: Option<OffsetDateTime>,
     #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
     pub expires_at: Option<OffsetDateTime>,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub priority_at: Option<OffsetDateTime>,
     pub sell_currency_id: Option<i32>,
-    pub buy_currency_id: Option<i32>
+    pub buy_currency_id: Option<i32>,
+    pub limit_price: Option<f64>,
+    pub time_in_force: Option<TimeInForce>,
+    pub order_group_id: Option<i32>,
+    ///
+    /// Slice size shown in the book for iceberg orders, `None` for fully displayed ones
+    pub display_amount: Option<i32>,
+    ///
+    /// Part of the current iceberg slice that has not filled yet
+    pub visible_amount: Option<i32>,
+    ///
+    /// Caller-chosen id, unique per user across buy and sell orders
+    pub client_order_id: Option<String>,
+    ///
+    /// Applied when the order takes liquidity from another order of its issuer
+    pub self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+impl BuyOrder {
+    ///
+    /// Most a taker can fill against the order at once, the current slice for iceberg orders
+    pub fn shown_amount(&self) -> i32 {
+        shown_amount(self.remaining_amount.unwrap_or(0), self.visible_amount)
+    }
+
+    ///
+    /// The order as seen by `viewer_id`: anyone but the issuer only sees the current slice of an iceberg order
+    pub fn view_for(mut self, viewer_id: i32) -> Self {
+        if self.display_amount.is_some() && self.issuer_id != Some(viewer_id) {
+            let shown = self.shown_amount();
+            self.buy_currency_amount = Some(self.filled_amount.unwrap_or(0) + shown);
+            self.remaining_amount = Some(shown);
+            self.display_amount = None;
+            self.visible_amount = None;
+        }
+        self
+    }
+
+    ///
+    /// Applies a fill of `amount` to a copy of the order held in memory, as the repository does to the stored one
+    pub fn apply_fill(&mut self, amount: i32, now: OffsetDateTime) {
+        let remaining_amount = self.remaining_amount.unwrap_or(0) - amount;
+        let (visible_amount, refreshed) = next_visible_amount(self.display_amount, self.visible_amount, amount, remaining_amount);
+        self.filled_amount = Some(self.filled_amount.unwrap_or(0) + amount);
+        self.remaining_amount = Some(remaining_amount);
+        self.status = Some(OrderStatus::after_fill(remaining_amount));
+        self.visible_amount = visible_amount;
+        if refreshed {
+            self.priority_at = Some(now);
+        }
+    }
+}
+
+impl SellOrder {
+    ///
+    /// Most a taker can fill against the order at once, the current slice for iceberg orders
+    pub fn shown_amount(&self) -> i32 {
+        shown_amount(self.remaining_amount.unwrap_or(0), self.visible_amount)
+    }
+
+    ///
+    /// The order as seen by `viewer_id`: anyone but the issuer only sees the current slice of an iceberg order
+    pub fn view_for(mut self, viewer_id: i32) -> Self {
+        if self.display_amount.is_some() && self.issuer_id != Some(viewer_id) {
+            let shown = self.shown_amount();
+            self.sell_currency_amount = Some(self.filled_amount.unwrap_or(0) + shown);
+            self.remaining_amount = Some(shown);
+            self.display_amount = None;
+            self.visible_amount = None;
+        }
+        self
+    }
+
+    ///
+    /// Applies a fill of `amount` to a copy of the order held in memory, as the repository does to the stored one
+    pub fn apply_fill(&mut self, amount: i32, now: OffsetDateTime) {
+        let remaining_amount = self.remaining_amount.unwrap_or(0) - amount;
+        let (visible_amount, refreshed) = next_visible_amount(self.display_amount, self.visible_amount, amount, remaining_amount);
+        self.filled_amount = Some(self.filled_amount.unwrap_or(0) + amount);
+        self.remaining_amount = Some(remaining_amount);
+        self.status = Some(OrderStatus::after_fill(remaining_amount));
+        self.visible_amount = visible_amount;
+        if refreshed {
+            self.priority_at = Some(now);
+        }
+    }
+}
+
+fn shown_amount(remaining_amount: i32, visible_amount: Option<i32>) -> i32 {
+    visible_amount.map_or(remaining_amount, |visible_amount| visible_amount.min(remaining_amount))
+}
+
+///
+/// Slice of an iceberg order left on show after `amount` of it fills, `remaining_amount` being what is left
+/// of the whole order. A fill that uses up the slice replenishes it from the hidden quantity, which sends
+/// the order to the back of its price level; the flag tells whether that happened
+pub fn next_visible_amount(
+    display_amount: Option<i32>,
+    visible_amount: Option<i32>,
+    amount: i32,
+    remaining_amount: i32
+) -> (Option<i32>, bool) {
+    let Some(display_amount) = display_amount else {
+        return (None, false);
+    };
+    let left = visible_amount.unwrap_or(display_amount) - amount;
+    if left > 0 {
+        (Some(left.min(remaining_amount)), false)
+    } else {
+        (Some(display_amount.min(remaining_amount)), remaining_amount > 0)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -69,11 +329,13 @@ pub struct Wallet {
     pub currency_id: i32
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CurrencyBalance {
     pub user_id: Option<i32>,
     pub wallet_id: Option<i32>,
     pub amount: Option<i32>,
+    pub held: Option<i32>,
+    pub available: Option<i32>,
     pub currency_id: Option<i32>
 }
 
@@ -113,4 +375,603 @@ pub struct CurrencyAmountQuery {
     pub amount: Option<i32>,
     pub currency_id: Option<i32>,
     pub wallet_id: Option<i32>,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, FromRow, Debug)]
+#[serde_as]
+pub struct CurrencyExchangeRecord {
+    pub id: i32,
+    pub income: Option<f64>,
+    pub sell_order_id: Option<i32>,
+    pub buy_order_id: Option<i32>,
+    pub amount: i32,
+    pub exchange_rate: f64,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub created_at: Option<OffsetDateTime>,
+    pub buyer_id: Option<i32>,
+    pub seller_id: Option<i32>,
+    pub base_currency_id: Option<i32>,
+    pub quote_currency_id: Option<i32>,
+    pub taker_side: Option<OrderSide>,
+}
+///
+/// Match between two orders of the same user that was resolved by its self-trade prevention
+/// mode instead of trading. The order id of a taker trading without an order is `None`
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[serde_as]
+pub struct PreventedTrade {
+    pub prevented_trade_id: i32,
+    pub user_id: i32,
+    pub mode: SelfTradePrevention,
+    pub taker_side: OrderSide,
+    pub buy_order_id: Option<i32>,
+    pub sell_order_id: Option<i32>,
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub amount: i32,
+    pub exchange_rate: f64,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub created_at: OffsetDateTime,
+}
+
+///
+/// Result of pairing two orders: a trade, or a self-trade that was prevented
+#[derive(Debug)]
+pub enum MatchOutcome {
+    Traded(CurrencyExchangeRecord),
+    Prevented(PreventedTrade),
+}
+
+///
+/// Trades an order executed while matching and the self-trades it was kept from
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MatchReport {
+    pub exchanges: Vec<CurrencyExchangeRecord>,
+    pub prevented_trades: Vec<PreventedTrade>,
+}
+
+impl MatchReport {
+    pub fn push(&mut self, outcome: MatchOutcome) {
+        match outcome {
+            MatchOutcome::Traded(exchange) => self.exchanges.push(exchange),
+            MatchOutcome::Prevented(prevented) => self.prevented_trades.push(prevented),
+        }
+    }
+
+    ///
+    /// Number of matches, traded or prevented
+    pub fn matches(&self) -> usize {
+        self.exchanges.len() + self.prevented_trades.len()
+    }
+}
+
+///
+/// Per-account trading preferences. Orders placed without a self-trade prevention mode take this one
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[serde_as]
+pub struct TradingSettings {
+    pub user_id: i32,
+    pub self_trade_prevention: SelfTradePrevention,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Debug)]
+#[serde_as]
+pub struct Fill {
+    pub fill_id: i32,
+    pub exchange_id: i32,
+    pub buy_order_id: Option<i32>,
+    pub sell_order_id: Option<i32>,
+    pub amount: i32,
+    pub exchange_rate: f64,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub created_at: OffsetDateTime,
+}
+
+///
+/// Resting liquidity at one limit price. `size` is the unfilled amount of the base currency
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+pub struct PriceLevel {
+    pub price: f64,
+    pub size: i64,
+    pub order_count: i64,
+}
+
+///
+/// Aggregated book for a currency pair, bids and asks both sorted best price first
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderBook {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+}
+
+///
+/// Execution on the public tape. `amount` is in the base currency, `quote_amount` in the quote currency.
+/// Maker is the resting order, taker the order or execute request that crossed it
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[serde_as]
+pub struct Trade {
+    pub trade_id: i32,
+    pub base_currency_id: Option<i32>,
+    pub quote_currency_id: Option<i32>,
+    pub price: f64,
+    pub amount: i32,
+    pub quote_amount: Option<f64>,
+    pub buy_order_id: Option<i32>,
+    pub sell_order_id: Option<i32>,
+    pub maker_order_id: Option<i32>,
+    pub taker_order_id: Option<i32>,
+    pub taker_side: Option<OrderSide>,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl From<&CurrencyExchangeRecord> for Trade {
+    fn from(exchange: &CurrencyExchangeRecord) -> Self {
+        let (maker_order_id, taker_order_id) = match exchange.taker_side {
+            Some(OrderSide::Buy) => (exchange.sell_order_id, exchange.buy_order_id),
+            Some(OrderSide::Sell) => (exchange.buy_order_id, exchange.sell_order_id),
+            None => (None, None),
+        };
+        Self {
+            trade_id: exchange.id,
+            base_currency_id: exchange.base_currency_id,
+            quote_currency_id: exchange.quote_currency_id,
+            price: exchange.exchange_rate,
+            amount: exchange.amount,
+            quote_amount: exchange.income,
+            buy_order_id: exchange.buy_order_id,
+            sell_order_id: exchange.sell_order_id,
+            maker_order_id,
+            taker_order_id,
+            taker_side: exchange.taker_side,
+            created_at: exchange.created_at,
+        }
+    }
+}
+
+impl Trade {
+    ///
+    /// The trade as seen by the counterparty on `side`
+    pub fn for_side(&self, side: OrderSide) -> UserTrade {
+        let order_id = match side {
+            OrderSide::Buy => self.buy_order_id,
+            OrderSide::Sell => self.sell_order_id,
+        };
+        UserTrade {
+            trade_id: self.trade_id,
+            base_currency_id: self.base_currency_id,
+            quote_currency_id: self.quote_currency_id,
+            price: self.price,
+            amount: self.amount,
+            quote_amount: self.quote_amount,
+            side,
+            order_id,
+            is_maker: self.taker_side.map(|taker_side| taker_side != side),
+            created_at: self.created_at,
+        }
+    }
+}
+
+///
+/// Execution seen from one of its counterparties
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[serde_as]
+pub struct UserTrade {
+    pub trade_id: i32,
+    pub base_currency_id: Option<i32>,
+    pub quote_currency_id: Option<i32>,
+    pub price: f64,
+    pub amount: i32,
+    pub quote_amount: Option<f64>,
+    pub side: OrderSide,
+    pub order_id: Option<i32>,
+    pub is_maker: Option<bool>,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+///
+/// OHLCV bar for one bucket. Buckets without trades have no candle.
+/// `volume` is in the base currency, `quote_volume` in the quote currency
+#[derive(Serialize, Deserialize, FromRow, Debug)]
+#[serde_as]
+pub struct Candle {
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub interval: CandleInterval,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub bucket_start: OffsetDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+    pub quote_volume: f64,
+    pub trade_count: i32,
+}
+
+///
+/// Market snapshot for a pair. The 24h figures cover executions in the last 24 hours and
+/// `change_percent_24h` compares the last price with the first one in that window
+#[derive(Serialize, Deserialize, FromRow, Debug)]
+pub struct Ticker {
+    pub base_currency_id: i32,
+    pub base_currency: Option<String>,
+    pub quote_currency_id: i32,
+    pub quote_currency: Option<String>,
+    pub last_price: Option<f64>,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub high_24h: Option<f64>,
+    pub low_24h: Option<f64>,
+    pub volume_24h: i64,
+    pub quote_volume_24h: f64,
+    pub trade_count_24h: i64,
+    pub change_percent_24h: Option<f64>,
+}
+
+///
+/// Outcome of a market order, one trade per resting order it filled.
+/// `quote_amount` is what was paid for a buy and what was received for a sell
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarketOrderResult {
+    pub side: OrderSide,
+    pub requested_amount: i32,
+    pub filled_amount: i32,
+    pub quote_amount: f64,
+    pub average_price: Option<f64>,
+    pub trades: Vec<Trade>,
+    ///
+    /// Resting orders of the taker the market order met instead of trading
+    #[serde(default)]
+    pub prevented_trades: Vec<PreventedTrade>,
+}
+
+impl MarketOrderResult {
+    pub fn new(side: OrderSide, requested_amount: i32, exchanges: &[CurrencyExchangeRecord]) -> Self {
+        let filled_amount: i32 = exchanges.iter().map(|exchange| exchange.amount).sum();
+        let quote_amount: f64 = exchanges.iter().map(|exchange| exchange.income.unwrap_or_default()).sum();
+        let notional: f64 = exchanges.iter().map(|exchange| exchange.amount as f64 * exchange.exchange_rate).sum();
+        let average_price = (filled_amount > 0).then(|| notional / filled_amount as f64);
+        Self {
+            side,
+            requested_amount,
+            filled_amount,
+            quote_amount,
+            average_price,
+            trades: exchanges.iter().map(Trade::from).collect(),
+            prevented_trades: Vec::new(),
+        }
+    }
+
+    pub fn with_prevented_trades(self, prevented_trades: Vec<PreventedTrade>) -> Self {
+        Self { prevented_trades, ..self }
+    }
+}
+
+///
+/// Lifecycle of a stop order. A pending stop moves to triggered once the last trade price crosses
+/// its stop price, or to rejected when the order it places cannot be accepted
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(type_name = "stop_order_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum StopOrderStatus {
+    Pending,
+    Triggered,
+    Cancelled,
+    Rejected,
+}
+
+///
+/// Dormant order that enters the book once the pair trades at `stop_price` or through it.
+/// Stop-limit orders carry a `limit_price` and place a limit order, stop-market orders sweep the book.
+/// `buy_order_id`/`sell_order_id` point at the limit order a triggered stop-limit placed
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[serde_as]
+pub struct StopOrder {
+    pub stop_order_id: i32,
+    pub issuer_id: i32,
+    pub side: OrderSide,
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub amount: i32,
+    pub stop_price: f64,
+    pub limit_price: Option<f64>,
+    pub status: StopOrderStatus,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub created_at: OffsetDateTime,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub updated_at: OffsetDateTime,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub triggered_at: Option<OffsetDateTime>,
+    pub trigger_price: Option<f64>,
+    pub buy_order_id: Option<i32>,
+    pub sell_order_id: Option<i32>,
+    pub exchange_id: Option<i32>,
+    pub reject_reason: Option<String>,
+    pub order_group_id: Option<i32>,
+}
+
+impl StopOrder {
+    ///
+    /// Buy stops trigger when the price rises to the stop price, sell stops when it falls to it
+    pub fn is_triggered_by(&self, price: f64) -> bool {
+        stop_crossed(self.side, self.stop_price, price)
+    }
+}
+
+///
+/// Orders linked so that one executing cancels the rest. A one-cancels-other group holds a limit
+/// order and a stop order: the first fill of the limit order cancels the stop, the stop triggering
+/// cancels whatever is left of the limit order
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OrderGroup {
+    pub order_group_id: i32,
+    pub buy_orders: Vec<BuyOrder>,
+    pub sell_orders: Vec<SellOrder>,
+    pub stop_orders: Vec<StopOrder>,
+}
+
+///
+/// Working orders of a user: live limit orders and pending stop orders
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserOrders {
+    pub buy_orders: Vec<BuyOrder>,
+    pub sell_orders: Vec<SellOrder>,
+    pub stop_orders: Vec<StopOrder>,
+}
+
+///
+/// What a trading halt does to the orders already on the pair:
+/// `Cancel` cancels them, `Freeze` keeps them on the book without matching until trading resumes
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(type_name = "trading_halt_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TradingHaltMode {
+    Cancel,
+    Freeze,
+}
+
+///
+/// Halt on a currency pair, in both directions. No orders are placed, amended or filled on
+/// the pair until `resumed_at` is set
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[serde_as]
+pub struct TradingHalt {
+    pub trading_halt_id: i32,
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub mode: TradingHaltMode,
+    pub reason: Option<String>,
+    pub halted_by: i32,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub halted_at: OffsetDateTime,
+    #[serde_as(as = "serde_with::TimestampSecondsWithFrac<String>")]
+    pub resumed_at: Option<OffsetDateTime>,
+}
+
+///
+/// New halt and the orders it cancelled, which are none for frozen pairs
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TradingHaltResult {
+    pub halt: TradingHalt,
+    pub cancelled_orders: UserOrders,
+}
+
+///
+/// Outcome of one batch item: the order it created or cancelled, or why it failed
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BatchOrderResult {
+    pub buy_order: Option<BuyOrder>,
+    pub sell_order: Option<SellOrder>,
+    pub error: Option<String>,
+}
+
+///
+/// Results of an order batch, one per item in request order
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BatchOrderResponse {
+    pub atomic: bool,
+    pub results: Vec<BatchOrderResult>,
+}
+
+///
+/// Order a user placed under a client order id, on whichever side it was placed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientOrder {
+    pub buy_order: Option<BuyOrder>,
+    pub sell_order: Option<SellOrder>,
+}
+
+///
+/// Whether a trade at `price` sets off a stop on `side` at `stop_price`
+pub fn stop_crossed(side: OrderSide, stop_price: f64, price: f64) -> bool {
+    match side {
+        OrderSide::Buy => price >= stop_price,
+        OrderSide::Sell => price <= stop_price,
+    }
+}
+
+///
+/// One page of trades, newest first. Pass `next_cursor` as `cursor` to fetch the next page
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TradePage<T> {
+    pub trades: Vec<T>,
+    pub next_cursor: Option<i32>,
+}
+
+impl<T> TradePage<T> {
+    ///
+    /// `next_cursor` is only set when the page is full, so a short page marks the end
+    pub fn new(trades: Vec<T>, limit: i64, trade_id: impl Fn(&T) -> i32) -> Self {
+        let next_cursor = if trades.len() as i64 >= limit {
+            trades.last().map(&trade_id)
+        } else {
+            None
+        };
+        Self { trades, next_cursor }
+    }
+}
+
+#[cfg(test)]
+mod models_spec {
+    use crate::datasource::models::{next_visible_amount, stop_crossed, CurrencyExchangeRecord, MarketOrderResult, OrderSide, OrderStatus, SelfTradePrevention, Trade, TradePage};
+
+    fn trade(taker_side: Option<OrderSide>) -> Trade {
+        Trade {
+            trade_id: 1,
+            base_currency_id: Some(1),
+            quote_currency_id: Some(2),
+            price: 1.1,
+            amount: 10,
+            quote_amount: Some(11.0),
+            buy_order_id: Some(3),
+            sell_order_id: Some(4),
+            maker_order_id: None,
+            taker_order_id: None,
+            taker_side,
+            created_at: None,
+        }
+    }
+
+    fn exchange(id: i32, amount: i32, exchange_rate: f64) -> CurrencyExchangeRecord {
+        CurrencyExchangeRecord {
+            id,
+            income: Some((amount as f64 * exchange_rate).round()),
+            sell_order_id: Some(id),
+            buy_order_id: None,
+            amount,
+            exchange_rate,
+            created_at: None,
+            buyer_id: Some(1),
+            seller_id: Some(2),
+            base_currency_id: Some(1),
+            quote_currency_id: Some(2),
+            taker_side: Some(OrderSide::Buy),
+        }
+    }
+
+    #[test]
+    fn should_allow_live_transitions() {
+        assert!(OrderStatus::Open.can_transition_to(OrderStatus::PartiallyFilled));
+        assert!(OrderStatus::Open.can_transition_to(OrderStatus::Cancelled));
+        assert!(OrderStatus::PartiallyFilled.can_transition_to(OrderStatus::PartiallyFilled));
+        assert!(OrderStatus::PartiallyFilled.can_transition_to(OrderStatus::Filled));
+        assert!(OrderStatus::PartiallyFilled.can_transition_to(OrderStatus::Expired));
+    }
+
+    #[test]
+    fn should_reject_transitions_out_of_terminal_states() {
+        assert!(!OrderStatus::Filled.can_transition_to(OrderStatus::Cancelled));
+        assert!(!OrderStatus::Cancelled.can_transition_to(OrderStatus::Open));
+        assert!(!OrderStatus::Expired.can_transition_to(OrderStatus::PartiallyFilled));
+        assert!(!OrderStatus::PartiallyFilled.can_transition_to(OrderStatus::Open));
+    }
+
+    #[test]
+    fn should_set_cursor_only_for_full_pages() {
+        let full = TradePage::new(vec![9, 8, 7], 3, |id| *id);
+        assert_eq!(full.next_cursor, Some(7));
+        let last = TradePage::new(vec![6, 5], 3, |id| *id);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn should_pick_order_and_liquidity_for_side() {
+        let buyer = trade(Some(OrderSide::Sell)).for_side(OrderSide::Buy);
+        assert_eq!(buyer.order_id, Some(3));
+        assert_eq!(buyer.is_maker, Some(true));
+        let seller = trade(Some(OrderSide::Sell)).for_side(OrderSide::Sell);
+        assert_eq!(seller.order_id, Some(4));
+        assert_eq!(seller.is_maker, Some(false));
+    }
+
+    #[test]
+    fn should_leave_liquidity_unknown_without_taker_side() {
+        assert_eq!(trade(None).for_side(OrderSide::Buy).is_maker, None);
+    }
+
+    #[test]
+    fn should_weight_average_price_by_amount() {
+        let result = MarketOrderResult::new(OrderSide::Buy, 500, &[exchange(1, 100, 1.0), exchange(2, 300, 2.0)]);
+        assert_eq!(result.filled_amount, 400);
+        assert_eq!(result.quote_amount, 700.0);
+        assert_eq!(result.average_price, Some(1.75));
+        assert_eq!(result.trades.len(), 2);
+    }
+
+    #[test]
+    fn should_have_no_average_price_without_fills() {
+        let result = MarketOrderResult::new(OrderSide::Sell, 500, &[]);
+        assert_eq!(result.filled_amount, 0);
+        assert_eq!(result.average_price, None);
+    }
+
+    #[test]
+    fn should_trigger_buy_stop_at_or_above_stop_price() {
+        assert!(stop_crossed(OrderSide::Buy, 1.1, 1.1));
+        assert!(stop_crossed(OrderSide::Buy, 1.1, 1.2));
+        assert!(!stop_crossed(OrderSide::Buy, 1.1, 1.05));
+    }
+
+    #[test]
+    fn should_trigger_sell_stop_at_or_below_stop_price() {
+        assert!(stop_crossed(OrderSide::Sell, 0.9, 0.9));
+        assert!(stop_crossed(OrderSide::Sell, 0.9, 0.8));
+        assert!(!stop_crossed(OrderSide::Sell, 0.9, 0.95));
+    }
+
+    #[test]
+    fn should_leave_plain_orders_alone() {
+        assert_eq!(next_visible_amount(None, None, 5, 10), (None, false));
+    }
+
+    #[test]
+    fn should_shrink_slice_without_losing_priority() {
+        assert_eq!(next_visible_amount(Some(10), Some(10), 4, 96), (Some(6), false));
+    }
+
+    #[test]
+    fn should_replenish_used_up_slice_and_lose_priority() {
+        assert_eq!(next_visible_amount(Some(10), Some(6), 6, 90), (Some(10), true));
+        assert_eq!(next_visible_amount(Some(10), Some(10), 10, 4), (Some(4), true));
+    }
+
+    #[test]
+    fn should_show_nothing_once_filled() {
+        assert_eq!(next_visible_amount(Some(10), Some(4), 4, 0), (Some(0), false));
+    }
+
+    #[test]
+    fn should_cap_slice_of_taker_filling_past_it() {
+        assert_eq!(next_visible_amount(Some(10), Some(10), 25, 75), (Some(10), true));
+    }
+
+    #[test]
+    fn should_default_to_cancel_newest() {
+        assert_eq!(SelfTradePrevention::default(), SelfTradePrevention::CancelNewest);
+    }
+
+    #[test]
+    fn should_tell_which_orders_a_mode_cancels() {
+        assert!(SelfTradePrevention::CancelNewest.cancels_taker());
+        assert!(!SelfTradePrevention::CancelNewest.cancels_maker());
+        assert!(!SelfTradePrevention::CancelOldest.cancels_taker());
+        assert!(SelfTradePrevention::CancelOldest.cancels_maker());
+        assert!(SelfTradePrevention::CancelBoth.cancels_taker());
+        assert!(SelfTradePrevention::CancelBoth.cancels_maker());
+        assert!(!SelfTradePrevention::Decrement.cancels_taker());
+        assert!(!SelfTradePrevention::Decrement.cancels_maker());
+    }
+
+    #[test]
+    fn should_cancel_orders_decremented_to_nothing() {
+        assert_eq!(OrderStatus::PartiallyFilled.after_decrement(5), OrderStatus::PartiallyFilled);
+        assert_eq!(OrderStatus::Open.after_decrement(5), OrderStatus::Open);
+        assert_eq!(OrderStatus::Open.after_decrement(0), OrderStatus::Cancelled);
+    }
+}
@@ -0,0 +1,11 @@
+use crate::datasource::api_models::BatchOrderItem;
+use crate::datasource::errors::DataError;
+use crate::datasource::models::BatchOrderResult;
+
+#[async_trait::async_trait]
+pub trait BatchOrderRepository {
+    ///
+    /// Creates and cancels the items of `issuer_id` in one transaction, in request order.
+    /// The first failing item rolls the whole batch back
+    async fn apply_order_batch(&self, issuer_id: &i32, items: &[BatchOrderItem], max_open_orders: i64) -> Result<Vec<BatchOrderResult>, DataError>;
+}
@@ -1,17 +1,82 @@
 use crate::datasource::errors::DataError;
-use crate::datasource::models::{Currency, CurrencyAmount, CurrencyAmountQuery, Wallet};
+use crate::datasource::models::{CurrencyAmount, MatchOutcome, MatchReport, OrderSide, SelfTradePrevention};
 
 #[async_trait::async_trait]
 pub trait CurrencyAmountRepository {
     async fn currency_amount(&self, currency_id: &i32) -> Result<Option<CurrencyAmount>, DataError>;
     
-    async fn exchange_currencies(
-        &self, 
-        sum: i32, 
-        incoming_currency_id: i32, 
-        outgoing_currency_id: i32, 
-        exchange_rate: f32, 
-        incoming_currency_wallet_id: i32, 
-        outgoing_currency_wallet_id: i32
-    ) -> Result<Option<CurrencyAmountQuery>, DataError>;
-}
\ No newline at end of file
+    ///
+    /// Settles a buy order against a sell order in a single transaction
+    /// # Arguments
+    ///
+    /// * `buy_order_id`: buy order to fill
+    /// * `sell_order_id`: sell order to fill
+    /// * `exchange_rate`: units of the buy order's sell currency paid per unit bought
+    /// * `taker_side`: side of the incoming order, the other side is the resting maker
+    ///
+    /// returns: recorded exchange, the prevented self-trade when both orders belong to the same user
+    /// (resolved by the taker's mode), or None if either order has nothing left to fill
+    async fn settle_exchange(
+        &self,
+        buy_order_id: i32,
+        sell_order_id: i32,
+        exchange_rate: f64,
+        taker_side: OrderSide
+    ) -> Result<Option<MatchOutcome>, DataError>;
+
+    ///
+    /// Fills a resting buy order with the taker's currency at the order's limit price
+    /// # Arguments
+    ///
+    /// * `buy_order_id`: buy order to fill
+    /// * `taker_id`: user selling into the order
+    /// * `amount`: requested amount, capped by what is left on the order
+    /// * `self_trade_prevention`: taker's mode when the order is its own, the account setting when `None`
+    ///
+    /// returns: recorded exchange, or the prevented self-trade when the order belongs to the taker
+    async fn fill_buy_order(
+        &self,
+        buy_order_id: i32,
+        taker_id: i32,
+        amount: i32,
+        self_trade_prevention: Option<SelfTradePrevention>
+    ) -> Result<MatchOutcome, DataError>;
+
+    ///
+    /// Fills a resting sell order for the taker at the order's limit price
+    /// # Arguments
+    ///
+    /// * `sell_order_id`: sell order to fill
+    /// * `taker_id`: user buying from the order
+    /// * `amount`: requested amount, capped by what is left on the order
+    /// * `self_trade_prevention`: taker's mode when the order is its own, the account setting when `None`
+    ///
+    /// returns: recorded exchange, or the prevented self-trade when the order belongs to the taker
+    async fn fill_sell_order(
+        &self,
+        sell_order_id: i32,
+        taker_id: i32,
+        amount: i32,
+        self_trade_prevention: Option<SelfTradePrevention>
+    ) -> Result<MatchOutcome, DataError>;
+
+    ///
+    /// Fills a fill-or-kill buy order against crossing sell orders in a single transaction,
+    /// nothing is settled unless the whole remaining amount fills
+    /// # Arguments
+    ///
+    /// * `buy_order_id`: buy order to fill
+    ///
+    /// returns: recorded exchanges and prevented self-trades, or `FillOrKillError` when the book cannot fill the order
+    async fn fill_or_kill_buy_order(&self, buy_order_id: i32) -> Result<MatchReport, DataError>;
+
+    ///
+    /// Fills a fill-or-kill sell order against crossing buy orders in a single transaction,
+    /// nothing is settled unless the whole remaining amount fills
+    /// # Arguments
+    ///
+    /// * `sell_order_id`: sell order to fill
+    ///
+    /// returns: recorded exchanges and prevented self-trades, or `FillOrKillError` when the book cannot fill the order
+    async fn fill_or_kill_sell_order(&self, sell_order_id: i32) -> Result<MatchReport, DataError>;
+}
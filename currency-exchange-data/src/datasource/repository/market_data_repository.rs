@@ -0,0 +1,36 @@
+use time::OffsetDateTime;
+use crate::datasource::api_models::{CandleQueryParams, TradeQueryParams};
+use crate::datasource::errors::DataError;
+use crate::datasource::models::{Candle, PriceLevel, Ticker, Trade, UserTrade};
+
+#[async_trait::async_trait]
+pub trait MarketDataRepository {
+    ///
+    /// Live buy orders for the pair grouped by limit price, highest price first
+    async fn find_bid_levels(&self, base_currency_id: &i32, quote_currency_id: &i32, depth: i64) -> Result<Vec<PriceLevel>, DataError>;
+
+    ///
+    /// Live sell orders for the pair grouped by limit price, lowest price first
+    async fn find_ask_levels(&self, base_currency_id: &i32, quote_currency_id: &i32, depth: i64) -> Result<Vec<PriceLevel>, DataError>;
+
+    ///
+    /// Executions for the pair matching `params`, newest first
+    async fn find_trades(&self, base_currency_id: &i32, quote_currency_id: &i32, params: &TradeQueryParams) -> Result<Vec<Trade>, DataError>;
+
+    ///
+    /// Executions where the user was buyer or seller matching `params`, newest first
+    async fn find_user_trades(&self, user_id: &i32, params: &TradeQueryParams) -> Result<Vec<UserTrade>, DataError>;
+
+    ///
+    /// Candles for the pair matching `params`, oldest first
+    async fn find_candles(&self, base_currency_id: &i32, quote_currency_id: &i32, params: &CandleQueryParams) -> Result<Vec<Candle>, DataError>;
+
+    ///
+    /// Recomputes every closed candle overlapping `from`..`to` from recorded exchanges, returning the number written.
+    /// Open buckets are skipped since live trades are still upserting them
+    async fn backfill_candles(&self, base_currency_id: &i32, quote_currency_id: &i32, from: OffsetDateTime, to: OffsetDateTime) -> Result<u64, DataError>;
+
+    ///
+    /// Tickers for every ordered pair of registered currencies, narrowed to one base or quote when given
+    async fn find_tickers(&self, base_currency_id: Option<i32>, quote_currency_id: Option<i32>) -> Result<Vec<Ticker>, DataError>;
+}
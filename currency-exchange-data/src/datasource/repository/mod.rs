@@ -3,4 +3,10 @@ pub mod user_repository;
 pub mod wallet_repository;
 pub mod currency_repository;
 pub mod order_repository;
-pub mod currency_amount_repository;
\ No newline at end of file
+pub mod currency_amount_repository;
+pub mod market_data_repository;
+pub mod stop_order_repository;
+pub mod order_group_repository;
+pub mod batch_order_repository;
+pub mod trading_halt_repository;
+pub mod trading_settings_repository;
\ No newline at end of file
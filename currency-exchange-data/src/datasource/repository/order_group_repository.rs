@@ -0,0 +1,17 @@
+use crate::datasource::api_models::CreateOcoOrderRequest;
+use crate::datasource::errors::DataError;
+use crate::datasource::models::OrderGroup;
+
+#[async_trait::async_trait]
+pub trait OrderGroupRepository {
+    ///
+    /// Stores both legs of a one-cancels-other pair in one transaction:
+    /// the GTC limit order, counted against `max_open_orders`, and the pending stop order
+    async fn create_oco_order(&self, issuer_id: &i32, request: &CreateOcoOrderRequest, max_open_orders: i64) -> Result<OrderGroup, DataError>;
+
+    async fn find_order_group(&self, order_group_id: &i32) -> Result<Option<OrderGroup>, DataError>;
+
+    ///
+    /// Cancels every leg of the group that is still working, the group must belong to `issuer_id`
+    async fn cancel_order_group(&self, order_group_id: &i32, issuer_id: &i32) -> Result<OrderGroup, DataError>;
+}
@@ -1,13 +1,113 @@
-use crate::datasource::api_models::{CreateBuyOrderRequest, CreateSellOrderRequest};
+use crate::datasource::api_models::{AmendOrderRequest, CreateBuyOrderRequest, CreateSellOrderRequest};
 use crate::datasource::errors::DataError;
-use crate::datasource::models::{BuyOrder, SellOrder};
+use crate::datasource::models::{BuyOrder, ClientOrder, Fill, OrderStatus, SellOrder, UserOrders};
 
 #[async_trait::async_trait]
 pub trait OrderRepository {
-    async fn find_buy_orders<S : Into<i64> + Send>(&self, limit: S) -> Result<Vec<BuyOrder>, DataError>;
+    ///
+    /// Buy orders in `status`, or every live order when no status is given
+    async fn find_buy_orders<S : Into<i64> + Send>(&self, limit: S, status: Option<OrderStatus>) -> Result<Vec<BuyOrder>, DataError>;
 
-    async fn find_sell_orders<S: Into<i64> + Send>(&self, limit: S) -> Result<Vec<SellOrder>, DataError>;
+    ///
+    /// Sell orders in `status`, or every live order when no status is given
+    async fn find_sell_orders<S: Into<i64> + Send>(&self, limit: S, status: Option<OrderStatus>) -> Result<Vec<SellOrder>, DataError>;
 
-    async fn create_buy_order(&self, req: &CreateBuyOrderRequest) -> Result<BuyOrder, DataError>;
-    async fn create_sell_order(&self, req: &CreateSellOrderRequest) -> Result<SellOrder, DataError>;
-}
\ No newline at end of file
+    async fn find_buy_order(&self, buy_order_id: &i32) -> Result<Option<BuyOrder>, DataError>;
+
+    async fn find_sell_order(&self, sell_order_id: &i32) -> Result<Option<SellOrder>, DataError>;
+
+    ///
+    /// Places a buy order unless the issuer already has `max_open_orders` live orders
+    async fn create_buy_order(&self, req: &CreateBuyOrderRequest, max_open_orders: i64) -> Result<BuyOrder, DataError>;
+
+    ///
+    /// Places a sell order unless the issuer already has `max_open_orders` live orders
+    async fn create_sell_order(&self, req: &CreateSellOrderRequest, max_open_orders: i64) -> Result<SellOrder, DataError>;
+
+    ///
+    /// Sell orders priced at or below the buy order's limit, best price first then oldest
+    async fn find_crossing_sell_orders(&self, buy_order: &BuyOrder) -> Result<Vec<SellOrder>, DataError>;
+
+    ///
+    /// Buy orders priced at or above the sell order's limit, best price first then oldest
+    async fn find_crossing_buy_orders(&self, sell_order: &SellOrder) -> Result<Vec<BuyOrder>, DataError>;
+
+    ///
+    /// Live sell orders of `base_currency_id` for `quote_currency_id`, best price first then oldest.
+    /// Only orders priced at or below `max_price` when one is given
+    async fn find_resting_sell_orders(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        max_price: Option<f64>
+    ) -> Result<Vec<SellOrder>, DataError>;
+
+    ///
+    /// Live buy orders of `base_currency_id` paying `quote_currency_id`, best price first then oldest.
+    /// Only orders priced at or above `min_price` when one is given
+    async fn find_resting_buy_orders(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        min_price: Option<f64>
+    ) -> Result<Vec<BuyOrder>, DataError>;
+
+    ///
+    /// Moves the buy order to `status`, rejecting transitions the lifecycle does not allow
+    async fn update_buy_order_status(&self, buy_order_id: &i32, status: OrderStatus) -> Result<BuyOrder, DataError>;
+
+    ///
+    /// Moves the sell order to `status`, rejecting transitions the lifecycle does not allow
+    async fn update_sell_order_status(&self, sell_order_id: &i32, status: OrderStatus) -> Result<SellOrder, DataError>;
+
+    ///
+    /// Cancels a live buy order on behalf of its issuer.
+    /// Balances are only moved at settlement, so nothing is held against the order
+    async fn cancel_buy_order(&self, buy_order_id: &i32, issuer_id: &i32) -> Result<BuyOrder, DataError>;
+
+    ///
+    /// Cancels a live sell order on behalf of its issuer.
+    /// Balances are only moved at settlement, so nothing is held against the order
+    async fn cancel_sell_order(&self, sell_order_id: &i32, issuer_id: &i32) -> Result<SellOrder, DataError>;
+
+    ///
+    /// Changes size, price or expiry of a live buy order on behalf of its issuer.
+    /// Growing or repricing the order resets its time priority
+    async fn amend_buy_order(&self, buy_order_id: &i32, issuer_id: &i32, request: &AmendOrderRequest) -> Result<BuyOrder, DataError>;
+
+    ///
+    /// Changes size, price or expiry of a live sell order on behalf of its issuer.
+    /// Growing or repricing the order resets its time priority
+    async fn amend_sell_order(&self, sell_order_id: &i32, issuer_id: &i32, request: &AmendOrderRequest) -> Result<SellOrder, DataError>;
+
+    ///
+    /// Moves up to `batch_size` live buy orders past their expiry to expired.
+    /// Rows locked by another sweeper are skipped, so several instances can run at once
+    async fn expire_buy_orders(&self, batch_size: i64) -> Result<Vec<BuyOrder>, DataError>;
+
+    ///
+    /// Moves up to `batch_size` live sell orders past their expiry to expired.
+    /// Rows locked by another sweeper are skipped, so several instances can run at once
+    async fn expire_sell_orders(&self, batch_size: i64) -> Result<Vec<SellOrder>, DataError>;
+
+    ///
+    /// Executions against the buy order, oldest first
+    async fn find_buy_order_fills(&self, buy_order_id: &i32) -> Result<Vec<Fill>, DataError>;
+
+    ///
+    /// Executions against the sell order, oldest first
+    async fn find_sell_order_fills(&self, sell_order_id: &i32) -> Result<Vec<Fill>, DataError>;
+
+    ///
+    /// Live buy and sell orders and pending stop orders of the user, newest first
+    async fn find_user_orders(&self, user_id: &i32) -> Result<UserOrders, DataError>;
+
+    ///
+    /// Cancels every live limit order and pending stop order of the user in one transaction,
+    /// only those between the two currencies of `pair` when given, in either direction
+    async fn cancel_user_orders(&self, user_id: &i32, pair: Option<(i32, i32)>) -> Result<UserOrders, DataError>;
+
+    ///
+    /// Buy or sell order the user placed under `client_order_id`
+    async fn find_client_order(&self, user_id: &i32, client_order_id: &str) -> Result<ClientOrder, DataError>;
+}
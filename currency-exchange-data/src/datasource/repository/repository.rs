@@ -1,13 +1,20 @@
-use crate::datasource::api_models::{AddCurrencyRequest, BalanceRequest, CreateBuyOrderRequest, CreateCurrencyRequest, CreateSellOrderRequest, CreateUserRequest, CreateWalletRequest};
+use crate::datasource::api_models::{AddCurrencyRequest, AmendOrderRequest, BalanceRequest, BatchOrderItem, CreateBuyOrderRequest, CreateCurrencyRequest, CreateOcoOrderRequest, CreateSellOrderRequest, CreateStopOrderRequest, CreateUserRequest, CreateWalletRequest, CandleQueryParams, TradeQueryParams, TradingHaltRequest, TradingSettingsRequest, MAX_CANDLES};
 use crate::datasource::errors::DataError;
-use crate::datasource::models::{BuyOrder, Currency, CurrencyAmount, CurrencyAmountQuery, CurrencyBalance, IncomingCurrencyWallet, OutgoingCurrencyWallet, SellOrder, User, Wallet};
+use crate::datasource::models::{BatchOrderResult, BuyOrder, Candle, CandleInterval, ClientOrder, Currency, CurrencyAmount, CurrencyBalance, CurrencyExchangeRecord, Fill, IncomingCurrencyWallet, MatchOutcome, MatchReport, next_visible_amount, OrderGroup, OrderSide, OrderStatus, OutgoingCurrencyWallet, PreventedTrade, PriceLevel, SelfTradePrevention, SellOrder, StopOrder, StopOrderStatus, Ticker, TimeInForce, Trade, TradingHalt, TradingHaltMode, TradingHaltResult, TradingSettings, User, UserOrders, UserTrade, Wallet};
 use crate::datasource::repository::currency_repository::CurrencyRepository;
 use crate::datasource::repository::user_repository::UserRepository;
 use crate::datasource::repository::wallet_repository::WalletRepository;
-use sqlx::PgPool;
-use time::{Duration, OffsetDateTime};
+use std::cmp::Ordering;
+use sqlx::{PgConnection, PgPool};
+use time::OffsetDateTime;
+use crate::datasource::repository::batch_order_repository::BatchOrderRepository;
 use crate::datasource::repository::currency_amount_repository::CurrencyAmountRepository;
+use crate::datasource::repository::market_data_repository::MarketDataRepository;
+use crate::datasource::repository::order_group_repository::OrderGroupRepository;
 use crate::datasource::repository::order_repository::OrderRepository;
+use crate::datasource::repository::stop_order_repository::StopOrderRepository;
+use crate::datasource::repository::trading_halt_repository::TradingHaltRepository;
+use crate::datasource::repository::trading_settings_repository::TradingSettingsRepository;
 
 pub struct Repository {
     pool: PgPool
@@ -38,6 +45,882 @@ impl Repository {
             .await
             .expect("Error currency querying")
     }
+
+    async fn settlement_wallet_id(
+        conn: &mut PgConnection,
+        user_id: i32,
+        currency_id: i32
+    ) -> Result<i32, DataError> {
+        let wallet_id = sqlx::query_scalar!(
+            "SELECT wallet_id FROM wallets WHERE user_id = $1 AND currency_id = $2 ORDER BY wallet_id LIMIT 1",
+            user_id, currency_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        wallet_id.ok_or(DataError::WalletNotFoundError(
+            format!("Wallet for user with id={} and currency with id={} not found", user_id, currency_id)
+        ))
+    }
+
+    async fn debit_wallet(
+        conn: &mut PgConnection,
+        wallet_id: i32,
+        currency_id: i32,
+        amount: i32
+    ) -> Result<(), DataError> {
+        let result = sqlx::query!(
+            "UPDATE currency_amount SET amount = amount - $1 WHERE wallet_id = $2 AND currency_id = $3 AND amount - held >= $1",
+            amount, wallet_id, currency_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        if result.rows_affected() > 0 {
+            Ok(())
+        } else {
+            Err(DataError::WalletBalanceError(format!("Insufficient funds in wallet with id={}", wallet_id)))
+        }
+    }
+
+    async fn credit_wallet(
+        conn: &mut PgConnection,
+        wallet_id: i32,
+        currency_id: i32,
+        amount: i32
+    ) -> Result<(), DataError> {
+        sqlx::query!(
+            "INSERT INTO currency_amount(amount, currency_id, wallet_id) VALUES ($1, $2, $3)
+            ON CONFLICT (wallet_id, currency_id)
+            DO UPDATE SET amount = COALESCE(currency_amount.amount, 0) + EXCLUDED.amount",
+            amount, currency_id, wallet_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+
+    ///
+    /// Moves `amount` of the user's settlement wallet from available to held balance
+    async fn hold_funds(
+        conn: &mut PgConnection,
+        user_id: i32,
+        currency_id: i32,
+        amount: i32
+    ) -> Result<(), DataError> {
+        let result = sqlx::query!(
+            "UPDATE currency_amount SET held = held + $3
+            WHERE currency_id = $2
+            AND wallet_id = (SELECT wallet_id FROM wallets WHERE user_id = $1 AND currency_id = $2 ORDER BY wallet_id LIMIT 1)
+            AND COALESCE(amount, 0) - held >= $3",
+            user_id, currency_id, amount)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        if result.rows_affected() > 0 {
+            Ok(())
+        } else {
+            Err(DataError::InsufficientFundsError(
+                format!("User with id={} has less than {} of currency with id={} available", user_id, amount, currency_id)
+            ))
+        }
+    }
+
+    ///
+    /// Shrinks the holds of buy orders to what their remaining amount still needs once `filling` more
+    /// is filled, keeping at most what is left after `consumed` is paid, and frees the rest in the wallets.
+    /// Orders that are no longer live keep nothing
+    async fn release_buy_holds(
+        conn: &mut PgConnection,
+        buy_order_ids: &[i32],
+        filling: i32,
+        consumed: i32
+    ) -> Result<(), DataError> {
+        sqlx::query!(
+            "WITH orders AS (
+                SELECT buy_order_id, issuer_id, sell_currency_id AS currency_id, held_amount,
+                    CASE WHEN status IN ('open', 'partially_filled')
+                        THEN GREATEST(LEAST(held_amount - $3,
+                            CEIL(((remaining_amount - $2) * limit_price)::NUMERIC)::INTEGER), 0)
+                        ELSE 0 END AS kept_amount
+                FROM buy_orders WHERE buy_order_id = ANY($1) AND held_amount > 0
+            ), released AS (
+                UPDATE buy_orders SET held_amount = orders.kept_amount
+                FROM orders
+                WHERE buy_orders.buy_order_id = orders.buy_order_id AND orders.kept_amount < orders.held_amount
+                RETURNING orders.issuer_id, orders.currency_id, orders.held_amount - orders.kept_amount AS amount
+            )
+            UPDATE currency_amount SET held = GREATEST(currency_amount.held - totals.amount, 0)
+            FROM (SELECT issuer_id, currency_id, SUM(amount)::INTEGER AS amount FROM released GROUP BY issuer_id, currency_id) AS totals
+            WHERE currency_amount.currency_id = totals.currency_id
+            AND currency_amount.wallet_id = (SELECT wallet_id FROM wallets
+                WHERE wallets.user_id = totals.issuer_id AND wallets.currency_id = totals.currency_id
+                ORDER BY wallet_id LIMIT 1)",
+            buy_order_ids, filling, consumed)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn release_sell_holds(
+        conn: &mut PgConnection,
+        sell_order_ids: &[i32],
+        filling: i32,
+        consumed: i32
+    ) -> Result<(), DataError> {
+        sqlx::query!(
+            "WITH orders AS (
+                SELECT sell_order_id, issuer_id, sell_currency_id AS currency_id, held_amount,
+                    CASE WHEN status IN ('open', 'partially_filled')
+                        THEN GREATEST(LEAST(held_amount - $3, remaining_amount - $2), 0)
+                        ELSE 0 END AS kept_amount
+                FROM sell_orders WHERE sell_order_id = ANY($1) AND held_amount > 0
+            ), released AS (
+                UPDATE sell_orders SET held_amount = orders.kept_amount
+                FROM orders
+                WHERE sell_orders.sell_order_id = orders.sell_order_id AND orders.kept_amount < orders.held_amount
+                RETURNING orders.issuer_id, orders.currency_id, orders.held_amount - orders.kept_amount AS amount
+            )
+            UPDATE currency_amount SET held = GREATEST(currency_amount.held - totals.amount, 0)
+            FROM (SELECT issuer_id, currency_id, SUM(amount)::INTEGER AS amount FROM released GROUP BY issuer_id, currency_id) AS totals
+            WHERE currency_amount.currency_id = totals.currency_id
+            AND currency_amount.wallet_id = (SELECT wallet_id FROM wallets
+                WHERE wallets.user_id = totals.issuer_id AND wallets.currency_id = totals.currency_id
+                ORDER BY wallet_id LIMIT 1)",
+            sell_order_ids, filling, consumed)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+
+    ///
+    /// Holds `growth` more for an amended buy order, or frees what it no longer needs when it shrank
+    async fn resize_buy_hold(conn: &mut PgConnection, buy_order: &BuyOrder, growth: i32) -> Result<(), DataError> {
+        let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+        if growth <= 0 {
+            return Self::release_buy_holds(conn, &[buy_order_id], 0, 0).await;
+        }
+        let issuer_id = buy_order.issuer_id.unwrap_or_default();
+        Self::hold_funds(conn, issuer_id, buy_order.sell_currency_id.unwrap_or_default(), growth).await?;
+        sqlx::query!("UPDATE buy_orders SET held_amount = held_amount + $1 WHERE buy_order_id = $2", growth, buy_order_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn resize_sell_hold(conn: &mut PgConnection, sell_order: &SellOrder, growth: i32) -> Result<(), DataError> {
+        let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+        if growth <= 0 {
+            return Self::release_sell_holds(conn, &[sell_order_id], 0, 0).await;
+        }
+        let issuer_id = sell_order.issuer_id.unwrap_or_default();
+        Self::hold_funds(conn, issuer_id, sell_order.sell_currency_id.unwrap_or_default(), growth).await?;
+        sqlx::query!("UPDATE sell_orders SET held_amount = held_amount + $1 WHERE sell_order_id = $2", growth, sell_order_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn lock_buy_order(conn: &mut PgConnection, buy_order_id: i32) -> Result<BuyOrder, DataError> {
+        sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders WHERE buy_order_id = $1 FOR UPDATE", buy_order_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Buy order with id={} not found", buy_order_id)))
+    }
+
+    async fn lock_sell_order(conn: &mut PgConnection, sell_order_id: i32) -> Result<SellOrder, DataError> {
+        sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders WHERE sell_order_id = $1 FOR UPDATE", sell_order_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Sell order with id={} not found", sell_order_id)))
+    }
+
+    async fn reduce_buy_order(conn: &mut PgConnection, buy_order: &BuyOrder, amount: i32) -> Result<(), DataError> {
+        let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+        let status = Self::next_status(buy_order.status, buy_order.remaining_amount.unwrap_or(0) - amount)
+            .ok_or(DataError::OrderStatusError(format!("Buy order with id={} can no longer be filled", buy_order_id)))?;
+        let (visible_amount, refreshed) = next_visible_amount(
+            buy_order.display_amount,
+            buy_order.visible_amount,
+            amount,
+            buy_order.remaining_amount.unwrap_or(0) - amount
+        );
+        sqlx::query!(
+            "UPDATE buy_orders SET filled_amount = filled_amount + $1, remaining_amount = remaining_amount - $1,
+            status = $2, updated_at = $3, visible_amount = $5,
+            priority_at = CASE WHEN $6 THEN $3 ELSE priority_at END
+            WHERE buy_order_id = $4",
+            amount, status as OrderStatus, OffsetDateTime::now_utc(), buy_order_id, visible_amount, refreshed)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        if let Some(order_group_id) = buy_order.order_group_id {
+            Self::cancel_group_stop_orders(conn, order_group_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn reduce_sell_order(conn: &mut PgConnection, sell_order: &SellOrder, amount: i32) -> Result<(), DataError> {
+        let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+        let status = Self::next_status(sell_order.status, sell_order.remaining_amount.unwrap_or(0) - amount)
+            .ok_or(DataError::OrderStatusError(format!("Sell order with id={} can no longer be filled", sell_order_id)))?;
+        let (visible_amount, refreshed) = next_visible_amount(
+            sell_order.display_amount,
+            sell_order.visible_amount,
+            amount,
+            sell_order.remaining_amount.unwrap_or(0) - amount
+        );
+        sqlx::query!(
+            "UPDATE sell_orders SET filled_amount = filled_amount + $1, remaining_amount = remaining_amount - $1,
+            status = $2, updated_at = $3, visible_amount = $5,
+            priority_at = CASE WHEN $6 THEN $3 ELSE priority_at END
+            WHERE sell_order_id = $4",
+            amount, status as OrderStatus, OffsetDateTime::now_utc(), sell_order_id, visible_amount, refreshed)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        if let Some(order_group_id) = sell_order.order_group_id {
+            Self::cancel_group_stop_orders(conn, order_group_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn transition_buy_order(
+        conn: &mut PgConnection,
+        buy_order: &BuyOrder,
+        status: OrderStatus
+    ) -> Result<BuyOrder, DataError> {
+        let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+        let current = buy_order.status.unwrap_or(OrderStatus::Open);
+        if !current.can_transition_to(status) {
+            return Err(DataError::OrderStatusError(
+                format!("Buy order with id={} cannot move from {:?} to {:?}", buy_order_id, current, status)
+            ));
+        }
+        let updated = sqlx::query_as!(BuyOrder,
+            "UPDATE buy_orders SET status = $1, updated_at = $2 WHERE buy_order_id = $3
+            RETURNING buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            status as OrderStatus, OffsetDateTime::now_utc(), buy_order_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Self::release_buy_holds(conn, &[buy_order_id], 0, 0).await?;
+        Ok(updated)
+    }
+
+    async fn transition_sell_order(
+        conn: &mut PgConnection,
+        sell_order: &SellOrder,
+        status: OrderStatus
+    ) -> Result<SellOrder, DataError> {
+        let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+        let current = sell_order.status.unwrap_or(OrderStatus::Open);
+        if !current.can_transition_to(status) {
+            return Err(DataError::OrderStatusError(
+                format!("Sell order with id={} cannot move from {:?} to {:?}", sell_order_id, current, status)
+            ));
+        }
+        let updated = sqlx::query_as!(SellOrder,
+            "UPDATE sell_orders SET status = $1, updated_at = $2 WHERE sell_order_id = $3
+            RETURNING sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            status as OrderStatus, OffsetDateTime::now_utc(), sell_order_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Self::release_sell_holds(conn, &[sell_order_id], 0, 0).await?;
+        Ok(updated)
+    }
+
+    ///
+    /// Cancels the pending stop orders of a group once another leg has started executing
+    async fn cancel_group_stop_orders(conn: &mut PgConnection, order_group_id: i32) -> Result<(), DataError> {
+        sqlx::query!(
+            "UPDATE stop_orders SET status = 'cancelled', updated_at = $2
+            WHERE order_group_id = $1 AND status = 'pending'",
+            order_group_id, OffsetDateTime::now_utc())
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+
+    ///
+    /// Cancels every leg of a group that is still working
+    async fn cancel_group_legs(conn: &mut PgConnection, order_group_id: i32) -> Result<(), DataError> {
+        let now = OffsetDateTime::now_utc();
+        let buy_order_ids = sqlx::query_scalar!(
+            "UPDATE buy_orders SET status = 'cancelled', updated_at = $2
+            WHERE order_group_id = $1 AND status IN ('open', 'partially_filled')
+            RETURNING buy_order_id",
+            order_group_id, now)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let sell_order_ids = sqlx::query_scalar!(
+            "UPDATE sell_orders SET status = 'cancelled', updated_at = $2
+            WHERE order_group_id = $1 AND status IN ('open', 'partially_filled')
+            RETURNING sell_order_id",
+            order_group_id, now)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Self::release_buy_holds(conn, &buy_order_ids, 0, 0).await?;
+        Self::release_sell_holds(conn, &sell_order_ids, 0, 0).await?;
+        Self::cancel_group_stop_orders(conn, order_group_id).await
+    }
+
+    async fn load_order_group(conn: &mut PgConnection, order_group_id: i32) -> Result<OrderGroup, DataError> {
+        let buy_orders = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders WHERE order_group_id = $1 ORDER BY buy_order_id", order_group_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        let sell_orders = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders WHERE order_group_id = $1 ORDER BY sell_order_id", order_group_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        let stop_orders = sqlx::query_as!(StopOrder,
+            "SELECT stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id
+            FROM stop_orders WHERE order_group_id = $1 ORDER BY stop_order_id", order_group_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        Ok(OrderGroup { order_group_id, buy_orders, sell_orders, stop_orders })
+    }
+
+    ///
+    /// Serialises order placement per user with an advisory lock so concurrent
+    /// requests cannot overshoot `max_open_orders`
+    async fn insert_buy_order(
+        conn: &mut PgConnection,
+        req: &CreateBuyOrderRequest,
+        max_open_orders: i64
+    ) -> Result<BuyOrder, DataError> {
+        let issuer_id = req.issuer_id;
+        let amount = req.buy_amount;
+        let buy_id = req.buy_currency_id;
+        let sell_id = req.sell_currency_id;
+        let limit_price = req.limit_price;
+        let created_at = OffsetDateTime::now_utc();
+        let updated_at = OffsetDateTime::now_utc();
+        let (time_in_force, expiry_total) = req.expiry(created_at)
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Self::check_client_order_id(conn, issuer_id, req.client_order_id.as_deref()).await?;
+        Self::check_trading_halt(conn, buy_id, sell_id).await?;
+        Self::check_open_order_limit(conn, issuer_id, max_open_orders).await?;
+        let held_amount = hold_amount(amount, limit_price);
+        Self::hold_funds(conn, issuer_id, sell_id, held_amount).await?;
+        let result = sqlx::query_as!(BuyOrder,
+            "INSERT INTO buy_orders(issuer_id, buy_currency_amount, remaining_amount, buy_currency_id, sell_currency_id, limit_price, created_at, updated_at, expires_at, priority_at, time_in_force, display_amount, visible_amount, client_order_id, self_trade_prevention, held_amount)
+            VALUES ($1, $2, $2, $3, $4, $5, $6, $7, $8, $6, $9, $10, $10, $11,
+                COALESCE($12, (SELECT self_trade_prevention FROM trading_settings WHERE user_id = $1), 'cancel_newest'), $13)
+            RETURNING buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            issuer_id, amount, buy_id, sell_id, limit_price, created_at, updated_at, expiry_total, time_in_force as TimeInForce, req.display_amount, req.client_order_id,
+            req.self_trade_prevention as Option<SelfTradePrevention>, held_amount)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Ok(result)
+    }
+
+    async fn insert_sell_order(
+        conn: &mut PgConnection,
+        req: &CreateSellOrderRequest,
+        max_open_orders: i64
+    ) -> Result<SellOrder, DataError> {
+        let issuer_id = req.issuer_id;
+        let amount = req.sell_amount;
+        let buy_id = &req.buy_currency_id;
+        let sell_id = &req.sell_currency_id;
+        let limit_price = req.limit_price;
+        let created_at = OffsetDateTime::now_utc();
+        let updated_at = OffsetDateTime::now_utc();
+        let (time_in_force, expiry_total) = req.expiry(created_at)
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Self::check_client_order_id(conn, issuer_id, req.client_order_id.as_deref()).await?;
+        Self::check_trading_halt(conn, *buy_id, *sell_id).await?;
+        Self::check_open_order_limit(conn, issuer_id, max_open_orders).await?;
+        let held_amount = amount;
+        Self::hold_funds(conn, issuer_id, *sell_id, held_amount).await?;
+        let result = sqlx::query_as!(SellOrder,
+            "INSERT INTO sell_orders(issuer_id, sell_currency_amount, remaining_amount, buy_currency_id, sell_currency_id, limit_price, created_at, updated_at, expires_at, priority_at, time_in_force, display_amount, visible_amount, client_order_id, self_trade_prevention, held_amount)
+            VALUES ($1, $2, $2, $3, $4, $5, $6, $7, $8, $6, $9, $10, $10, $11,
+                COALESCE($12, (SELECT self_trade_prevention FROM trading_settings WHERE user_id = $1), 'cancel_newest'), $13)
+            RETURNING sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            issuer_id, amount, buy_id, sell_id, limit_price, created_at, updated_at, expiry_total, time_in_force as TimeInForce, req.display_amount, req.client_order_id,
+            req.self_trade_prevention as Option<SelfTradePrevention>, held_amount)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Ok(result)
+    }
+
+    async fn cancel_owned_buy_order(
+        conn: &mut PgConnection,
+        buy_order_id: i32,
+        issuer_id: i32
+    ) -> Result<BuyOrder, DataError> {
+        let buy_order = Self::lock_buy_order(conn, buy_order_id).await?;
+        if buy_order.issuer_id != Some(issuer_id) {
+            return Err(DataError::OrderOwnershipError(
+                format!("Buy order with id={} does not belong to user with id={}", buy_order_id, issuer_id)
+            ));
+        }
+        let cancelled = Self::transition_buy_order(conn, &buy_order, OrderStatus::Cancelled).await?;
+        if let Some(order_group_id) = cancelled.order_group_id {
+            Self::cancel_group_legs(conn, order_group_id).await?;
+        }
+        Ok(cancelled)
+    }
+
+    async fn cancel_owned_sell_order(
+        conn: &mut PgConnection,
+        sell_order_id: i32,
+        issuer_id: i32
+    ) -> Result<SellOrder, DataError> {
+        let sell_order = Self::lock_sell_order(conn, sell_order_id).await?;
+        if sell_order.issuer_id != Some(issuer_id) {
+            return Err(DataError::OrderOwnershipError(
+                format!("Sell order with id={} does not belong to user with id={}", sell_order_id, issuer_id)
+            ));
+        }
+        let cancelled = Self::transition_sell_order(conn, &sell_order, OrderStatus::Cancelled).await?;
+        if let Some(order_group_id) = cancelled.order_group_id {
+            Self::cancel_group_legs(conn, order_group_id).await?;
+        }
+        Ok(cancelled)
+    }
+
+    ///
+    /// Shrinks a live order by `amount` without filling it, cancelling it once nothing is left
+    async fn decrement_buy_order(conn: &mut PgConnection, buy_order: &BuyOrder, amount: i32) -> Result<(), DataError> {
+        let remaining_amount = buy_order.remaining_amount.unwrap_or(0) - amount;
+        let status = buy_order.status.unwrap_or(OrderStatus::Open).after_decrement(remaining_amount);
+        let (visible_amount, refreshed) = next_visible_amount(buy_order.display_amount, buy_order.visible_amount, amount, remaining_amount);
+        sqlx::query!(
+            "UPDATE buy_orders SET remaining_amount = $1, status = $2, updated_at = $3, visible_amount = $5,
+            priority_at = CASE WHEN $6 THEN $3 ELSE priority_at END
+            WHERE buy_order_id = $4",
+            remaining_amount.max(0), status as OrderStatus, OffsetDateTime::now_utc(), buy_order.buy_order_id,
+            visible_amount, refreshed)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Self::release_buy_holds(conn, &[buy_order.buy_order_id.unwrap_or_default()], 0, 0).await
+    }
+
+    async fn decrement_sell_order(conn: &mut PgConnection, sell_order: &SellOrder, amount: i32) -> Result<(), DataError> {
+        let remaining_amount = sell_order.remaining_amount.unwrap_or(0) - amount;
+        let status = sell_order.status.unwrap_or(OrderStatus::Open).after_decrement(remaining_amount);
+        let (visible_amount, refreshed) = next_visible_amount(sell_order.display_amount, sell_order.visible_amount, amount, remaining_amount);
+        sqlx::query!(
+            "UPDATE sell_orders SET remaining_amount = $1, status = $2, updated_at = $3, visible_amount = $5,
+            priority_at = CASE WHEN $6 THEN $3 ELSE priority_at END
+            WHERE sell_order_id = $4",
+            remaining_amount.max(0), status as OrderStatus, OffsetDateTime::now_utc(), sell_order.sell_order_id,
+            visible_amount, refreshed)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Self::release_sell_holds(conn, &[sell_order.sell_order_id.unwrap_or_default()], 0, 0).await
+    }
+
+    ///
+    /// Self-trade prevention mode of the user's account, used by takers that name none
+    async fn account_self_trade_prevention(conn: &mut PgConnection, user_id: i32) -> Result<SelfTradePrevention, DataError> {
+        let mode = sqlx::query_scalar!(
+            "SELECT self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM trading_settings WHERE user_id = $1",
+            user_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(mode.unwrap_or_default())
+    }
+
+    ///
+    /// Resolves a match between two orders of the same user the way its mode asks instead of trading,
+    /// cancelling or shrinking the orders involved, and records it for the user
+    async fn prevent_self_trade(conn: &mut PgConnection, self_trade: &SelfTradeMatch<'_>) -> Result<PreventedTrade, DataError> {
+        let (user_id, base, quote) = match (self_trade.buy_order, self_trade.sell_order) {
+            (Some(buy_order), _) => (buy_order.issuer_id, buy_order.buy_currency_id, buy_order.sell_currency_id),
+            (None, Some(sell_order)) => (sell_order.issuer_id, sell_order.sell_currency_id, sell_order.buy_currency_id),
+            (None, None) => return Err(DataError::CurrencyExchangeError("Self-trade needs at least one order".to_string())),
+        };
+        let (base, quote) = (base.unwrap_or_default(), quote.unwrap_or_default());
+        Self::check_trading_halt(conn, base, quote).await?;
+        let mode = self_trade.mode;
+        let (cancels_buy, cancels_sell) = match self_trade.taker_side {
+            OrderSide::Buy => (mode.cancels_taker(), mode.cancels_maker()),
+            OrderSide::Sell => (mode.cancels_maker(), mode.cancels_taker()),
+        };
+        if let Some(buy_order) = self_trade.buy_order {
+            if cancels_buy {
+                Self::transition_buy_order(conn, buy_order, OrderStatus::Cancelled).await?;
+                if let Some(order_group_id) = buy_order.order_group_id {
+                    Self::cancel_group_legs(conn, order_group_id).await?;
+                }
+            } else if mode == SelfTradePrevention::Decrement {
+                Self::decrement_buy_order(conn, buy_order, self_trade.amount).await?;
+            }
+        }
+        if let Some(sell_order) = self_trade.sell_order {
+            if cancels_sell {
+                Self::transition_sell_order(conn, sell_order, OrderStatus::Cancelled).await?;
+                if let Some(order_group_id) = sell_order.order_group_id {
+                    Self::cancel_group_legs(conn, order_group_id).await?;
+                }
+            } else if mode == SelfTradePrevention::Decrement {
+                Self::decrement_sell_order(conn, sell_order, self_trade.amount).await?;
+            }
+        }
+        sqlx::query_as!(PreventedTrade,
+            "INSERT INTO prevented_trades(user_id, mode, taker_side, buy_order_id, sell_order_id, base_currency_id, quote_currency_id,
+                amount, exchange_rate, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING prevented_trade_id, user_id, mode as \"mode: SelfTradePrevention\", taker_side as \"taker_side: OrderSide\",
+                buy_order_id, sell_order_id, base_currency_id, quote_currency_id, amount, exchange_rate, created_at",
+            user_id, mode as SelfTradePrevention, self_trade.taker_side as OrderSide,
+            self_trade.buy_order.and_then(|order| order.buy_order_id), self_trade.sell_order.and_then(|order| order.sell_order_id),
+            base, quote, self_trade.amount, self_trade.exchange_rate, OffsetDateTime::now_utc())
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))
+    }
+
+    ///
+    /// A fill-or-kill order that its own mode cancels on meeting a resting order of its issuer keeps none
+    /// of its fills, so the prevention is applied in a transaction of its own once those are rolled back
+    async fn prevent_fill_or_kill_self_trade(
+        &self,
+        buy_order_id: i32,
+        sell_order_id: i32,
+        taker_side: OrderSide
+    ) -> Result<MatchReport, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let buy_order = Self::lock_buy_order(&mut tx, buy_order_id).await?;
+        let sell_order = Self::lock_sell_order(&mut tx, sell_order_id).await?;
+        let (mode, amount, exchange_rate) = match taker_side {
+            OrderSide::Buy => (
+                buy_order.self_trade_prevention,
+                buy_order.remaining_amount.unwrap_or(0).min(sell_order.shown_amount()),
+                sell_order.limit_price
+            ),
+            OrderSide::Sell => (
+                sell_order.self_trade_prevention,
+                buy_order.shown_amount().min(sell_order.remaining_amount.unwrap_or(0)),
+                buy_order.limit_price
+            ),
+        };
+        let self_trade = SelfTradeMatch {
+            mode: mode.unwrap_or_default(),
+            taker_side,
+            buy_order: Some(&buy_order),
+            sell_order: Some(&sell_order),
+            amount,
+            exchange_rate: exchange_rate.unwrap_or_default(),
+        };
+        let prevented = Self::prevent_self_trade(&mut tx, &self_trade).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(MatchReport { exchanges: Vec::new(), prevented_trades: vec![prevented] })
+    }
+
+    ///
+    /// Fails when trading between the two currencies is halted, in either direction.
+    /// Holds a shared lock on the pair so a halt waits for the transaction to finish
+    async fn check_trading_halt(conn: &mut PgConnection, currency_id: i32, counter_currency_id: i32) -> Result<(), DataError> {
+        sqlx::query!("SELECT pg_advisory_xact_lock_shared(LEAST($1::INTEGER, $2::INTEGER), GREATEST($1::INTEGER, $2::INTEGER))",
+            currency_id, counter_currency_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::TradingHaltedError(e.to_string()))?;
+        let halted = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM trading_halts WHERE resumed_at IS NULL
+                AND LEAST(base_currency_id, quote_currency_id) = LEAST($1::INTEGER, $2::INTEGER)
+                AND GREATEST(base_currency_id, quote_currency_id) = GREATEST($1::INTEGER, $2::INTEGER))",
+            currency_id, counter_currency_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::TradingHaltedError(e.to_string()))?
+            .unwrap_or(false);
+        if halted {
+            return Err(DataError::TradingHaltedError(
+                format!("Trading between currencies with id={} and id={} is halted", currency_id, counter_currency_id)
+            ));
+        }
+        Ok(())
+    }
+
+    ///
+    /// Cancels live limit orders and pending stop orders of `issuer_id`, of everyone when `None`,
+    /// on the currency pair in either direction, on every pair when `None`
+    async fn cancel_live_orders(
+        conn: &mut PgConnection,
+        issuer_id: Option<i32>,
+        pair: Option<(i32, i32)>
+    ) -> Result<UserOrders, DataError> {
+        let now = OffsetDateTime::now_utc();
+        let (currency_id, counter_currency_id) = pair.unzip();
+        let buy_orders = sqlx::query_as!(BuyOrder,
+            "UPDATE buy_orders SET status = 'cancelled', updated_at = $4
+            WHERE status IN ('open', 'partially_filled')
+            AND ($1::INTEGER IS NULL OR issuer_id = $1)
+            AND ($2::INTEGER IS NULL OR (buy_currency_id = $2 AND sell_currency_id = $3) OR (buy_currency_id = $3 AND sell_currency_id = $2))
+            RETURNING buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            issuer_id, currency_id, counter_currency_id, now)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let sell_orders = sqlx::query_as!(SellOrder,
+            "UPDATE sell_orders SET status = 'cancelled', updated_at = $4
+            WHERE status IN ('open', 'partially_filled')
+            AND ($1::INTEGER IS NULL OR issuer_id = $1)
+            AND ($2::INTEGER IS NULL OR (sell_currency_id = $2 AND buy_currency_id = $3) OR (sell_currency_id = $3 AND buy_currency_id = $2))
+            RETURNING sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            issuer_id, currency_id, counter_currency_id, now)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let stop_orders = sqlx::query_as!(StopOrder,
+            "UPDATE stop_orders SET status = 'cancelled', updated_at = $4
+            WHERE status = 'pending'
+            AND ($1::INTEGER IS NULL OR issuer_id = $1)
+            AND ($2::INTEGER IS NULL OR (base_currency_id = $2 AND quote_currency_id = $3) OR (base_currency_id = $3 AND quote_currency_id = $2))
+            RETURNING stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id",
+            issuer_id, currency_id, counter_currency_id, now)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let buy_order_ids: Vec<i32> = buy_orders.iter().filter_map(|order| order.buy_order_id).collect();
+        let sell_order_ids: Vec<i32> = sell_orders.iter().filter_map(|order| order.sell_order_id).collect();
+        Self::release_buy_holds(conn, &buy_order_ids, 0, 0).await?;
+        Self::release_sell_holds(conn, &sell_order_ids, 0, 0).await?;
+        Ok(UserOrders { buy_orders, sell_orders, stop_orders })
+    }
+
+    async fn check_client_order_id(
+        conn: &mut PgConnection,
+        issuer_id: i32,
+        client_order_id: Option<&str>
+    ) -> Result<(), DataError> {
+        let Some(client_order_id) = client_order_id else {
+            return Ok(());
+        };
+        sqlx::query!("SELECT pg_advisory_xact_lock($1)", issuer_id as i64)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        let taken = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM buy_orders WHERE issuer_id = $1 AND client_order_id = $2)
+                OR EXISTS(SELECT 1 FROM sell_orders WHERE issuer_id = $1 AND client_order_id = $2)",
+            issuer_id, client_order_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?
+            .unwrap_or(false);
+        if taken {
+            return Err(DataError::DuplicateClientOrderIdError(
+                format!("User with id={} already placed an order with client order id {}", issuer_id, client_order_id)
+            ));
+        }
+        Ok(())
+    }
+
+    async fn check_open_order_limit(
+        conn: &mut PgConnection,
+        issuer_id: i32,
+        max_open_orders: i64
+    ) -> Result<(), DataError> {
+        sqlx::query!("SELECT pg_advisory_xact_lock($1)", issuer_id as i64)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        let open_orders = sqlx::query_scalar!(
+            "SELECT
+                (SELECT COUNT(*) FROM buy_orders WHERE issuer_id = $1 AND status IN ('open', 'partially_filled'))
+                + (SELECT COUNT(*) FROM sell_orders WHERE issuer_id = $1 AND status IN ('open', 'partially_filled'))",
+            issuer_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?
+            .unwrap_or(0);
+        if open_orders >= max_open_orders {
+            return Err(DataError::OpenOrderLimitError(
+                format!("User with id={} already has {} open orders", issuer_id, open_orders)
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_amendment(request: &AmendOrderRequest, filled_amount: i32) -> Result<(), DataError> {
+        if request.amount.is_some_and(|amount| amount <= filled_amount) {
+            return Err(DataError::OrderAmendmentError(
+                format!("Amount must be greater than the {} already filled", filled_amount)
+            ));
+        }
+        if request.limit_price.is_some_and(|price| price <= 0.0) {
+            return Err(DataError::OrderAmendmentError("Limit price must be positive".to_string()));
+        }
+        if request.expires_at.is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc()) {
+            return Err(DataError::OrderAmendmentError("Expiry must be in the future".to_string()));
+        }
+        Ok(())
+    }
+
+    ///
+    /// Status after a fill leaves `remaining_amount`, or `None` when the current status forbids it
+    fn next_status(current: Option<OrderStatus>, remaining_amount: i32) -> Option<OrderStatus> {
+        let current = current.unwrap_or(OrderStatus::Open);
+        let next = OrderStatus::after_fill(remaining_amount);
+        current.can_transition_to(next).then_some(next)
+    }
+
+    ///
+    /// Moves the base currency from seller to buyer and the counter amount back, paying out of
+    /// the holds of the orders involved, then records the exchange together with its fill
+    async fn settle(conn: &mut PgConnection, settlement: &Settlement) -> Result<CurrencyExchangeRecord, DataError> {
+        let income = Self::fill_cost(conn, settlement).await?;
+        let base = settlement.base_currency_id;
+        let quote = settlement.quote_currency_id;
+        Self::check_trading_halt(conn, base, quote).await?;
+
+        let buyer_base_wallet = Self::settlement_wallet_id(conn, settlement.buyer_id, base).await?;
+        let buyer_quote_wallet = Self::settlement_wallet_id(conn, settlement.buyer_id, quote).await?;
+        let seller_base_wallet = Self::settlement_wallet_id(conn, settlement.seller_id, base).await?;
+        let seller_quote_wallet = Self::settlement_wallet_id(conn, settlement.seller_id, quote).await?;
+
+        if let Some(buy_order_id) = settlement.buy_order_id {
+            Self::release_buy_holds(conn, &[buy_order_id], settlement.amount, income).await?;
+        }
+        if let Some(sell_order_id) = settlement.sell_order_id {
+            Self::release_sell_holds(conn, &[sell_order_id], settlement.amount, settlement.amount).await?;
+        }
+        Self::debit_wallet(conn, seller_base_wallet, base, settlement.amount).await?;
+        Self::debit_wallet(conn, buyer_quote_wallet, quote, income).await?;
+        Self::credit_wallet(conn, buyer_base_wallet, base, settlement.amount).await?;
+        Self::credit_wallet(conn, seller_quote_wallet, quote, income).await?;
+
+        let exchange = sqlx::query_as!(CurrencyExchangeRecord,
+            "INSERT INTO currency_exchange(income, sell_order_id, buy_order_id, buyer_id, seller_id, amount, exchange_rate, created_at,
+                base_currency_id, quote_currency_id, taker_side)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id, income, sell_order_id, buy_order_id, amount, exchange_rate, created_at, buyer_id, seller_id,
+                base_currency_id, quote_currency_id, taker_side as \"taker_side: OrderSide\"",
+            income as f64, settlement.sell_order_id, settlement.buy_order_id, settlement.buyer_id,
+            settlement.seller_id, settlement.amount, settlement.exchange_rate, OffsetDateTime::now_utc(),
+            base, quote, settlement.taker_side as OrderSide)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+
+        sqlx::query!(
+            "INSERT INTO fills(exchange_id, buy_order_id, sell_order_id, amount, exchange_rate, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)",
+            exchange.id, settlement.buy_order_id, settlement.sell_order_id, settlement.amount,
+            settlement.exchange_rate, OffsetDateTime::now_utc())
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Self::record_candles(conn, &exchange).await?;
+        Ok(exchange)
+    }
+
+    ///
+    /// Counter amount the buyer pays for the fill. A buy order's cost is rounded over everything it
+    /// has filled so far, so rounding one fill at a time never adds up to more than the order's notional
+    async fn fill_cost(conn: &mut PgConnection, settlement: &Settlement) -> Result<i32, DataError> {
+        let Some(buy_order_id) = settlement.buy_order_id else {
+            return Ok(counter_amount(settlement.amount, settlement.exchange_rate));
+        };
+        let filled = sqlx::query!(
+            "SELECT COALESCE(SUM(amount * exchange_rate), 0) AS \"notional!\", COALESCE(SUM(income), 0) AS \"paid!\"
+            FROM currency_exchange WHERE buy_order_id = $1",
+            buy_order_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let notional = filled.notional + settlement.amount as f64 * settlement.exchange_rate;
+        Ok(notional.round() as i32 - filled.paid.round() as i32)
+    }
+
+    ///
+    /// Folds the exchange into the candle of every interval.
+    /// Open and close follow trade ids, so settlements committing out of order still land correctly
+    async fn record_candles(conn: &mut PgConnection, exchange: &CurrencyExchangeRecord) -> Result<(), DataError> {
+        let created_at = exchange.created_at.unwrap_or_else(OffsetDateTime::now_utc);
+        sqlx::query!(
+            "INSERT INTO candles(base_currency_id, quote_currency_id, interval, bucket_start, open, high, low, close,
+                volume, quote_volume, trade_count, first_trade_id, last_trade_id)
+            SELECT $1, $2, i.interval, date_bin(candle_step(i.interval), $3, TIMESTAMPTZ 'epoch'), $4, $4, $4, $4, $5, $6, 1, $7, $7
+            FROM unnest(enum_range(NULL::candle_interval)) AS i(interval)
+            ON CONFLICT (base_currency_id, quote_currency_id, interval, bucket_start) DO UPDATE SET
+                open = CASE WHEN EXCLUDED.first_trade_id < candles.first_trade_id THEN EXCLUDED.open ELSE candles.open END,
+                high = GREATEST(candles.high, EXCLUDED.high),
+                low = LEAST(candles.low, EXCLUDED.low),
+                close = CASE WHEN EXCLUDED.last_trade_id > candles.last_trade_id THEN EXCLUDED.close ELSE candles.close END,
+                volume = candles.volume + EXCLUDED.volume,
+                quote_volume = candles.quote_volume + EXCLUDED.quote_volume,
+                trade_count = candles.trade_count + 1,
+                first_trade_id = LEAST(candles.first_trade_id, EXCLUDED.first_trade_id),
+                last_trade_id = GREATEST(candles.last_trade_id, EXCLUDED.last_trade_id)",
+            exchange.base_currency_id, exchange.quote_currency_id, created_at, exchange.exchange_rate,
+            exchange.amount as i64, exchange.income.unwrap_or_default(), exchange.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+///
+/// Two orders of one user about to trade. A taker trading without an order leaves its side `None`
+struct SelfTradeMatch<'a> {
+    mode: SelfTradePrevention,
+    taker_side: OrderSide,
+    buy_order: Option<&'a BuyOrder>,
+    sell_order: Option<&'a SellOrder>,
+    amount: i32,
+    exchange_rate: f64,
+}
+
+struct Settlement {
+    buyer_id: i32,
+    seller_id: i32,
+    base_currency_id: i32,
+    quote_currency_id: i32,
+    amount: i32,
+    exchange_rate: f64,
+    buy_order_id: Option<i32>,
+    sell_order_id: Option<i32>,
+    taker_side: OrderSide,
+}
+
+///
+/// Amount of the counter currency due for `amount` units at `exchange_rate`
+pub fn counter_amount(amount: i32, exchange_rate: f64) -> i32 {
+    (amount as f64 * exchange_rate).round() as i32
+}
+
+///
+/// Funds a buy order of `amount` units holds at `limit_price`, rounded up so its fills always fit.
+/// Products such as 10 * 1.1 land a hair above the integer, so they are trimmed to nine decimals first
+pub fn hold_amount(amount: i32, limit_price: f64) -> i32 {
+    ((amount as f64 * limit_price * 1e9).round() / 1e9).ceil() as i32
 }
 
 #[async_trait::async_trait]
@@ -109,7 +992,7 @@ impl WalletRepository for Repository {
     async fn get_currency_balance(&self, request: &BalanceRequest) -> Result<Option<CurrencyBalance>, DataError> {
         let uid = request.user_id;
         let result = sqlx::query_as!(CurrencyBalance,
-            "SELECT w.wallet_id, w.user_id, ca.amount, ca.currency_id FROM wallets as w
+            "SELECT w.wallet_id, w.user_id, ca.amount, ca.held as \"held?\", ca.amount - ca.held as available, ca.currency_id FROM wallets as w
             JOIN currency_amount as ca
             ON w.wallet_id = ca.wallet_id
             WHERE w.user_id = $1", uid)
@@ -122,6 +1005,18 @@ impl WalletRepository for Repository {
         }
     }
 
+    async fn find_user_balances(&self, user_id: &i32) -> Result<Vec<CurrencyBalance>, DataError> {
+        sqlx::query_as!(CurrencyBalance,
+            "SELECT w.wallet_id, w.user_id, ca.amount, ca.held as \"held?\", ca.amount - ca.held as available, ca.currency_id FROM wallets as w
+            JOIN currency_amount as ca
+            ON w.wallet_id = ca.wallet_id
+            WHERE w.user_id = $1
+            ORDER BY ca.currency_id", user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| DataError::WalletBalanceError(format!("Wallet balance query for user with id={} failed", user_id)))
+    }
+
     async fn find_wallet_by_incoming_currency(
         &self, 
         issuer_id: &i32, 
@@ -182,10 +1077,18 @@ impl CurrencyRepository for Repository {
 impl OrderRepository for Repository {
     async fn find_buy_orders<S: Into<i64> + Send>(
         &self,
-        limit: S
+        limit: S,
+        status: Option<OrderStatus>
     ) -> Result<Vec<BuyOrder>, DataError> {
         let limit = limit.into();
-        let vec = sqlx::query_as!(BuyOrder, "SELECT * FROM buy_orders LIMIT $1", limit)
+        let vec = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders
+            WHERE CASE WHEN $2::order_status IS NULL
+                THEN status IN ('open', 'partially_filled') AND (expires_at IS NULL OR expires_at > now())
+                ELSE status = $2 END
+            ORDER BY limit_price DESC, priority_at, buy_order_id LIMIT $1",
+            limit, status as Option<OrderStatus>)
             .fetch_all(&self.pool)
             .await
             .expect("Error loading orders");
@@ -194,53 +1097,403 @@ impl OrderRepository for Repository {
 
     async fn find_sell_orders<S: Into<i64> + Send>(
         &self,
-        limit: S
+        limit: S,
+        status: Option<OrderStatus>
     ) -> Result<Vec<SellOrder>, DataError> {
         let limit = limit.into();
-        let vec = sqlx::query_as!(SellOrder, "SELECT * FROM sell_orders LIMIT $1", limit)
+        let vec = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders
+            WHERE CASE WHEN $2::order_status IS NULL
+                THEN status IN ('open', 'partially_filled') AND (expires_at IS NULL OR expires_at > now())
+                ELSE status = $2 END
+            ORDER BY limit_price, priority_at, sell_order_id LIMIT $1",
+            limit, status as Option<OrderStatus>)
             .fetch_all(&self.pool)
             .await
             .expect("Error loading orders");
         Ok(vec)
     }
 
-    async fn create_buy_order(&self, req: &CreateBuyOrderRequest) -> Result<BuyOrder, DataError> {
-        let issuer_id = req.issuer_id;
-        let amount = req.buy_amount;
-        let buy_id = req.buy_currency_id;
-        let sell_id = req.sell_currency_id;
-        let expiry_total = OffsetDateTime::now_utc() + Duration::days(req.expiry_days as i64);
-        let created_at = OffsetDateTime::now_utc();
-        let updated_at = OffsetDateTime::now_utc();
-        let result = sqlx::query_as!(BuyOrder, 
-            "INSERT INTO buy_orders(issuer_id, buy_currency_amount, buy_currency_id, sell_currency_id, created_at, updated_at, expires_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING buy_order_id, issuer_id, buy_currency_amount, buy_currency_id, sell_currency_id, created_at, updated_at, expires_at", 
-            issuer_id, amount, buy_id, sell_id, created_at, updated_at, expiry_total)
-            .fetch_one(&self.pool)
+    async fn find_buy_order(&self, buy_order_id: &i32) -> Result<Option<BuyOrder>, DataError> {
+        let order = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders WHERE buy_order_id = $1", buy_order_id)
+            .fetch_optional(&self.pool)
             .await
-            .expect("Error creating buy order");
-        Ok(result)   
+            .expect("Error loading order");
+        Ok(order)
     }
 
-    async fn create_sell_order(&self, req: &CreateSellOrderRequest) -> Result<SellOrder, DataError> {
-        let issuer_id = req.issuer_id;
-        let amount = req.sell_amount;
-        let buy_id = &req.buy_currency_id;
-        let sell_id = &req.sell_currency_id;
-        let expiry_total = OffsetDateTime::now_utc() + Duration::days(req.expiry_days as i64);
-        let created_at = OffsetDateTime::now_utc();
-        let updated_at = OffsetDateTime::now_utc();
-        let result = sqlx::query_as!(SellOrder, 
-            "INSERT INTO sell_orders(issuer_id, sell_currency_amount, buy_currency_id, sell_currency_id, created_at, updated_at, expires_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING sell_order_id, issuer_id, sell_currency_amount, buy_currency_id, sell_currency_id, created_at, updated_at, expires_at", 
-            issuer_id, amount, buy_id, sell_id, created_at, updated_at, expiry_total)
-            .fetch_one(&self.pool)
+    async fn find_sell_order(&self, sell_order_id: &i32) -> Result<Option<SellOrder>, DataError> {
+        let order = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders WHERE sell_order_id = $1", sell_order_id)
+            .fetch_optional(&self.pool)
+            .await
+            .expect("Error loading order");
+        Ok(order)
+    }
+
+    async fn create_buy_order(&self, req: &CreateBuyOrderRequest, max_open_orders: i64) -> Result<BuyOrder, DataError> {
+        let mut tx = self.pool.begin()
             .await
-            .expect("Error creating sell order");
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        let result = Self::insert_buy_order(&mut tx, req, max_open_orders).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
         Ok(result)
     }
+
+    async fn create_sell_order(&self, req: &CreateSellOrderRequest, max_open_orders: i64) -> Result<SellOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        let result = Self::insert_sell_order(&mut tx, req, max_open_orders).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Ok(result)
+    }
+
+    async fn find_crossing_sell_orders(&self, buy_order: &BuyOrder) -> Result<Vec<SellOrder>, DataError> {
+        let vec = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders
+            WHERE sell_currency_id = $1 AND buy_currency_id = $2 AND remaining_amount > 0
+            AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+            AND limit_price <= $3
+            AND (expires_at IS NULL OR expires_at > now())
+            ORDER BY limit_price, priority_at, sell_order_id",
+            buy_order.buy_currency_id, buy_order.sell_currency_id, buy_order.limit_price)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(vec)
+    }
+
+    async fn find_crossing_buy_orders(&self, sell_order: &SellOrder) -> Result<Vec<BuyOrder>, DataError> {
+        let vec = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders
+            WHERE buy_currency_id = $1 AND sell_currency_id = $2 AND remaining_amount > 0
+            AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+            AND limit_price >= $3
+            AND (expires_at IS NULL OR expires_at > now())
+            ORDER BY limit_price DESC, priority_at, buy_order_id",
+            sell_order.sell_currency_id, sell_order.buy_currency_id, sell_order.limit_price)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(vec)
+    }
+
+    async fn find_resting_sell_orders(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        max_price: Option<f64>
+    ) -> Result<Vec<SellOrder>, DataError> {
+        sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders
+            WHERE sell_currency_id = $1 AND buy_currency_id = $2 AND remaining_amount > 0
+            AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+            AND limit_price IS NOT NULL
+            AND ($3::float8 IS NULL OR limit_price <= $3)
+            AND (expires_at IS NULL OR expires_at > now())
+            ORDER BY limit_price, priority_at, sell_order_id",
+            base_currency_id, quote_currency_id, max_price)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))
+    }
+
+    async fn find_resting_buy_orders(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        min_price: Option<f64>
+    ) -> Result<Vec<BuyOrder>, DataError> {
+        sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders
+            WHERE buy_currency_id = $1 AND sell_currency_id = $2 AND remaining_amount > 0
+            AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+            AND limit_price IS NOT NULL
+            AND ($3::float8 IS NULL OR limit_price >= $3)
+            AND (expires_at IS NULL OR expires_at > now())
+            ORDER BY limit_price DESC, priority_at, buy_order_id",
+            base_currency_id, quote_currency_id, min_price)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))
+    }
+
+    async fn update_buy_order_status(&self, buy_order_id: &i32, status: OrderStatus) -> Result<BuyOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let buy_order = Self::lock_buy_order(&mut tx, *buy_order_id).await?;
+        let updated = Self::transition_buy_order(&mut tx, &buy_order, status).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(updated)
+    }
+
+    async fn cancel_buy_order(&self, buy_order_id: &i32, issuer_id: &i32) -> Result<BuyOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let cancelled = Self::cancel_owned_buy_order(&mut tx, *buy_order_id, *issuer_id).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(cancelled)
+    }
+
+    async fn update_sell_order_status(&self, sell_order_id: &i32, status: OrderStatus) -> Result<SellOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let sell_order = Self::lock_sell_order(&mut tx, *sell_order_id).await?;
+        let updated = Self::transition_sell_order(&mut tx, &sell_order, status).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(updated)
+    }
+
+    async fn cancel_sell_order(&self, sell_order_id: &i32, issuer_id: &i32) -> Result<SellOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let cancelled = Self::cancel_owned_sell_order(&mut tx, *sell_order_id, *issuer_id).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(cancelled)
+    }
+
+    async fn amend_buy_order(
+        &self,
+        buy_order_id: &i32,
+        issuer_id: &i32,
+        request: &AmendOrderRequest
+    ) -> Result<BuyOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let buy_order = Self::lock_buy_order(&mut tx, *buy_order_id).await?;
+        if buy_order.issuer_id != Some(*issuer_id) {
+            return Err(DataError::OrderOwnershipError(
+                format!("Buy order with id={} does not belong to user with id={}", buy_order_id, issuer_id)
+            ));
+        }
+        if !buy_order.status.is_some_and(|s| s.is_live()) {
+            return Err(DataError::OrderStatusError(format!("Buy order with id={} is no longer open", buy_order_id)));
+        }
+        Self::check_amendment(request, buy_order.filled_amount.unwrap_or(0))?;
+        Self::check_trading_halt(&mut tx, buy_order.buy_currency_id.unwrap_or_default(), buy_order.sell_currency_id.unwrap_or_default()).await?;
+
+        let current_amount = buy_order.buy_currency_amount.unwrap_or(0);
+        let current_price = buy_order.limit_price.unwrap_or_default();
+        let now = OffsetDateTime::now_utc();
+        let amount = request.amount.unwrap_or(current_amount);
+        let limit_price = request.limit_price.unwrap_or(current_price);
+        let expires_at = request.expires_at.or(buy_order.expires_at);
+        let priority_at = if request.loses_priority(current_amount, current_price) {
+            Some(now)
+        } else {
+            buy_order.priority_at
+        };
+        let amended = sqlx::query_as!(BuyOrder,
+            "UPDATE buy_orders SET buy_currency_amount = $1, remaining_amount = $1 - filled_amount,
+            limit_price = $2, expires_at = $3, priority_at = $4, updated_at = $5,
+            visible_amount = CASE WHEN display_amount IS NULL THEN NULL ELSE LEAST(visible_amount, $1 - filled_amount) END
+            WHERE buy_order_id = $6
+            RETURNING buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            amount, limit_price, expires_at, priority_at, now, buy_order_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let growth = hold_amount(amended.remaining_amount.unwrap_or(0), limit_price) - hold_amount(buy_order.remaining_amount.unwrap_or(0), current_price);
+        Self::resize_buy_hold(&mut tx, &amended, growth).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(amended)
+    }
+
+    async fn amend_sell_order(
+        &self,
+        sell_order_id: &i32,
+        issuer_id: &i32,
+        request: &AmendOrderRequest
+    ) -> Result<SellOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let sell_order = Self::lock_sell_order(&mut tx, *sell_order_id).await?;
+        if sell_order.issuer_id != Some(*issuer_id) {
+            return Err(DataError::OrderOwnershipError(
+                format!("Sell order with id={} does not belong to user with id={}", sell_order_id, issuer_id)
+            ));
+        }
+        if !sell_order.status.is_some_and(|s| s.is_live()) {
+            return Err(DataError::OrderStatusError(format!("Sell order with id={} is no longer open", sell_order_id)));
+        }
+        Self::check_amendment(request, sell_order.filled_amount.unwrap_or(0))?;
+        Self::check_trading_halt(&mut tx, sell_order.buy_currency_id.unwrap_or_default(), sell_order.sell_currency_id.unwrap_or_default()).await?;
+
+        let current_amount = sell_order.sell_currency_amount.unwrap_or(0);
+        let current_price = sell_order.limit_price.unwrap_or_default();
+        let now = OffsetDateTime::now_utc();
+        let amount = request.amount.unwrap_or(current_amount);
+        let limit_price = request.limit_price.unwrap_or(current_price);
+        let expires_at = request.expires_at.or(sell_order.expires_at);
+        let priority_at = if request.loses_priority(current_amount, current_price) {
+            Some(now)
+        } else {
+            sell_order.priority_at
+        };
+        let amended = sqlx::query_as!(SellOrder,
+            "UPDATE sell_orders SET sell_currency_amount = $1, remaining_amount = $1 - filled_amount,
+            limit_price = $2, expires_at = $3, priority_at = $4, updated_at = $5,
+            visible_amount = CASE WHEN display_amount IS NULL THEN NULL ELSE LEAST(visible_amount, $1 - filled_amount) END
+            WHERE sell_order_id = $6
+            RETURNING sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            amount, limit_price, expires_at, priority_at, now, sell_order_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let growth = amended.remaining_amount.unwrap_or(0) - sell_order.remaining_amount.unwrap_or(0);
+        Self::resize_sell_hold(&mut tx, &amended, growth).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(amended)
+    }
+
+    async fn expire_buy_orders(&self, batch_size: i64) -> Result<Vec<BuyOrder>, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::OrderStatusError(e.to_string()))?;
+        let vec = sqlx::query_as!(BuyOrder,
+            "UPDATE buy_orders SET status = 'expired', updated_at = now()
+            WHERE buy_order_id IN (
+                SELECT buy_order_id FROM buy_orders
+                WHERE status IN ('open', 'partially_filled') AND expires_at <= now()
+                ORDER BY expires_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            batch_size)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DataError::OrderStatusError(e.to_string()))?;
+        let buy_order_ids: Vec<i32> = vec.iter().filter_map(|order| order.buy_order_id).collect();
+        Self::release_buy_holds(&mut tx, &buy_order_ids, 0, 0).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderStatusError(e.to_string()))?;
+        Ok(vec)
+    }
+
+    async fn expire_sell_orders(&self, batch_size: i64) -> Result<Vec<SellOrder>, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::OrderStatusError(e.to_string()))?;
+        let vec = sqlx::query_as!(SellOrder,
+            "UPDATE sell_orders SET status = 'expired', updated_at = now()
+            WHERE sell_order_id IN (
+                SELECT sell_order_id FROM sell_orders
+                WHERE status IN ('open', 'partially_filled') AND expires_at <= now()
+                ORDER BY expires_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"",
+            batch_size)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DataError::OrderStatusError(e.to_string()))?;
+        let sell_order_ids: Vec<i32> = vec.iter().filter_map(|order| order.sell_order_id).collect();
+        Self::release_sell_holds(&mut tx, &sell_order_ids, 0, 0).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderStatusError(e.to_string()))?;
+        Ok(vec)
+    }
+
+    async fn find_buy_order_fills(&self, buy_order_id: &i32) -> Result<Vec<Fill>, DataError> {
+        let vec = sqlx::query_as!(Fill,
+            "SELECT * FROM fills WHERE buy_order_id = $1 ORDER BY created_at, fill_id", buy_order_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(vec)
+    }
+
+    async fn find_sell_order_fills(&self, sell_order_id: &i32) -> Result<Vec<Fill>, DataError> {
+        let vec = sqlx::query_as!(Fill,
+            "SELECT * FROM fills WHERE sell_order_id = $1 ORDER BY created_at, fill_id", sell_order_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(vec)
+    }
+
+    async fn find_user_orders(&self, user_id: &i32) -> Result<UserOrders, DataError> {
+        let buy_orders = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders
+            WHERE issuer_id = $1 AND status IN ('open', 'partially_filled')
+            ORDER BY created_at DESC, buy_order_id DESC", user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        let sell_orders = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders
+            WHERE issuer_id = $1 AND status IN ('open', 'partially_filled')
+            ORDER BY created_at DESC, sell_order_id DESC", user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        let stop_orders = self.find_stop_orders(user_id, Some(StopOrderStatus::Pending)).await?;
+        Ok(UserOrders { buy_orders, sell_orders, stop_orders })
+    }
+
+    async fn cancel_user_orders(&self, user_id: &i32, pair: Option<(i32, i32)>) -> Result<UserOrders, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let cancelled = Self::cancel_live_orders(&mut tx, Some(*user_id), pair).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(cancelled)
+    }
+
+    async fn find_client_order(&self, user_id: &i32, client_order_id: &str) -> Result<ClientOrder, DataError> {
+        let buy_order = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders WHERE issuer_id = $1 AND client_order_id = $2", user_id, client_order_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        let sell_order = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders WHERE issuer_id = $1 AND client_order_id = $2", user_id, client_order_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        Ok(ClientOrder { buy_order, sell_order })
+    }
 }
 
 #[async_trait::async_trait]
@@ -254,41 +1507,1018 @@ impl CurrencyAmountRepository for Repository {
         Ok(result)
     }
 
-    async fn exchange_currencies(
+    async fn settle_exchange(
         &self,
-        sum: i32,
-        incoming_currency_id: i32,
-        outgoing_currency_id: i32,
-        exchange_rate: f32,
-        incoming_currency_wallet_id: i32,
-        outgoing_currency_wallet_id: i32
-    ) -> Result<Option<CurrencyAmountQuery>, DataError> {
-        let replenish_wallet_result = sqlx::query_as!(CurrencyAmount,
-            "UPDATE currency_amount SET amount = $1 WHERE currency_id = $2 AND wallet_id = $3", sum, incoming_currency_id, incoming_currency_wallet_id)
-            .execute(&self.pool)
+        buy_order_id: i32,
+        sell_order_id: i32,
+        exchange_rate: f64,
+        taker_side: OrderSide
+    ) -> Result<Option<MatchOutcome>, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        // Buy side is always locked first so concurrent settlements cannot deadlock
+        let buy_order = Self::lock_buy_order(&mut tx, buy_order_id).await?;
+        let sell_order = Self::lock_sell_order(&mut tx, sell_order_id).await?;
+
+        let live = buy_order.status.is_some_and(|s| s.is_live()) && sell_order.status.is_some_and(|s| s.is_live());
+        // The resting side only offers its current slice when it is an iceberg order
+        let amount = match taker_side {
+            OrderSide::Buy => buy_order.remaining_amount.unwrap_or(0).min(sell_order.shown_amount()),
+            OrderSide::Sell => buy_order.shown_amount().min(sell_order.remaining_amount.unwrap_or(0)),
+        };
+        if !live || amount <= 0 {
+            return Ok(None);
+        }
+        if buy_order.issuer_id == sell_order.issuer_id {
+            let mode = match taker_side {
+                OrderSide::Buy => buy_order.self_trade_prevention,
+                OrderSide::Sell => sell_order.self_trade_prevention,
+            };
+            let self_trade = SelfTradeMatch {
+                mode: mode.unwrap_or_default(),
+                taker_side,
+                buy_order: Some(&buy_order),
+                sell_order: Some(&sell_order),
+                amount,
+                exchange_rate,
+            };
+            let prevented = Self::prevent_self_trade(&mut tx, &self_trade).await?;
+            tx.commit()
+                .await
+                .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+            return Ok(Some(MatchOutcome::Prevented(prevented)));
+        }
+        let settlement = Settlement {
+            buyer_id: buy_order.issuer_id.unwrap_or_default(),
+            seller_id: sell_order.issuer_id.unwrap_or_default(),
+            base_currency_id: buy_order.buy_currency_id.unwrap_or_default(),
+            quote_currency_id: buy_order.sell_currency_id.unwrap_or_default(),
+            amount,
+            exchange_rate,
+            buy_order_id: Some(buy_order_id),
+            sell_order_id: Some(sell_order_id),
+            taker_side,
+        };
+        let exchange = Self::settle(&mut tx, &settlement).await?;
+        Self::reduce_buy_order(&mut tx, &buy_order, amount).await?;
+        Self::reduce_sell_order(&mut tx, &sell_order, amount).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(Some(MatchOutcome::Traded(exchange)))
+    }
+
+    async fn fill_buy_order(
+        &self,
+        buy_order_id: i32,
+        taker_id: i32,
+        amount: i32,
+        self_trade_prevention: Option<SelfTradePrevention>
+    ) -> Result<MatchOutcome, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let buy_order = Self::lock_buy_order(&mut tx, buy_order_id).await?;
+        if buy_order.expires_at.is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc()) {
+            return Err(DataError::CurrencyExchangeError(format!("Buy order with id={} has expired", buy_order_id)));
+        }
+        if !buy_order.status.is_some_and(|s| s.is_live()) {
+            return Err(DataError::OrderStatusError(format!("Buy order with id={} is no longer open", buy_order_id)));
+        }
+        if !buy_order.time_in_force.unwrap_or(TimeInForce::Gtc).rests() {
+            return Err(DataError::OrderStatusError(format!("Buy order with id={} is not resting on the book", buy_order_id)));
+        }
+        let fill = amount.min(buy_order.shown_amount());
+        if fill <= 0 {
+            return Err(DataError::CurrencyExchangeError(format!("Buy order with id={} has nothing left to fill", buy_order_id)));
+        }
+        if buy_order.issuer_id == Some(taker_id) {
+            let mode = match self_trade_prevention {
+                Some(mode) => mode,
+                None => Self::account_self_trade_prevention(&mut tx, taker_id).await?,
+            };
+            let self_trade = SelfTradeMatch {
+                mode,
+                taker_side: OrderSide::Sell,
+                buy_order: Some(&buy_order),
+                sell_order: None,
+                amount: fill,
+                exchange_rate: buy_order.limit_price.unwrap_or_default(),
+            };
+            let prevented = Self::prevent_self_trade(&mut tx, &self_trade).await?;
+            tx.commit()
+                .await
+                .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+            return Ok(MatchOutcome::Prevented(prevented));
+        }
+        let settlement = Settlement {
+            buyer_id: buy_order.issuer_id.unwrap_or_default(),
+            seller_id: taker_id,
+            base_currency_id: buy_order.buy_currency_id.unwrap_or_default(),
+            quote_currency_id: buy_order.sell_currency_id.unwrap_or_default(),
+            amount: fill,
+            exchange_rate: buy_order.limit_price.unwrap_or_default(),
+            buy_order_id: Some(buy_order_id),
+            sell_order_id: None,
+            taker_side: OrderSide::Sell,
+        };
+        let exchange = Self::settle(&mut tx, &settlement).await?;
+        Self::reduce_buy_order(&mut tx, &buy_order, fill).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(MatchOutcome::Traded(exchange))
+    }
+
+    async fn fill_sell_order(
+        &self,
+        sell_order_id: i32,
+        taker_id: i32,
+        amount: i32,
+        self_trade_prevention: Option<SelfTradePrevention>
+    ) -> Result<MatchOutcome, DataError> {
+        let mut tx = self.pool.begin()
             .await
-            .expect("Error replenishing currency amount");
-        let drain_sum = sum * exchange_rate as i32;
-        let drain_amount = self.currency_amount(&outgoing_currency_id)
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let sell_order = Self::lock_sell_order(&mut tx, sell_order_id).await?;
+        if sell_order.expires_at.is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc()) {
+            return Err(DataError::CurrencyExchangeError(format!("Sell order with id={} has expired", sell_order_id)));
+        }
+        if !sell_order.status.is_some_and(|s| s.is_live()) {
+            return Err(DataError::OrderStatusError(format!("Sell order with id={} is no longer open", sell_order_id)));
+        }
+        if !sell_order.time_in_force.unwrap_or(TimeInForce::Gtc).rests() {
+            return Err(DataError::OrderStatusError(format!("Sell order with id={} is not resting on the book", sell_order_id)));
+        }
+        let fill = amount.min(sell_order.shown_amount());
+        if fill <= 0 {
+            return Err(DataError::CurrencyExchangeError(format!("Sell order with id={} has nothing left to fill", sell_order_id)));
+        }
+        if sell_order.issuer_id == Some(taker_id) {
+            let mode = match self_trade_prevention {
+                Some(mode) => mode,
+                None => Self::account_self_trade_prevention(&mut tx, taker_id).await?,
+            };
+            let self_trade = SelfTradeMatch {
+                mode,
+                taker_side: OrderSide::Buy,
+                buy_order: None,
+                sell_order: Some(&sell_order),
+                amount: fill,
+                exchange_rate: sell_order.limit_price.unwrap_or_default(),
+            };
+            let prevented = Self::prevent_self_trade(&mut tx, &self_trade).await?;
+            tx.commit()
+                .await
+                .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+            return Ok(MatchOutcome::Prevented(prevented));
+        }
+        let settlement = Settlement {
+            buyer_id: taker_id,
+            seller_id: sell_order.issuer_id.unwrap_or_default(),
+            base_currency_id: sell_order.sell_currency_id.unwrap_or_default(),
+            quote_currency_id: sell_order.buy_currency_id.unwrap_or_default(),
+            amount: fill,
+            exchange_rate: sell_order.limit_price.unwrap_or_default(),
+            buy_order_id: None,
+            sell_order_id: Some(sell_order_id),
+            taker_side: OrderSide::Buy,
+        };
+        let exchange = Self::settle(&mut tx, &settlement).await?;
+        Self::reduce_sell_order(&mut tx, &sell_order, fill).await?;
+
+        tx.commit()
             .await
-            .expect("Error getting currency amount")
-            .unwrap()
-            .amount
-            .unwrap() - drain_sum;
-        let drain_wallet_result = sqlx::query_as!(CurrencyAmount,
-            "UPDATE currency_amount SET amount = $1 WHERE currency_id = $2 AND wallet_id = $3", drain_amount, outgoing_currency_id, outgoing_currency_wallet_id)
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(MatchOutcome::Traded(exchange))
+    }
+
+    async fn fill_or_kill_buy_order(&self, buy_order_id: i32) -> Result<MatchReport, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let mut buy_order = Self::lock_buy_order(&mut tx, buy_order_id).await?;
+        if !buy_order.status.is_some_and(|s| s.is_live()) {
+            return Err(DataError::OrderStatusError(format!("Buy order with id={} is no longer open", buy_order_id)));
+        }
+        let mut sell_orders = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders
+            WHERE sell_currency_id = $1 AND buy_currency_id = $2 AND remaining_amount > 0
+            AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+            AND limit_price <= $3
+            AND (expires_at IS NULL OR expires_at > now())
+            ORDER BY limit_price, priority_at, sell_order_id
+            FOR UPDATE",
+            buy_order.buy_currency_id, buy_order.sell_currency_id, buy_order.limit_price)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let mut report = MatchReport::default();
+        // Iceberg orders offer one slice at a time and move behind their price level on every refresh
+        while buy_order.remaining_amount.unwrap_or(0) > 0 {
+            let best = sell_orders.iter_mut()
+                .filter(|order| order.status.is_some_and(|s| s.is_live()) && order.shown_amount() > 0)
+                .min_by(|a, b| a.limit_price.partial_cmp(&b.limit_price).unwrap_or(Ordering::Equal)
+                    .then(a.priority_at.cmp(&b.priority_at))
+                    .then(a.sell_order_id.cmp(&b.sell_order_id)));
+            let Some(sell_order) = best else {
+                break;
+            };
+            let amount = buy_order.remaining_amount.unwrap_or(0).min(sell_order.shown_amount());
+            let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+            if sell_order.issuer_id == buy_order.issuer_id {
+                let mode = buy_order.self_trade_prevention.unwrap_or_default();
+                if mode.cancels_taker() {
+                    tx.rollback()
+                        .await
+                        .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+                    return self.prevent_fill_or_kill_self_trade(buy_order_id, sell_order_id, OrderSide::Buy).await;
+                }
+                let self_trade = SelfTradeMatch {
+                    mode,
+                    taker_side: OrderSide::Buy,
+                    buy_order: Some(&buy_order),
+                    sell_order: Some(&*sell_order),
+                    amount,
+                    exchange_rate: sell_order.limit_price.unwrap_or_default(),
+                };
+                report.prevented_trades.push(Self::prevent_self_trade(&mut tx, &self_trade).await?);
+                buy_order = Self::lock_buy_order(&mut tx, buy_order_id).await?;
+                *sell_order = Self::lock_sell_order(&mut tx, sell_order_id).await?;
+                continue;
+            }
+            let settlement = Settlement {
+                buyer_id: buy_order.issuer_id.unwrap_or_default(),
+                seller_id: sell_order.issuer_id.unwrap_or_default(),
+                base_currency_id: buy_order.buy_currency_id.unwrap_or_default(),
+                quote_currency_id: buy_order.sell_currency_id.unwrap_or_default(),
+                amount,
+                exchange_rate: sell_order.limit_price.unwrap_or_default(),
+                buy_order_id: Some(buy_order_id),
+                sell_order_id: sell_order.sell_order_id,
+                taker_side: OrderSide::Buy,
+            };
+            report.exchanges.push(Self::settle(&mut tx, &settlement).await?);
+            Self::reduce_buy_order(&mut tx, &buy_order, amount).await?;
+            Self::reduce_sell_order(&mut tx, sell_order, amount).await?;
+            let now = OffsetDateTime::now_utc();
+            buy_order.apply_fill(amount, now);
+            sell_order.apply_fill(amount, now);
+        }
+        if buy_order.remaining_amount.unwrap_or(0) > 0 {
+            // Dropping the transaction rolls back every fill made above
+            return Err(DataError::FillOrKillError(format!("Buy order with id={} cannot be filled in full", buy_order_id)));
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(report)
+    }
+
+    async fn fill_or_kill_sell_order(&self, sell_order_id: i32) -> Result<MatchReport, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let sell_order = sqlx::query_as!(SellOrder,
+            "SELECT sell_order_id, issuer_id, sell_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, sell_currency_id, buy_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM sell_orders WHERE sell_order_id = $1", sell_order_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Sell order with id={} not found", sell_order_id)))?;
+        // Buy side is always locked first so concurrent settlements cannot deadlock
+        let mut buy_orders = sqlx::query_as!(BuyOrder,
+            "SELECT buy_order_id, issuer_id, buy_currency_amount, filled_amount, remaining_amount, status as \"status: OrderStatus\", created_at, updated_at, expires_at, priority_at, buy_currency_id, sell_currency_id, limit_price, time_in_force as \"time_in_force: TimeInForce\", order_group_id, display_amount, visible_amount, client_order_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\"
+            FROM buy_orders
+            WHERE buy_currency_id = $1 AND sell_currency_id = $2 AND remaining_amount > 0
+            AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+            AND limit_price >= $3
+            AND (expires_at IS NULL OR expires_at > now())
+            ORDER BY limit_price DESC, priority_at, buy_order_id
+            FOR UPDATE",
+            sell_order.sell_currency_id, sell_order.buy_currency_id, sell_order.limit_price)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let mut sell_order = Self::lock_sell_order(&mut tx, sell_order_id).await?;
+        if !sell_order.status.is_some_and(|s| s.is_live()) {
+            return Err(DataError::OrderStatusError(format!("Sell order with id={} is no longer open", sell_order_id)));
+        }
+        let mut report = MatchReport::default();
+        // Iceberg orders offer one slice at a time and move behind their price level on every refresh
+        while sell_order.remaining_amount.unwrap_or(0) > 0 {
+            let best = buy_orders.iter_mut()
+                .filter(|order| order.status.is_some_and(|s| s.is_live()) && order.shown_amount() > 0)
+                .min_by(|a, b| b.limit_price.partial_cmp(&a.limit_price).unwrap_or(Ordering::Equal)
+                    .then(a.priority_at.cmp(&b.priority_at))
+                    .then(a.buy_order_id.cmp(&b.buy_order_id)));
+            let Some(buy_order) = best else {
+                break;
+            };
+            let amount = sell_order.remaining_amount.unwrap_or(0).min(buy_order.shown_amount());
+            let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+            if buy_order.issuer_id == sell_order.issuer_id {
+                let mode = sell_order.self_trade_prevention.unwrap_or_default();
+                if mode.cancels_taker() {
+                    tx.rollback()
+                        .await
+                        .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+                    return self.prevent_fill_or_kill_self_trade(buy_order_id, sell_order_id, OrderSide::Sell).await;
+                }
+                let self_trade = SelfTradeMatch {
+                    mode,
+                    taker_side: OrderSide::Sell,
+                    buy_order: Some(&*buy_order),
+                    sell_order: Some(&sell_order),
+                    amount,
+                    exchange_rate: buy_order.limit_price.unwrap_or_default(),
+                };
+                report.prevented_trades.push(Self::prevent_self_trade(&mut tx, &self_trade).await?);
+                *buy_order = Self::lock_buy_order(&mut tx, buy_order_id).await?;
+                sell_order = Self::lock_sell_order(&mut tx, sell_order_id).await?;
+                continue;
+            }
+            let settlement = Settlement {
+                buyer_id: buy_order.issuer_id.unwrap_or_default(),
+                seller_id: sell_order.issuer_id.unwrap_or_default(),
+                base_currency_id: buy_order.buy_currency_id.unwrap_or_default(),
+                quote_currency_id: buy_order.sell_currency_id.unwrap_or_default(),
+                amount,
+                exchange_rate: buy_order.limit_price.unwrap_or_default(),
+                buy_order_id: buy_order.buy_order_id,
+                sell_order_id: Some(sell_order_id),
+                taker_side: OrderSide::Sell,
+            };
+            report.exchanges.push(Self::settle(&mut tx, &settlement).await?);
+            Self::reduce_buy_order(&mut tx, buy_order, amount).await?;
+            Self::reduce_sell_order(&mut tx, &sell_order, amount).await?;
+            let now = OffsetDateTime::now_utc();
+            buy_order.apply_fill(amount, now);
+            sell_order.apply_fill(amount, now);
+        }
+        if sell_order.remaining_amount.unwrap_or(0) > 0 {
+            // Dropping the transaction rolls back every fill made above
+            return Err(DataError::FillOrKillError(format!("Sell order with id={} cannot be filled in full", sell_order_id)));
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(report)
+    }
+}
+
+#[async_trait::async_trait]
+impl MarketDataRepository for Repository {
+    async fn find_bid_levels(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        depth: i64
+    ) -> Result<Vec<PriceLevel>, DataError> {
+        sqlx::query_as!(PriceLevel,
+            "SELECT limit_price as \"price!\", SUM(LEAST(remaining_amount, visible_amount))::BIGINT as \"size!\", COUNT(*) as \"order_count!\"
+            FROM buy_orders
+            WHERE buy_currency_id = $1 AND sell_currency_id = $2
+                AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+                AND remaining_amount > 0
+                AND limit_price IS NOT NULL
+                AND (expires_at IS NULL OR expires_at > now())
+            GROUP BY limit_price
+            ORDER BY limit_price DESC
+            LIMIT $3",
+            base_currency_id, quote_currency_id, depth)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))
+    }
+
+    async fn find_ask_levels(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        depth: i64
+    ) -> Result<Vec<PriceLevel>, DataError> {
+        sqlx::query_as!(PriceLevel,
+            "SELECT limit_price as \"price!\", SUM(LEAST(remaining_amount, visible_amount))::BIGINT as \"size!\", COUNT(*) as \"order_count!\"
+            FROM sell_orders
+            WHERE sell_currency_id = $1 AND buy_currency_id = $2
+                AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd')
+                AND remaining_amount > 0
+                AND limit_price IS NOT NULL
+                AND (expires_at IS NULL OR expires_at > now())
+            GROUP BY limit_price
+            ORDER BY limit_price
+            LIMIT $3",
+            base_currency_id, quote_currency_id, depth)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))
+    }
+
+    async fn find_trades(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        params: &TradeQueryParams
+    ) -> Result<Vec<Trade>, DataError> {
+        sqlx::query_as!(Trade,
+            "SELECT id as trade_id, base_currency_id, quote_currency_id, exchange_rate as price, amount, income as quote_amount,
+                buy_order_id, sell_order_id,
+                CASE taker_side WHEN 'buy' THEN sell_order_id WHEN 'sell' THEN buy_order_id END as maker_order_id,
+                CASE taker_side WHEN 'buy' THEN buy_order_id WHEN 'sell' THEN sell_order_id END as taker_order_id,
+                taker_side as \"taker_side: OrderSide\", created_at
+            FROM currency_exchange
+            WHERE base_currency_id = $1 AND quote_currency_id = $2
+                AND ($3::timestamptz IS NULL OR created_at >= $3)
+                AND ($4::timestamptz IS NULL OR created_at < $4)
+                AND ($5::int IS NULL OR id < $5)
+            ORDER BY id DESC
+            LIMIT $6",
+            base_currency_id, quote_currency_id, params.from, params.to, params.cursor, params.limit())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))
+    }
+
+    async fn find_user_trades(&self, user_id: &i32, params: &TradeQueryParams) -> Result<Vec<UserTrade>, DataError> {
+        sqlx::query_as!(UserTrade,
+            "SELECT id as trade_id, base_currency_id, quote_currency_id, exchange_rate as price, amount, income as quote_amount,
+                (CASE WHEN buyer_id = $1 THEN 'buy' ELSE 'sell' END)::order_side as \"side!: OrderSide\",
+                CASE WHEN buyer_id = $1 THEN buy_order_id ELSE sell_order_id END as order_id,
+                CASE WHEN buyer_id = $1 THEN taker_side = 'sell' ELSE taker_side = 'buy' END as is_maker,
+                created_at
+            FROM currency_exchange
+            WHERE (buyer_id = $1 OR seller_id = $1)
+                AND ($2::timestamptz IS NULL OR created_at >= $2)
+                AND ($3::timestamptz IS NULL OR created_at < $3)
+                AND ($4::int IS NULL OR id < $4)
+            ORDER BY id DESC
+            LIMIT $5",
+            user_id, params.from, params.to, params.cursor, params.limit())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))
+    }
+
+    async fn find_candles(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        params: &CandleQueryParams
+    ) -> Result<Vec<Candle>, DataError> {
+        let mut candles = sqlx::query_as!(Candle,
+            "SELECT base_currency_id, quote_currency_id, interval as \"interval: CandleInterval\", bucket_start,
+                open, high, low, close, volume, quote_volume, trade_count
+            FROM candles
+            WHERE base_currency_id = $1 AND quote_currency_id = $2 AND interval = $3
+                AND ($4::timestamptz IS NULL OR bucket_start >= $4)
+                AND ($5::timestamptz IS NULL OR bucket_start < $5)
+            ORDER BY bucket_start DESC
+            LIMIT $6",
+            base_currency_id, quote_currency_id, params.interval as CandleInterval, params.from, params.to, MAX_CANDLES)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))?;
+        candles.reverse();
+        Ok(candles)
+    }
+
+    async fn backfill_candles(
+        &self,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        from: OffsetDateTime,
+        to: OffsetDateTime
+    ) -> Result<u64, DataError> {
+        let result = sqlx::query!(
+            "INSERT INTO candles(base_currency_id, quote_currency_id, interval, bucket_start, open, high, low, close,
+                volume, quote_volume, trade_count, first_trade_id, last_trade_id)
+            SELECT $1, $2, i.interval, date_bin(candle_step(i.interval), e.created_at, TIMESTAMPTZ 'epoch'),
+                (array_agg(e.exchange_rate ORDER BY e.id))[1],
+                MAX(e.exchange_rate),
+                MIN(e.exchange_rate),
+                (array_agg(e.exchange_rate ORDER BY e.id DESC))[1],
+                SUM(e.amount),
+                COALESCE(SUM(e.income), 0),
+                COUNT(*),
+                MIN(e.id),
+                MAX(e.id)
+            FROM currency_exchange e
+            CROSS JOIN unnest(enum_range(NULL::candle_interval)) AS i(interval)
+            WHERE e.base_currency_id = $1 AND e.quote_currency_id = $2
+                AND e.created_at >= date_bin(candle_step(i.interval), $3, TIMESTAMPTZ 'epoch')
+                AND e.created_at < date_bin(candle_step(i.interval), $4, TIMESTAMPTZ 'epoch') + candle_step(i.interval)
+                AND date_bin(candle_step(i.interval), e.created_at, TIMESTAMPTZ 'epoch') + candle_step(i.interval) <= now()
+            GROUP BY i.interval, date_bin(candle_step(i.interval), e.created_at, TIMESTAMPTZ 'epoch')
+            ON CONFLICT (base_currency_id, quote_currency_id, interval, bucket_start) DO UPDATE SET
+                open = EXCLUDED.open,
+                high = EXCLUDED.high,
+                low = EXCLUDED.low,
+                close = EXCLUDED.close,
+                volume = EXCLUDED.volume,
+                quote_volume = EXCLUDED.quote_volume,
+                trade_count = EXCLUDED.trade_count,
+                first_trade_id = EXCLUDED.first_trade_id,
+                last_trade_id = EXCLUDED.last_trade_id",
+            base_currency_id, quote_currency_id, from, to)
             .execute(&self.pool)
             .await
-            .expect("Error draining currency amount");
-        if replenish_wallet_result.rows_affected() > 0 && drain_wallet_result.rows_affected() > 0 {
-            let exchanged_amount = sqlx::query_as!(CurrencyAmountQuery,
-            "SELECT * FROM currency_amount WHERE currency_id = $1", incoming_currency_id)
-                .fetch_optional(&self.pool)
+            .map_err(|e| DataError::MarketDataError(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    async fn find_tickers(
+        &self,
+        base_currency_id: Option<i32>,
+        quote_currency_id: Option<i32>
+    ) -> Result<Vec<Ticker>, DataError> {
+        sqlx::query_as!(Ticker,
+            "SELECT b.currency_id as \"base_currency_id!\", b.currency_code as base_currency,
+                q.currency_id as \"quote_currency_id!\", q.currency_code as quote_currency,
+                last.exchange_rate as \"last_price?\",
+                (SELECT MAX(limit_price) FROM buy_orders
+                    WHERE buy_currency_id = b.currency_id AND sell_currency_id = q.currency_id
+                        AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd') AND remaining_amount > 0
+                        AND (expires_at IS NULL OR expires_at > now())) as best_bid,
+                (SELECT MIN(limit_price) FROM sell_orders
+                    WHERE sell_currency_id = b.currency_id AND buy_currency_id = q.currency_id
+                        AND status IN ('open', 'partially_filled') AND time_in_force IN ('gtc', 'gtd') AND remaining_amount > 0
+                        AND (expires_at IS NULL OR expires_at > now())) as best_ask,
+                day.high as high_24h,
+                day.low as low_24h,
+                COALESCE(day.volume, 0) as \"volume_24h!\",
+                COALESCE(day.quote_volume, 0) as \"quote_volume_24h!\",
+                day.trade_count as \"trade_count_24h!\",
+                CASE WHEN day.open > 0 THEN (last.exchange_rate - day.open) / day.open * 100 END as change_percent_24h
+            FROM currencies b
+            CROSS JOIN currencies q
+            LEFT JOIN LATERAL (
+                SELECT exchange_rate FROM currency_exchange
+                WHERE base_currency_id = b.currency_id AND quote_currency_id = q.currency_id
+                ORDER BY id DESC LIMIT 1
+            ) last ON true
+            CROSS JOIN LATERAL (
+                SELECT MAX(exchange_rate) as high, MIN(exchange_rate) as low, SUM(amount)::BIGINT as volume,
+                    SUM(income) as quote_volume, COUNT(*) as trade_count, (array_agg(exchange_rate ORDER BY id))[1] as open
+                FROM currency_exchange
+                WHERE base_currency_id = b.currency_id AND quote_currency_id = q.currency_id
+                    AND created_at >= now() - INTERVAL '24 hours'
+            ) day
+            WHERE b.currency_id <> q.currency_id
+                AND ($1::int IS NULL OR b.currency_id = $1)
+                AND ($2::int IS NULL OR q.currency_id = $2)
+            ORDER BY b.currency_code, q.currency_code",
+            base_currency_id, quote_currency_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl StopOrderRepository for Repository {
+    async fn create_stop_order(&self, issuer_id: &i32, request: &CreateStopOrderRequest) -> Result<StopOrder, DataError> {
+        let created_at = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Self::check_trading_halt(&mut tx, request.base_currency_id, request.quote_currency_id).await?;
+        let order = sqlx::query_as!(StopOrder,
+            "INSERT INTO stop_orders(issuer_id, side, base_currency_id, quote_currency_id, amount, stop_price, limit_price, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            RETURNING stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id",
+            issuer_id, request.side as OrderSide, request.base_currency_id, request.quote_currency_id,
+            request.amount, request.stop_price, request.limit_price, created_at)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Ok(order)
+    }
+
+    async fn find_stop_orders(&self, issuer_id: &i32, status: Option<StopOrderStatus>) -> Result<Vec<StopOrder>, DataError> {
+        sqlx::query_as!(StopOrder,
+            "SELECT stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id
+            FROM stop_orders
+            WHERE issuer_id = $1 AND ($2::stop_order_status IS NULL OR status = $2)
+            ORDER BY created_at DESC, stop_order_id DESC",
+            issuer_id, status as Option<StopOrderStatus>)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))
+    }
+
+    async fn cancel_stop_order(&self, stop_order_id: &i32, issuer_id: &i32) -> Result<StopOrder, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let stop_order = sqlx::query_as!(StopOrder,
+            "SELECT stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id
+            FROM stop_orders WHERE stop_order_id = $1 FOR UPDATE", stop_order_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Stop order with id={} not found", stop_order_id)))?;
+        if stop_order.issuer_id != *issuer_id {
+            return Err(DataError::OrderOwnershipError(
+                format!("Stop order with id={} does not belong to user with id={}", stop_order_id, issuer_id)
+            ));
+        }
+        if stop_order.status != StopOrderStatus::Pending {
+            return Err(DataError::OrderStatusError(format!("Stop order with id={} is no longer pending", stop_order_id)));
+        }
+        let cancelled = sqlx::query_as!(StopOrder,
+            "UPDATE stop_orders SET status = 'cancelled', updated_at = $2
+            WHERE stop_order_id = $1
+            RETURNING stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id",
+            stop_order_id, OffsetDateTime::now_utc())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        if let Some(order_group_id) = cancelled.order_group_id {
+            Self::cancel_group_legs(&mut tx, order_group_id).await?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(cancelled)
+    }
+
+    async fn last_trade_price(&self, base_currency_id: &i32, quote_currency_id: &i32) -> Result<Option<f64>, DataError> {
+        sqlx::query_scalar!(
+            "SELECT exchange_rate FROM currency_exchange
+            WHERE base_currency_id = $1 AND quote_currency_id = $2
+            ORDER BY id DESC LIMIT 1",
+            base_currency_id, quote_currency_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::MarketDataError(e.to_string()))
+    }
+
+    async fn find_stop_order_pairs(&self) -> Result<Vec<(i32, i32)>, DataError> {
+        let rows = sqlx::query!(
+            "SELECT DISTINCT base_currency_id, quote_currency_id FROM stop_orders WHERE status = 'pending'")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(rows.into_iter().map(|row| (row.base_currency_id, row.quote_currency_id)).collect())
+    }
+
+    async fn trigger_stop_orders(&self, base_currency_id: &i32, quote_currency_id: &i32, price: f64) -> Result<Vec<StopOrder>, DataError> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let triggered = sqlx::query_as!(StopOrder,
+            "WITH triggered AS (
+                UPDATE stop_orders SET status = 'triggered', triggered_at = $4, trigger_price = $3, updated_at = $4
+                WHERE stop_order_id IN (
+                    SELECT stop_order_id FROM stop_orders
+                    WHERE base_currency_id = $1 AND quote_currency_id = $2 AND status = 'pending'
+                    AND ((side = 'buy' AND stop_price <= $3) OR (side = 'sell' AND stop_price >= $3))
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING *
+            ),
+            cancelled_buy_orders AS (
+                UPDATE buy_orders SET status = 'cancelled', updated_at = $4
+                WHERE order_group_id IN (SELECT order_group_id FROM triggered) AND status IN ('open', 'partially_filled')
+            ),
+            cancelled_sell_orders AS (
+                UPDATE sell_orders SET status = 'cancelled', updated_at = $4
+                WHERE order_group_id IN (SELECT order_group_id FROM triggered) AND status IN ('open', 'partially_filled')
+            )
+            SELECT stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id
+            FROM triggered
+            ORDER BY created_at, stop_order_id",
+            base_currency_id, quote_currency_id, price, now)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let order_group_ids: Vec<i32> = triggered.iter().filter_map(|stop_order| stop_order.order_group_id).collect();
+        if !order_group_ids.is_empty() {
+            let buy_order_ids = sqlx::query_scalar!(
+                "SELECT buy_order_id FROM buy_orders WHERE order_group_id = ANY($1)", &order_group_ids)
+                .fetch_all(&mut *tx)
                 .await
-                .expect("Error replenishing currency amount");
-            Ok(exchanged_amount)
-        } else {
-            Err(DataError::CurrencyExchangeError("Error during currency exchange".to_string()))
+                .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+            let sell_order_ids = sqlx::query_scalar!(
+                "SELECT sell_order_id FROM sell_orders WHERE order_group_id = ANY($1)", &order_group_ids)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+            Self::release_buy_holds(&mut tx, &buy_order_ids, 0, 0).await?;
+            Self::release_sell_holds(&mut tx, &sell_order_ids, 0, 0).await?;
         }
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(triggered)
+    }
+
+    async fn link_stop_order(&self, stop_order_id: &i32, buy_order_id: Option<i32>, sell_order_id: Option<i32>) -> Result<StopOrder, DataError> {
+        sqlx::query_as!(StopOrder,
+            "UPDATE stop_orders SET buy_order_id = $2, sell_order_id = $3, updated_at = $4
+            WHERE stop_order_id = $1
+            RETURNING stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id",
+            stop_order_id, buy_order_id, sell_order_id, OffsetDateTime::now_utc())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Stop order with id={} not found", stop_order_id)))
+    }
+
+    async fn link_stop_order_exchange(&self, stop_order_id: &i32, exchange_id: &i32) -> Result<StopOrder, DataError> {
+        sqlx::query_as!(StopOrder,
+            "UPDATE stop_orders SET exchange_id = $2, updated_at = $3
+            WHERE stop_order_id = $1
+            RETURNING stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id",
+            stop_order_id, exchange_id, OffsetDateTime::now_utc())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Stop order with id={} not found", stop_order_id)))
     }
-}
\ No newline at end of file
+
+    async fn reject_stop_order(&self, stop_order_id: &i32, reason: &str) -> Result<StopOrder, DataError> {
+        sqlx::query_as!(StopOrder,
+            "UPDATE stop_orders SET status = 'rejected', reject_reason = $2, updated_at = $3
+            WHERE stop_order_id = $1
+            RETURNING stop_order_id, issuer_id, side as \"side: OrderSide\", base_currency_id, quote_currency_id, amount, stop_price, limit_price, status as \"status: StopOrderStatus\", created_at, updated_at, triggered_at, trigger_price, buy_order_id, sell_order_id, exchange_id, reject_reason, order_group_id",
+            stop_order_id, reason, OffsetDateTime::now_utc())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Stop order with id={} not found", stop_order_id)))
+    }
+}
+
+#[async_trait::async_trait]
+impl OrderGroupRepository for Repository {
+    async fn create_oco_order(&self, issuer_id: &i32, request: &CreateOcoOrderRequest, max_open_orders: i64) -> Result<OrderGroup, DataError> {
+        let created_at = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Self::check_trading_halt(&mut tx, request.base_currency_id, request.quote_currency_id).await?;
+        Self::check_open_order_limit(&mut tx, *issuer_id, max_open_orders).await?;
+        let order_group_id = sqlx::query_scalar!(
+            "INSERT INTO order_groups(issuer_id, created_at) VALUES ($1, $2) RETURNING order_group_id",
+            issuer_id, created_at)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        let limit_order = match request.side {
+            OrderSide::Buy => {
+                let held_amount = hold_amount(request.amount, request.limit_price);
+                Self::hold_funds(&mut tx, *issuer_id, request.quote_currency_id, held_amount).await?;
+                sqlx::query!(
+                    "INSERT INTO buy_orders(issuer_id, buy_currency_amount, remaining_amount, buy_currency_id, sell_currency_id, limit_price, created_at, updated_at, priority_at, time_in_force, order_group_id, held_amount)
+                    VALUES ($1, $2, $2, $3, $4, $5, $6, $6, $6, 'gtc', $7, $8)",
+                    issuer_id, request.amount, request.base_currency_id, request.quote_currency_id, request.limit_price, created_at, order_group_id, held_amount)
+                    .execute(&mut *tx)
+                    .await
+            }
+            OrderSide::Sell => {
+                Self::hold_funds(&mut tx, *issuer_id, request.base_currency_id, request.amount).await?;
+                sqlx::query!(
+                    "INSERT INTO sell_orders(issuer_id, sell_currency_amount, remaining_amount, sell_currency_id, buy_currency_id, limit_price, created_at, updated_at, priority_at, time_in_force, order_group_id, held_amount)
+                    VALUES ($1, $2, $2, $3, $4, $5, $6, $6, $6, 'gtc', $7, $2)",
+                    issuer_id, request.amount, request.base_currency_id, request.quote_currency_id, request.limit_price, created_at, order_group_id)
+                    .execute(&mut *tx)
+                    .await
+            }
+        };
+        limit_order.map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        sqlx::query!(
+            "INSERT INTO stop_orders(issuer_id, side, base_currency_id, quote_currency_id, amount, stop_price, limit_price, created_at, updated_at, order_group_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8, $9)",
+            issuer_id, request.side as OrderSide, request.base_currency_id, request.quote_currency_id,
+            request.amount, request.stop_price, request.stop_limit_price, created_at, order_group_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        let group = Self::load_order_group(&mut tx, order_group_id).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderCreationError(e.to_string()))?;
+        Ok(group)
+    }
+
+    async fn find_order_group(&self, order_group_id: &i32) -> Result<Option<OrderGroup>, DataError> {
+        let mut conn = self.pool.acquire()
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        let exists = sqlx::query_scalar!(
+            "SELECT order_group_id FROM order_groups WHERE order_group_id = $1", order_group_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| DataError::OrderNotFoundError(e.to_string()))?;
+        match exists {
+            Some(order_group_id) => Self::load_order_group(&mut conn, order_group_id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn cancel_order_group(&self, order_group_id: &i32, issuer_id: &i32) -> Result<OrderGroup, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        let owner = sqlx::query_scalar!(
+            "SELECT issuer_id FROM order_groups WHERE order_group_id = $1 FOR UPDATE", order_group_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .ok_or(DataError::OrderNotFoundError(format!("Order group with id={} not found", order_group_id)))?;
+        if owner != *issuer_id {
+            return Err(DataError::OrderOwnershipError(
+                format!("Order group with id={} does not belong to user with id={}", order_group_id, issuer_id)
+            ));
+        }
+        let working = sqlx::query_scalar!(
+            "SELECT
+                EXISTS(SELECT 1 FROM buy_orders WHERE order_group_id = $1 AND status IN ('open', 'partially_filled'))
+                OR EXISTS(SELECT 1 FROM sell_orders WHERE order_group_id = $1 AND status IN ('open', 'partially_filled'))
+                OR EXISTS(SELECT 1 FROM stop_orders WHERE order_group_id = $1 AND status = 'pending')",
+            order_group_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?
+            .unwrap_or(false);
+        if !working {
+            return Err(DataError::OrderStatusError(format!("Order group with id={} has no working orders", order_group_id)));
+        }
+        Self::cancel_group_legs(&mut tx, *order_group_id).await?;
+        let group = Self::load_order_group(&mut tx, *order_group_id).await?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+        Ok(group)
+    }
+}
+
+#[async_trait::async_trait]
+impl BatchOrderRepository for Repository {
+    async fn apply_order_batch(&self, issuer_id: &i32, items: &[BatchOrderItem], max_open_orders: i64) -> Result<Vec<BatchOrderResult>, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::OrderBatchError(e.to_string()))?;
+        let mut results = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            let result = match item {
+                BatchOrderItem::CreateBuy(request) => Self::insert_buy_order(&mut tx, request, max_open_orders).await
+                    .map(|order| BatchOrderResult { buy_order: Some(order), ..Default::default() }),
+                BatchOrderItem::CreateSell(request) => Self::insert_sell_order(&mut tx, request, max_open_orders).await
+                    .map(|order| BatchOrderResult { sell_order: Some(order), ..Default::default() }),
+                BatchOrderItem::CancelBuy { buy_order_id } => Self::cancel_owned_buy_order(&mut tx, *buy_order_id, *issuer_id).await
+                    .map(|order| BatchOrderResult { buy_order: Some(order), ..Default::default() }),
+                BatchOrderItem::CancelSell { sell_order_id } => Self::cancel_owned_sell_order(&mut tx, *sell_order_id, *issuer_id).await
+                    .map(|order| BatchOrderResult { sell_order: Some(order), ..Default::default() }),
+            };
+            results.push(result.map_err(|e| DataError::OrderBatchError(format!("Batch item {} failed: {}", index, e)))?);
+        }
+        tx.commit()
+            .await
+            .map_err(|e| DataError::OrderBatchError(e.to_string()))?;
+        Ok(results)
+    }
+}
+
+#[async_trait::async_trait]
+impl TradingHaltRepository for Repository {
+    async fn halt_trading(
+        &self,
+        halted_by: &i32,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        request: &TradingHaltRequest
+    ) -> Result<TradingHaltResult, DataError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| DataError::TradingHaltedError(e.to_string()))?;
+        // Waits for every transaction that checked the pair, so none places or fills an order after the halt
+        sqlx::query!("SELECT pg_advisory_xact_lock(LEAST($1::INTEGER, $2::INTEGER), GREATEST($1::INTEGER, $2::INTEGER))",
+            base_currency_id, quote_currency_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DataError::TradingHaltedError(e.to_string()))?;
+        let halt = sqlx::query_as!(TradingHalt,
+            "INSERT INTO trading_halts(base_currency_id, quote_currency_id, mode, reason, halted_by, halted_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING
+            RETURNING trading_halt_id, base_currency_id, quote_currency_id, mode as \"mode: TradingHaltMode\", reason, halted_by, halted_at, resumed_at",
+            base_currency_id, quote_currency_id, request.mode as TradingHaltMode, request.reason, halted_by, OffsetDateTime::now_utc())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| DataError::TradingHaltedError(e.to_string()))?
+            .ok_or(DataError::TradingHaltedError(
+                format!("Trading between currencies with id={} and id={} is already halted", base_currency_id, quote_currency_id)
+            ))?;
+        tx.commit()
+            .await
+            .map_err(|e| DataError::TradingHaltedError(e.to_string()))?;
+        // Orders are cancelled once the halt is visible, so the sweep never waits on a settlement holding the pair
+        let cancelled_orders = match halt.mode {
+            TradingHaltMode::Cancel => {
+                let mut tx = self.pool.begin()
+                    .await
+                    .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+                let cancelled = Self::cancel_live_orders(&mut tx, None, Some((*base_currency_id, *quote_currency_id))).await?;
+                tx.commit()
+                    .await
+                    .map_err(|e| DataError::CurrencyExchangeError(e.to_string()))?;
+                cancelled
+            }
+            TradingHaltMode::Freeze => UserOrders { buy_orders: Vec::new(), sell_orders: Vec::new(), stop_orders: Vec::new() },
+        };
+        Ok(TradingHaltResult { halt, cancelled_orders })
+    }
+
+    async fn resume_trading(&self, base_currency_id: &i32, quote_currency_id: &i32) -> Result<TradingHalt, DataError> {
+        sqlx::query_as!(TradingHalt,
+            "UPDATE trading_halts SET resumed_at = $3
+            WHERE resumed_at IS NULL
+            AND LEAST(base_currency_id, quote_currency_id) = LEAST($1::INTEGER, $2::INTEGER)
+            AND GREATEST(base_currency_id, quote_currency_id) = GREATEST($1::INTEGER, $2::INTEGER)
+            RETURNING trading_halt_id, base_currency_id, quote_currency_id, mode as \"mode: TradingHaltMode\", reason, halted_by, halted_at, resumed_at",
+            base_currency_id, quote_currency_id, OffsetDateTime::now_utc())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::TradingHaltNotFoundError(e.to_string()))?
+            .ok_or(DataError::TradingHaltNotFoundError(
+                format!("Trading between currencies with id={} and id={} is not halted", base_currency_id, quote_currency_id)
+            ))
+    }
+
+    async fn find_trading_halt(&self, base_currency_id: &i32, quote_currency_id: &i32) -> Result<Option<TradingHalt>, DataError> {
+        sqlx::query_as!(TradingHalt,
+            "SELECT trading_halt_id, base_currency_id, quote_currency_id, mode as \"mode: TradingHaltMode\", reason, halted_by, halted_at, resumed_at
+            FROM trading_halts
+            WHERE resumed_at IS NULL
+            AND LEAST(base_currency_id, quote_currency_id) = LEAST($1::INTEGER, $2::INTEGER)
+            AND GREATEST(base_currency_id, quote_currency_id) = GREATEST($1::INTEGER, $2::INTEGER)",
+            base_currency_id, quote_currency_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::TradingHaltNotFoundError(e.to_string()))
+    }
+
+    async fn find_trading_halts(&self) -> Result<Vec<TradingHalt>, DataError> {
+        sqlx::query_as!(TradingHalt,
+            "SELECT trading_halt_id, base_currency_id, quote_currency_id, mode as \"mode: TradingHaltMode\", reason, halted_by, halted_at, resumed_at
+            FROM trading_halts WHERE resumed_at IS NULL ORDER BY halted_at, trading_halt_id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::TradingHaltNotFoundError(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl TradingSettingsRepository for Repository {
+    async fn find_trading_settings(&self, user_id: &i32) -> Result<TradingSettings, DataError> {
+        let settings = sqlx::query_as!(TradingSettings,
+            "SELECT user_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\", updated_at
+            FROM trading_settings WHERE user_id = $1",
+            user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DataError::TradingSettingsError(e.to_string()))?;
+        Ok(settings.unwrap_or(TradingSettings {
+            user_id: *user_id,
+            self_trade_prevention: SelfTradePrevention::default(),
+            updated_at: None,
+        }))
+    }
+
+    async fn update_trading_settings(&self, user_id: &i32, request: &TradingSettingsRequest) -> Result<TradingSettings, DataError> {
+        sqlx::query_as!(TradingSettings,
+            "INSERT INTO trading_settings(user_id, self_trade_prevention, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE SET self_trade_prevention = EXCLUDED.self_trade_prevention, updated_at = EXCLUDED.updated_at
+            RETURNING user_id, self_trade_prevention as \"self_trade_prevention: SelfTradePrevention\", updated_at",
+            user_id, request.self_trade_prevention as SelfTradePrevention, OffsetDateTime::now_utc())
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| DataError::TradingSettingsError(e.to_string()))
+    }
+
+    async fn find_prevented_trades(&self, user_id: &i32) -> Result<Vec<PreventedTrade>, DataError> {
+        sqlx::query_as!(PreventedTrade,
+            "SELECT prevented_trade_id, user_id, mode as \"mode: SelfTradePrevention\", taker_side as \"taker_side: OrderSide\",
+                buy_order_id, sell_order_id, base_currency_id, quote_currency_id, amount, exchange_rate, created_at
+            FROM prevented_trades WHERE user_id = $1
+            ORDER BY prevented_trade_id DESC",
+            user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DataError::TradingSettingsError(e.to_string()))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::datasource::api_models::CreateStopOrderRequest;
+use crate::datasource::errors::DataError;
+use crate::datasource::models::{StopOrder, StopOrderStatus};
+
+#[async_trait::async_trait]
+pub trait StopOrderRepository {
+    async fn create_stop_order(&self, issuer_id: &i32, request: &CreateStopOrderRequest) -> Result<StopOrder, DataError>;
+
+    ///
+    /// Stop orders of the user, newest first, optionally only those in `status`
+    async fn find_stop_orders(&self, issuer_id: &i32, status: Option<StopOrderStatus>) -> Result<Vec<StopOrder>, DataError>;
+
+    ///
+    /// Cancels a pending stop order owned by `issuer_id`
+    async fn cancel_stop_order(&self, stop_order_id: &i32, issuer_id: &i32) -> Result<StopOrder, DataError>;
+
+    ///
+    /// Price of the most recent exchange on the pair
+    async fn last_trade_price(&self, base_currency_id: &i32, quote_currency_id: &i32) -> Result<Option<f64>, DataError>;
+
+    ///
+    /// Pairs with at least one pending stop order
+    async fn find_stop_order_pairs(&self) -> Result<Vec<(i32, i32)>, DataError>;
+
+    ///
+    /// Moves every pending stop on the pair that a trade at `price` sets off to triggered.
+    /// Each stop is claimed once, the claimed stops come back oldest first
+    async fn trigger_stop_orders(&self, base_currency_id: &i32, quote_currency_id: &i32, price: f64) -> Result<Vec<StopOrder>, DataError>;
+
+    ///
+    /// Links a triggered stop-limit order to the limit order it placed
+    async fn link_stop_order(&self, stop_order_id: &i32, buy_order_id: Option<i32>, sell_order_id: Option<i32>) -> Result<StopOrder, DataError>;
+
+    ///
+    /// Links a triggered stop-market order to the first trade it made
+    async fn link_stop_order_exchange(&self, stop_order_id: &i32, exchange_id: &i32) -> Result<StopOrder, DataError>;
+
+    ///
+    /// Marks a triggered stop whose order could not be placed
+    async fn reject_stop_order(&self, stop_order_id: &i32, reason: &str) -> Result<StopOrder, DataError>;
+}
@@ -0,0 +1,27 @@
+use crate::datasource::api_models::TradingHaltRequest;
+use crate::datasource::errors::DataError;
+use crate::datasource::models::{TradingHalt, TradingHaltResult};
+
+#[async_trait::async_trait]
+pub trait TradingHaltRepository {
+    ///
+    /// Halts trading between the two currencies in both directions, cancelling the live orders
+    /// on the pair when `request.mode` is cancel
+    async fn halt_trading(
+        &self,
+        halted_by: &i32,
+        base_currency_id: &i32,
+        quote_currency_id: &i32,
+        request: &TradingHaltRequest
+    ) -> Result<TradingHaltResult, DataError>;
+
+    ///
+    /// Lifts the active halt on the pair, frozen orders match again with the next order that crosses them
+    async fn resume_trading(&self, base_currency_id: &i32, quote_currency_id: &i32) -> Result<TradingHalt, DataError>;
+
+    async fn find_trading_halt(&self, base_currency_id: &i32, quote_currency_id: &i32) -> Result<Option<TradingHalt>, DataError>;
+
+    ///
+    /// Halts in force, oldest first
+    async fn find_trading_halts(&self) -> Result<Vec<TradingHalt>, DataError>;
+}
@@ -0,0 +1,18 @@
+use crate::datasource::api_models::TradingSettingsRequest;
+use crate::datasource::errors::DataError;
+use crate::datasource::models::{PreventedTrade, TradingSettings};
+
+#[async_trait::async_trait]
+pub trait TradingSettingsRepository {
+    ///
+    /// Settings of the user's account, the defaults when none were stored yet
+    async fn find_trading_settings(&self, user_id: &i32) -> Result<TradingSettings, DataError>;
+
+    ///
+    /// Stores the user's settings, orders placed earlier keep the mode they were placed with
+    async fn update_trading_settings(&self, user_id: &i32, request: &TradingSettingsRequest) -> Result<TradingSettings, DataError>;
+
+    ///
+    /// Self-trades prevented for the user, newest first
+    async fn find_prevented_trades(&self, user_id: &i32) -> Result<Vec<PreventedTrade>, DataError>;
+}
@@ -18,6 +18,13 @@ pub trait WalletRepository {
         &self, 
         request: &BalanceRequest
     ) -> Result<Option<CurrencyBalance>, DataError>;
+
+    ///
+    /// Every currency balance held in the user's wallets
+    async fn find_user_balances(
+        &self,
+        user_id: &i32
+    ) -> Result<Vec<CurrencyBalance>, DataError>;
     
     async fn find_wallet_by_incoming_currency(
         &self, 
@@ -0,0 +1,210 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::web::{Data, Path, Query};
+use serde::Serialize;
+use sqlx::PgPool;
+use currency_exchange_data::datasource::api_models::{parse_currency_pair, MassCancelQueryParams};
+use currency_exchange_data::datasource::error_responses::{OrderUpdateFailedResponse, TradingHaltFailedResponse};
+use currency_exchange_data::datasource::models::UserOrders;
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::repository::order_group_repository::OrderGroupRepository;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_data::datasource::repository::stop_order_repository::StopOrderRepository;
+use currency_exchange_data::datasource::repository::trading_halt_repository::TradingHaltRepository;
+use currency_exchange_middleware::jwt::Claims;
+use crate::get_handlers::currency_pair;
+use crate::models::TradingAdmins;
+use crate::order_events::OrderEventBus;
+
+///
+/// Cancels a buy order owned by the caller
+pub async fn cancel_buy_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    path: Path<i32>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let repo = Repository::new(pool.get_ref().clone());
+        let cancelled = repo.cancel_buy_order(&path.into_inner(), &uid).await;
+        if let Ok(order) = &cancelled {
+            events.buy_order_changed(order);
+            publish_order_group(&repo, &events, order.order_group_id).await;
+        }
+        order_update_response(cancelled)
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Cancels a sell order owned by the caller
+pub async fn cancel_sell_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    path: Path<i32>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let repo = Repository::new(pool.get_ref().clone());
+        let cancelled = repo.cancel_sell_order(&path.into_inner(), &uid).await;
+        if let Ok(order) = &cancelled {
+            events.sell_order_changed(order);
+            publish_order_group(&repo, &events, order.order_group_id).await;
+        }
+        order_update_response(cancelled)
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Cancels a pending stop order owned by the caller
+pub async fn cancel_stop_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    path: Path<i32>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let repo = Repository::new(pool.get_ref().clone());
+        let cancelled = repo.cancel_stop_order(&path.into_inner(), &uid).await;
+        if let Ok(order) = &cancelled {
+            events.stop_order_changed(order);
+            publish_order_group(&repo, &events, order.order_group_id).await;
+        }
+        order_update_response(cancelled)
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Cancels every working leg of an order group owned by the caller
+pub async fn cancel_order_group(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    path: Path<i32>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let repo = Repository::new(pool.get_ref().clone());
+        let cancelled = repo.cancel_order_group(&path.into_inner(), &uid).await;
+        if let Ok(group) = &cancelled {
+            events.order_group_changed(group);
+        }
+        order_update_response(cancelled)
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Kill switch: cancels every live order and pending stop order of the caller,
+/// only those on `pair` in either direction when given
+pub async fn cancel_my_orders(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    query: Query<MassCancelQueryParams>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let repo = Repository::new(pool.get_ref().clone());
+        let pair = match query.pair.as_deref().map(parse_currency_pair) {
+            None => None,
+            Some(Ok((base_code, quote_code))) => match currency_pair(&repo, base_code, quote_code).await {
+                Ok(pair) => Some(pair),
+                Err(response) => return response,
+            },
+            Some(Err(message)) => return HttpResponse::BadRequest().json(OrderUpdateFailedResponse::new(message)),
+        };
+        let cancelled = repo.cancel_user_orders(&uid, pair).await;
+        if let Ok(orders) = &cancelled {
+            publish_cancelled_orders(&events, orders);
+        }
+        order_update_response(cancelled)
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Lifts the trading halt on `base`/`quote`, only users listed in `ADMIN_USER_IDS` may resume trading
+pub async fn resume_trading(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    admins: Data<TradingAdmins>,
+    path: Path<(String, String)>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        if !admins.is_admin(uid) {
+            return HttpResponse::Forbidden().json(TradingHaltFailedResponse::new("Only admins can resume trading"));
+        }
+        let (base_code, quote_code) = path.into_inner();
+        let repo = Repository::new(pool.get_ref().clone());
+        let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+            Ok(pair) => pair,
+            Err(response) => return response,
+        };
+        match repo.resume_trading(&base_id, &quote_id).await {
+            Ok(halt) => {
+                tracing::warn!("User {} resumed trading on {}/{}", uid, base_code, quote_code);
+                HttpResponse::Ok().json(halt)
+            }
+            Err(DataError::TradingHaltNotFoundError(message)) => HttpResponse::NotFound().json(TradingHaltFailedResponse::new(message)),
+            Err(e) => HttpResponse::InternalServerError().json(TradingHaltFailedResponse::new(e.to_string())),
+        }
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Publishes every order a mass cancel or a halt cancelled
+pub(crate) fn publish_cancelled_orders(events: &OrderEventBus, orders: &UserOrders) {
+    orders.buy_orders.iter().for_each(|order| events.buy_order_changed(order));
+    orders.sell_orders.iter().for_each(|order| events.sell_order_changed(order));
+    orders.stop_orders.iter().for_each(|order| events.stop_order_changed(order));
+}
+
+///
+/// Cancelling one leg of a group cancels the others, so their new state is published too
+pub(crate) async fn publish_order_group(repo: &Repository, events: &OrderEventBus, order_group_id: Option<i32>) {
+    let Some(order_group_id) = order_group_id else {
+        return;
+    };
+    match repo.find_order_group(&order_group_id).await {
+        Ok(Some(group)) => events.order_group_changed(&group),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Loading order group {} failed: {}", order_group_id, e),
+    }
+}
+
+pub(crate) fn order_update_response<T: Serialize>(result: Result<T, DataError>) -> HttpResponse {
+    match result {
+        Ok(order) => HttpResponse::Ok().json(order),
+        Err(DataError::OrderNotFoundError(message)) => HttpResponse::NotFound().json(OrderUpdateFailedResponse::new(message)),
+        Err(DataError::OrderOwnershipError(message)) => HttpResponse::Forbidden().json(OrderUpdateFailedResponse::new(message)),
+        Err(DataError::OrderStatusError(message)) => HttpResponse::Conflict().json(OrderUpdateFailedResponse::new(message)),
+        Err(DataError::TradingHaltedError(message)) => HttpResponse::Conflict().json(OrderUpdateFailedResponse::new(message)),
+        Err(e) => HttpResponse::BadRequest().json(OrderUpdateFailedResponse::new(e.to_string())),
+    }
+}
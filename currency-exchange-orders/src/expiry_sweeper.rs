@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use sqlx::PgPool;
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use crate::order_events::{OrderEvent, OrderEventBus};
+
+///
+/// Periodically moves orders past `expires_at` to expired and publishes an `OrderEvent`
+/// for each of them. Expiring an order releases the funds still held for its unfilled amount
+pub struct ExpirySweeper {
+    pool: PgPool,
+    interval: Duration,
+    batch_size: i64,
+    events: OrderEventBus,
+}
+
+impl ExpirySweeper {
+    pub fn new(pool: PgPool, interval: Duration, batch_size: i64, events: OrderEventBus) -> Self {
+        Self { pool, interval, batch_size, events }
+    }
+
+    pub async fn run(self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            match self.sweep().await {
+                Ok(0) => {}
+                Ok(expired) => tracing::info!("Expired {} orders", expired),
+                Err(e) => tracing::warn!("Order expiry sweep failed: {}", e),
+            }
+        }
+    }
+
+    ///
+    /// Expires batches until a short batch shows nothing is left, returns the number of expired orders
+    pub async fn sweep(&self) -> Result<usize, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let mut total = 0;
+        let mut pairs = HashSet::new();
+        loop {
+            let buy_orders = repository.expire_buy_orders(self.batch_size).await?;
+            for order in &buy_orders {
+                self.events.publish(OrderEvent::BuyOrderExpired {
+                    buy_order_id: order.buy_order_id.unwrap_or_default(),
+                    issuer_id: order.issuer_id.unwrap_or_default(),
+                });
+                pairs.insert((order.buy_currency_id.unwrap_or_default(), order.sell_currency_id.unwrap_or_default()));
+            }
+            let sell_orders = repository.expire_sell_orders(self.batch_size).await?;
+            for order in &sell_orders {
+                self.events.publish(OrderEvent::SellOrderExpired {
+                    sell_order_id: order.sell_order_id.unwrap_or_default(),
+                    issuer_id: order.issuer_id.unwrap_or_default(),
+                });
+                pairs.insert((order.sell_currency_id.unwrap_or_default(), order.buy_currency_id.unwrap_or_default()));
+            }
+            total += buy_orders.len() + sell_orders.len();
+            if (buy_orders.len() as i64) < self.batch_size && (sell_orders.len() as i64) < self.batch_size {
+                for (base, quote) in pairs {
+                    self.events.book_changed(base, quote);
+                }
+                return Ok(total);
+            }
+        }
+    }
+}
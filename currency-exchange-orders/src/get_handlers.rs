@@ -1,16 +1,24 @@
 use actix_web::{web, HttpRequest, HttpResponse};
-use actix_web::web::{Json, Query};
+use actix_web::web::{Json, Path, Query};
 use sqlx::{PgPool};
 use web::{Data, ReqData};
-use currency_exchange_data::datasource::api_models::{BalanceRequest, BuyOrderQueryParams, SellOrderQueryParams};
+use currency_exchange_data::datasource::api_models::{BalanceRequest, BuyOrderQueryParams, CandleQueryParams, OrderBookQueryParams, SellOrderQueryParams, StopOrderQueryParams, TradeQueryParams};
+use currency_exchange_data::datasource::models::{BuyOrder, OrderBook, SellOrder, TradePage};
 use currency_exchange_data::datasource::repository::repository::Repository;
 use currency_exchange_data::datasource::repository::wallet_repository::WalletRepository;
-use currency_exchange_data::datasource::error_responses::{BalanceNotFoundResponse, CurrencyNotFoundResponse, OrdersNotFoundResponse, WalletNotFoundResponse};
+use currency_exchange_data::datasource::error_responses::{BalanceNotFoundResponse, CurrencyNotFoundResponse, OrdersNotFoundResponse, TradingSettingsFailedResponse, WalletNotFoundResponse};
+use currency_exchange_data::datasource::repository::market_data_repository::MarketDataRepository;
 use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::stop_order_repository::StopOrderRepository;
+use currency_exchange_data::datasource::repository::trading_halt_repository::TradingHaltRepository;
+use currency_exchange_data::datasource::repository::trading_settings_repository::TradingSettingsRepository;
 use currency_exchange_middleware::jwt::Claims;
 
+///
+/// Live or `status` buy orders, iceberg orders of other users show only their current slice
 pub async fn buy_orders(
     req: HttpRequest,
+    claims: ReqData<Claims>,
     pool: Data<PgPool>,
     query: Query<BuyOrderQueryParams>,
 ) -> HttpResponse {
@@ -18,9 +26,11 @@ pub async fn buy_orders(
     if let Some(_) = headers.get("Authorization") {
         let params = query.into_inner();
         let repo = Repository::new(pool.as_ref().clone());
-        let buy_orders = repo.find_buy_orders(params.count)
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let buy_orders = repo.find_buy_orders(params.count, params.status)
             .await;
         if let Ok(buy_orders) = buy_orders {
+            let buy_orders: Vec<BuyOrder> = buy_orders.into_iter().map(|order| order.view_for(uid)).collect();
             HttpResponse::Ok().json(buy_orders)
         } else {
             HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Buy orders not found"))
@@ -30,8 +40,11 @@ pub async fn buy_orders(
     }
 }
 
+///
+/// Live or `status` sell orders, iceberg orders of other users show only their current slice
 pub async fn sell_orders(
     req: HttpRequest,
+    claims: ReqData<Claims>,
     pool: Data<PgPool>,
     query: Query<SellOrderQueryParams>
 ) -> HttpResponse {
@@ -39,10 +52,12 @@ pub async fn sell_orders(
     if let Some(_) = headers.get("Authorization") {
         let params = query.into_inner();
         let repo = Repository::new(pool.as_ref().clone());
-        let buy_orders = repo.find_sell_orders(params.count)
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let sell_orders = repo.find_sell_orders(params.count, params.status)
             .await;
-        if let Ok(buy_orders) = buy_orders {
-            HttpResponse::Ok().json(buy_orders)
+        if let Ok(sell_orders) = sell_orders {
+            let sell_orders: Vec<SellOrder> = sell_orders.into_iter().map(|order| order.view_for(uid)).collect();
+            HttpResponse::Ok().json(sell_orders)
         } else {
             HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Sell orders not found"))
         }
@@ -51,6 +66,335 @@ pub async fn sell_orders(
     }
 }
 
+pub async fn buy_order_fills(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>,
+    path: Path<i32>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let buy_order_id = path.into_inner();
+        let repo = Repository::new(pool.as_ref().clone());
+        let order = repo.find_buy_order(&buy_order_id)
+            .await;
+        if !matches!(order, Ok(Some(ref order)) if order.issuer_id == Some(uid)) {
+            return HttpResponse::NotFound().json(OrdersNotFoundResponse::new(format!("Buy order with id={} not found", buy_order_id)));
+        }
+        let fills = repo.find_buy_order_fills(&buy_order_id)
+            .await;
+        if let Ok(fills) = fills {
+            HttpResponse::Ok().json(fills)
+        } else {
+            HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Buy order fills not found"))
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+pub async fn sell_order_fills(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>,
+    path: Path<i32>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let sell_order_id = path.into_inner();
+        let repo = Repository::new(pool.as_ref().clone());
+        let order = repo.find_sell_order(&sell_order_id)
+            .await;
+        if !matches!(order, Ok(Some(ref order)) if order.issuer_id == Some(uid)) {
+            return HttpResponse::NotFound().json(OrdersNotFoundResponse::new(format!("Sell order with id={} not found", sell_order_id)));
+        }
+        let fills = repo.find_sell_order_fills(&sell_order_id)
+            .await;
+        if let Ok(fills) = fills {
+            HttpResponse::Ok().json(fills)
+        } else {
+            HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Sell order fills not found"))
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// Bids and asks for `base`/`quote` currency codes aggregated by price level
+pub async fn order_book(
+    pool: Data<PgPool>,
+    path: Path<(String, String)>,
+    query: Query<OrderBookQueryParams>
+) -> HttpResponse {
+    let (base_code, quote_code) = path.into_inner();
+    let depth = query.into_inner().depth();
+    let repo = Repository::new(pool.as_ref().clone());
+    let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+        Ok(pair) => pair,
+        Err(response) => return response,
+    };
+    let bids = repo.find_bid_levels(&base_id, &quote_id, depth)
+        .await;
+    let asks = repo.find_ask_levels(&base_id, &quote_id, depth)
+        .await;
+    if let (Ok(bids), Ok(asks)) = (bids, asks) {
+        HttpResponse::Ok().json(OrderBook {
+            base_currency: base_code,
+            quote_currency: quote_code,
+            bids,
+            asks,
+        })
+    } else {
+        HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Order book not found"))
+    }
+}
+
+///
+/// Public tape for `base`/`quote` currency codes, newest first
+pub async fn trades(
+    pool: Data<PgPool>,
+    path: Path<(String, String)>,
+    query: Query<TradeQueryParams>
+) -> HttpResponse {
+    let (base_code, quote_code) = path.into_inner();
+    let params = query.into_inner();
+    let repo = Repository::new(pool.as_ref().clone());
+    let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+        Ok(pair) => pair,
+        Err(response) => return response,
+    };
+    let trades = repo.find_trades(&base_id, &quote_id, &params)
+        .await;
+    if let Ok(trades) = trades {
+        HttpResponse::Ok().json(TradePage::new(trades, params.limit(), |trade| trade.trade_id))
+    } else {
+        HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Trades not found"))
+    }
+}
+
+///
+/// Executions the caller took part in, newest first
+pub async fn my_trades(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>,
+    query: Query<TradeQueryParams>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let params = query.into_inner();
+        let repo = Repository::new(pool.as_ref().clone());
+        let trades = repo.find_user_trades(&uid, &params)
+            .await;
+        if let Ok(trades) = trades {
+            HttpResponse::Ok().json(TradePage::new(trades, params.limit(), |trade| trade.trade_id))
+        } else {
+            HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Trades not found"))
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// Stop orders the caller placed, newest first
+pub async fn stop_orders(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>,
+    query: Query<StopOrderQueryParams>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let repo = Repository::new(pool.as_ref().clone());
+        let stop_orders = repo.find_stop_orders(&uid, query.into_inner().status)
+            .await;
+        if let Ok(stop_orders) = stop_orders {
+            HttpResponse::Ok().json(stop_orders)
+        } else {
+            HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Stop orders not found"))
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// Caller's working orders: live buy and sell orders and pending stop orders, including both legs of order groups
+pub async fn my_orders(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let repo = Repository::new(pool.as_ref().clone());
+        if let Ok(orders) = repo.find_user_orders(&uid).await {
+            HttpResponse::Ok().json(orders)
+        } else {
+            HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Orders not found"))
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// Caller's trading settings, defaults apply until they are first updated
+pub async fn my_trading_settings(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let repo = Repository::new(pool.as_ref().clone());
+        match repo.find_trading_settings(&uid).await {
+            Ok(settings) => HttpResponse::Ok().json(settings),
+            Err(e) => HttpResponse::InternalServerError().json(TradingSettingsFailedResponse::new(e.to_string())),
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// Matches between the caller's own orders that self-trade prevention stopped, newest first
+pub async fn my_prevented_trades(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let repo = Repository::new(pool.as_ref().clone());
+        if let Ok(prevented_trades) = repo.find_prevented_trades(&uid).await {
+            HttpResponse::Ok().json(prevented_trades)
+        } else {
+            HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Prevented trades not found"))
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// Caller's buy or sell order placed under the given client order id
+pub async fn my_order_by_client_id(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    pool: Data<PgPool>,
+    path: Path<String>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let client_order_id = path.into_inner();
+        let repo = Repository::new(pool.as_ref().clone());
+        match repo.find_client_order(&uid, &client_order_id).await {
+            Ok(order) if order.buy_order.is_some() || order.sell_order.is_some() => HttpResponse::Ok().json(order),
+            _ => HttpResponse::NotFound().json(OrdersNotFoundResponse::new(format!("Order with client order id {} not found", client_order_id))),
+        }
+    } else {
+        HttpResponse::Unauthorized().body("Authorization unauthorized")
+    }
+}
+
+///
+/// OHLCV candles for `base`/`quote` currency codes, oldest first
+pub async fn candles(
+    pool: Data<PgPool>,
+    path: Path<(String, String)>,
+    query: Query<CandleQueryParams>
+) -> HttpResponse {
+    let (base_code, quote_code) = path.into_inner();
+    let params = query.into_inner();
+    let repo = Repository::new(pool.as_ref().clone());
+    let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+        Ok(pair) => pair,
+        Err(response) => return response,
+    };
+    let candles = repo.find_candles(&base_id, &quote_id, &params)
+        .await;
+    if let Ok(candles) = candles {
+        HttpResponse::Ok().json(candles)
+    } else {
+        HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Candles not found"))
+    }
+}
+
+///
+/// Trading halts in force, oldest first
+pub async fn trading_halts(pool: Data<PgPool>) -> HttpResponse {
+    let repo = Repository::new(pool.as_ref().clone());
+    if let Ok(halts) = repo.find_trading_halts().await {
+        HttpResponse::Ok().json(halts)
+    } else {
+        HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Trading halts not found"))
+    }
+}
+
+///
+/// 24h statistics for every pair of registered currencies
+pub async fn tickers(pool: Data<PgPool>) -> HttpResponse {
+    let repo = Repository::new(pool.as_ref().clone());
+    let tickers = repo.find_tickers(None, None)
+        .await;
+    if let Ok(tickers) = tickers {
+        HttpResponse::Ok().json(tickers)
+    } else {
+        HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Tickers not found"))
+    }
+}
+
+///
+/// 24h statistics for `base`/`quote` currency codes
+pub async fn ticker(
+    pool: Data<PgPool>,
+    path: Path<(String, String)>
+) -> HttpResponse {
+    let (base_code, quote_code) = path.into_inner();
+    let repo = Repository::new(pool.as_ref().clone());
+    let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+        Ok(pair) => pair,
+        Err(response) => return response,
+    };
+    let ticker = repo.find_tickers(Some(base_id), Some(quote_id))
+        .await
+        .map(|tickers| tickers.into_iter().next());
+    if let Ok(Some(ticker)) = ticker {
+        HttpResponse::Ok().json(ticker)
+    } else {
+        HttpResponse::NotFound().json(OrdersNotFoundResponse::new("Ticker not found"))
+    }
+}
+
+///
+/// Resolves currency codes to ids, or the response to send when the pair is unknown
+pub(crate) async fn currency_pair(repo: &Repository, base_code: &str, quote_code: &str) -> Result<(i32, i32), HttpResponse> {
+    let base_id = repo.find_currency(base_code)
+        .await
+        .and_then(|currency| currency.currency_id);
+    let quote_id = repo.find_currency(quote_code)
+        .await
+        .and_then(|currency| currency.currency_id);
+    let (Some(base_id), Some(quote_id)) = (base_id, quote_id) else {
+        return Err(HttpResponse::NotFound().json(CurrencyNotFoundResponse::new(format!("Currency pair {}/{} not found", base_code, quote_code))));
+    };
+    if base_id == quote_id {
+        return Err(HttpResponse::BadRequest().json(CurrencyNotFoundResponse::new("Base and quote currency must differ")));
+    }
+    Ok((base_id, quote_id))
+}
+
 pub async fn currency_balance(
     req: HttpRequest,
     claims: ReqData<Claims>,
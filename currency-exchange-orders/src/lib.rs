@@ -11,3 +11,23 @@ pub mod order_endpoints;
 pub mod order_transaction_manager;
 
 pub mod put_handlers;
+
+pub mod delete_handlers;
+
+pub mod patch_handlers;
+
+pub mod order_events;
+
+pub mod expiry_sweeper;
+
+pub mod matching_engine;
+
+pub mod market_data_feed;
+
+pub mod market_data_socket;
+
+pub mod user_data_feed;
+
+pub mod user_data_socket;
+
+pub mod stop_order_trigger;
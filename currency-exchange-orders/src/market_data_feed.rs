@@ -0,0 +1,248 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, Mutex};
+use currency_exchange_data::datasource::api_models::MAX_ORDER_BOOK_DEPTH;
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::{PriceLevel, Trade};
+use currency_exchange_data::datasource::repository::market_data_repository::MarketDataRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use crate::order_events::{OrderEvent, OrderEventBus};
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CurrencyPair {
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+}
+
+///
+/// Messages fanned out to market data subscribers.
+/// Book and trade sequence numbers are counted separately per pair and grow by one per message.
+/// A snapshot replaces the whole book, a jump means messages were missed and the book has to be
+/// re-subscribed or the missing trades fetched from the trade history
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketDataMessage {
+    BookSnapshot {
+        #[serde(flatten)]
+        pair: CurrencyPair,
+        seq: u64,
+        bids: Vec<PriceLevel>,
+        asks: Vec<PriceLevel>,
+    },
+    ///
+    /// Only the levels that changed, a level with size 0 has been removed
+    BookUpdate {
+        #[serde(flatten)]
+        pair: CurrencyPair,
+        seq: u64,
+        bids: Vec<PriceLevel>,
+        asks: Vec<PriceLevel>,
+    },
+    Trade {
+        #[serde(flatten)]
+        pair: CurrencyPair,
+        seq: u64,
+        trade: Trade,
+    },
+}
+
+struct BookState {
+    seq: u64,
+    bids: Vec<PriceLevel>,
+    asks: Vec<PriceLevel>,
+}
+
+#[derive(Default)]
+struct FeedState {
+    books: HashMap<CurrencyPair, BookState>,
+    trade_seqs: HashMap<CurrencyPair, u64>,
+}
+
+///
+/// Keeps the top MAX_ORDER_BOOK_DEPTH levels of every subscribed book in memory and turns
+/// `OrderEvent`s into sequenced book diffs and trades. Books are loaded on first subscription
+#[derive(Clone)]
+pub struct MarketDataFeed {
+    pool: PgPool,
+    state: Arc<Mutex<FeedState>>,
+    sender: broadcast::Sender<MarketDataMessage>,
+}
+
+impl MarketDataFeed {
+    pub fn new(pool: PgPool, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { pool, state: Arc::new(Mutex::new(FeedState::default())), sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MarketDataMessage> {
+        self.sender.subscribe()
+    }
+
+    pub async fn run(self, events: OrderEventBus) {
+        let mut receiver = events.subscribe();
+        loop {
+            let result = match receiver.recv().await {
+                Ok(OrderEvent::BookChanged { base_currency_id, quote_currency_id }) => {
+                    self.refresh_book(CurrencyPair { base_currency_id, quote_currency_id }).await
+                }
+                Ok(OrderEvent::TradeExecuted { trade, .. }) => {
+                    self.publish_trade(trade).await;
+                    Ok(())
+                }
+                Ok(_) => Ok(()),
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Market data feed missed {} order events, resyncing all pairs", missed);
+                    self.resync_all().await
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if let Err(e) = result {
+                tracing::warn!("Market data refresh failed: {}", e);
+            }
+        }
+    }
+
+    ///
+    /// Current book for the pair together with the sequence number it reflects
+    pub async fn snapshot(&self, pair: CurrencyPair) -> Result<MarketDataMessage, DataError> {
+        let mut state = self.state.lock().await;
+        if let Entry::Vacant(entry) = state.books.entry(pair) {
+            let (bids, asks) = self.load_levels(pair).await?;
+            entry.insert(BookState { seq: 0, bids, asks });
+        }
+        let book = &state.books[&pair];
+        Ok(MarketDataMessage::BookSnapshot {
+            pair,
+            seq: book.seq,
+            bids: book.bids.clone(),
+            asks: book.asks.clone(),
+        })
+    }
+
+    ///
+    /// Sequence number of the last trade published for the pair
+    pub async fn trade_seq(&self, pair: CurrencyPair) -> u64 {
+        let state = self.state.lock().await;
+        state.trade_seqs.get(&pair).copied().unwrap_or(0)
+    }
+
+    async fn refresh_book(&self, pair: CurrencyPair) -> Result<(), DataError> {
+        let mut state = self.state.lock().await;
+        if !state.books.contains_key(&pair) {
+            return Ok(());
+        }
+        let (bids, asks) = self.load_levels(pair).await?;
+        let book = state.books.get_mut(&pair).expect("Book checked above");
+        let changed_bids = diff_levels(&book.bids, &bids);
+        let changed_asks = diff_levels(&book.asks, &asks);
+        if changed_bids.is_empty() && changed_asks.is_empty() {
+            return Ok(());
+        }
+        book.seq += 1;
+        book.bids = bids;
+        book.asks = asks;
+        let _ = self.sender.send(MarketDataMessage::BookUpdate {
+            pair,
+            seq: book.seq,
+            bids: changed_bids,
+            asks: changed_asks,
+        });
+        Ok(())
+    }
+
+    ///
+    /// Events were dropped, so every loaded book is pushed as a fresh snapshot and every trade
+    /// sequence skips one number to show trade subscribers the gap
+    async fn resync_all(&self) -> Result<(), DataError> {
+        let mut state = self.state.lock().await;
+        for seq in state.trade_seqs.values_mut() {
+            *seq += 1;
+        }
+        let pairs: Vec<CurrencyPair> = state.books.keys().copied().collect();
+        for pair in pairs {
+            let (bids, asks) = self.load_levels(pair).await?;
+            let book = state.books.get_mut(&pair).expect("Pair taken from the loaded books");
+            book.seq += 1;
+            book.bids = bids.clone();
+            book.asks = asks.clone();
+            let _ = self.sender.send(MarketDataMessage::BookSnapshot { pair, seq: book.seq, bids, asks });
+        }
+        Ok(())
+    }
+
+    async fn publish_trade(&self, trade: Trade) {
+        let (Some(base_currency_id), Some(quote_currency_id)) = (trade.base_currency_id, trade.quote_currency_id) else {
+            return;
+        };
+        let pair = CurrencyPair { base_currency_id, quote_currency_id };
+        let mut state = self.state.lock().await;
+        let seq = state.trade_seqs.entry(pair).or_insert(0);
+        *seq += 1;
+        let _ = self.sender.send(MarketDataMessage::Trade { pair, seq: *seq, trade });
+    }
+
+    async fn load_levels(&self, pair: CurrencyPair) -> Result<(Vec<PriceLevel>, Vec<PriceLevel>), DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let bids = repository
+            .find_bid_levels(&pair.base_currency_id, &pair.quote_currency_id, MAX_ORDER_BOOK_DEPTH)
+            .await?;
+        let asks = repository
+            .find_ask_levels(&pair.base_currency_id, &pair.quote_currency_id, MAX_ORDER_BOOK_DEPTH)
+            .await?;
+        Ok((bids, asks))
+    }
+}
+
+///
+/// Levels that differ between two views of one side of the book.
+/// Levels gone from `current` are reported with size and order count 0
+pub fn diff_levels(previous: &[PriceLevel], current: &[PriceLevel]) -> Vec<PriceLevel> {
+    let same_price = |a: &PriceLevel, b: &PriceLevel| a.price.to_bits() == b.price.to_bits();
+    let mut changes: Vec<PriceLevel> = current
+        .iter()
+        .filter(|level| {
+            !previous.iter().any(|old| {
+                same_price(old, level) && old.size == level.size && old.order_count == level.order_count
+            })
+        })
+        .cloned()
+        .collect();
+    changes.extend(
+        previous
+            .iter()
+            .filter(|old| !current.iter().any(|level| same_price(old, level)))
+            .map(|old| PriceLevel { price: old.price, size: 0, order_count: 0 }),
+    );
+    changes
+}
+
+#[cfg(test)]
+mod market_data_feed_spec {
+    use currency_exchange_data::datasource::models::PriceLevel;
+    use crate::market_data_feed::diff_levels;
+
+    fn level(price: f64, size: i64, order_count: i64) -> PriceLevel {
+        PriceLevel { price, size, order_count }
+    }
+
+    #[test]
+    fn should_report_nothing_for_unchanged_book() {
+        let book = vec![level(1.1, 10, 1), level(1.0, 5, 2)];
+        assert!(diff_levels(&book, &book).is_empty());
+    }
+
+    #[test]
+    fn should_report_changed_added_and_removed_levels() {
+        let previous = vec![level(1.1, 10, 1), level(1.0, 5, 2)];
+        let current = vec![level(1.2, 3, 1), level(1.1, 7, 1)];
+        let changes = diff_levels(&previous, &current);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().any(|l| l.price == 1.2 && l.size == 3));
+        assert!(changes.iter().any(|l| l.price == 1.1 && l.size == 7));
+        assert!(changes.iter().any(|l| l.price == 1.0 && l.size == 0 && l.order_count == 0));
+    }
+}
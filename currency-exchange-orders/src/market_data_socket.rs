@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::web::Data;
+use actix_ws::{Closed, Message, MessageStream, Session};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use crate::market_data_feed::{CurrencyPair, MarketDataFeed, MarketDataMessage};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Book,
+    Trades,
+}
+
+///
+/// Commands sent by the client, `pair` is written as `BASE/QUOTE` currency codes.
+/// Subscribing to a book again sends a fresh snapshot, which is how clients resync after a gap
+#[derive(Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientCommand {
+    Subscribe { channel: Channel, pair: String },
+    Unsubscribe { channel: Channel, pair: String },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SessionMessage<'a> {
+    Subscribed {
+        channel: Channel,
+        pair: &'a str,
+        #[serde(flatten)]
+        ids: CurrencyPair,
+        seq: u64,
+    },
+    Unsubscribed {
+        channel: Channel,
+        pair: &'a str,
+    },
+    Error {
+        message: String,
+    },
+}
+
+///
+/// Upgrades the request to a market data WebSocket
+pub async fn market_data_socket(
+    req: HttpRequest,
+    body: web::Payload,
+    pool: Data<PgPool>,
+    feed: Data<MarketDataFeed>
+) -> Result<HttpResponse, actix_web::Error> {
+    let (response, session, stream) = actix_ws::handle(&req, body)?;
+    let connection = MarketDataConnection {
+        repository: Repository::new(pool.get_ref().clone()),
+        feed: feed.get_ref().clone(),
+        session,
+        subscriptions: HashSet::new(),
+        book_seqs: HashMap::new(),
+    };
+    actix_web::rt::spawn(connection.run(stream));
+    Ok(response)
+}
+
+struct MarketDataConnection {
+    repository: Repository,
+    feed: MarketDataFeed,
+    session: Session,
+    subscriptions: HashSet<(Channel, CurrencyPair)>,
+    ///
+    /// Last book sequence sent per pair, older updates still queued behind a snapshot are dropped
+    book_seqs: HashMap<CurrencyPair, u64>,
+}
+
+impl MarketDataConnection {
+    async fn run(mut self, mut stream: MessageStream) {
+        let mut updates = self.feed.subscribe();
+        let result: Result<(), Closed> = async {
+            loop {
+                tokio::select! {
+                    message = stream.recv() => match message {
+                        Some(Ok(Message::Text(text))) => self.handle_command(&text).await?,
+                        Some(Ok(Message::Ping(bytes))) => self.session.pong(&bytes).await?,
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return Ok(()),
+                        Some(Ok(_)) => {}
+                    },
+                    update = updates.recv() => match update {
+                        Ok(update) => self.forward(update).await?,
+                        Err(RecvError::Lagged(_)) => self.resync_books().await?,
+                        Err(RecvError::Closed) => return Ok(()),
+                    }
+                }
+            }
+        }.await;
+        if result.is_ok() {
+            let _ = self.session.close(None).await;
+        }
+    }
+
+    async fn handle_command(&mut self, text: &str) -> Result<(), Closed> {
+        let command = match serde_json::from_str::<ClientCommand>(text) {
+            Ok(command) => command,
+            Err(e) => return self.send(&SessionMessage::Error { message: e.to_string() }).await,
+        };
+        match command {
+            ClientCommand::Subscribe { channel, pair: code } => {
+                let Some(pair) = self.resolve_pair(&code).await else {
+                    return self.send(&SessionMessage::Error { message: format!("Currency pair {} not found", code) }).await;
+                };
+                self.subscriptions.insert((channel, pair));
+                match channel {
+                    Channel::Book => {
+                        let seq = self.send_snapshot(pair).await?;
+                        self.send(&SessionMessage::Subscribed { channel, pair: &code, ids: pair, seq }).await
+                    }
+                    Channel::Trades => {
+                        let seq = self.feed.trade_seq(pair).await;
+                        self.send(&SessionMessage::Subscribed { channel, pair: &code, ids: pair, seq }).await
+                    }
+                }
+            }
+            ClientCommand::Unsubscribe { channel, pair: code } => {
+                if let Some(pair) = self.resolve_pair(&code).await {
+                    self.subscriptions.remove(&(channel, pair));
+                    if channel == Channel::Book {
+                        self.book_seqs.remove(&pair);
+                    }
+                }
+                self.send(&SessionMessage::Unsubscribed { channel, pair: &code }).await
+            }
+        }
+    }
+
+    async fn forward(&mut self, update: MarketDataMessage) -> Result<(), Closed> {
+        match &update {
+            MarketDataMessage::BookSnapshot { pair, seq, .. } | MarketDataMessage::BookUpdate { pair, seq, .. } => {
+                if !self.subscriptions.contains(&(Channel::Book, *pair)) {
+                    return Ok(());
+                }
+                let last_seq = self.book_seqs.entry(*pair).or_insert(0);
+                if *seq <= *last_seq {
+                    return Ok(());
+                }
+                *last_seq = *seq;
+            }
+            MarketDataMessage::Trade { pair, .. } => {
+                if !self.subscriptions.contains(&(Channel::Trades, *pair)) {
+                    return Ok(());
+                }
+            }
+        }
+        self.send(&update).await
+    }
+
+    ///
+    /// Updates were dropped for this connection, so every subscribed book starts over from a snapshot
+    async fn resync_books(&mut self) -> Result<(), Closed> {
+        let pairs: Vec<CurrencyPair> = self.subscriptions
+            .iter()
+            .filter(|(channel, _)| *channel == Channel::Book)
+            .map(|(_, pair)| *pair)
+            .collect();
+        for pair in pairs {
+            self.send_snapshot(pair).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_snapshot(&mut self, pair: CurrencyPair) -> Result<u64, Closed> {
+        match self.feed.snapshot(pair).await {
+            Ok(snapshot) => {
+                let seq = match &snapshot {
+                    MarketDataMessage::BookSnapshot { seq, .. } => *seq,
+                    _ => 0,
+                };
+                self.book_seqs.insert(pair, seq);
+                self.send(&snapshot).await?;
+                Ok(seq)
+            }
+            Err(e) => {
+                self.send(&SessionMessage::Error { message: e.to_string() }).await?;
+                Ok(0)
+            }
+        }
+    }
+
+    async fn resolve_pair(&self, code: &str) -> Option<CurrencyPair> {
+        let (base_code, quote_code) = code.split_once('/')?;
+        let base_currency_id = self.repository.find_currency(base_code).await?.currency_id?;
+        let quote_currency_id = self.repository.find_currency(quote_code).await?.currency_id?;
+        (base_currency_id != quote_currency_id).then_some(CurrencyPair { base_currency_id, quote_currency_id })
+    }
+
+    async fn send<T: Serialize>(&mut self, message: &T) -> Result<(), Closed> {
+        let text = serde_json::to_string(message).expect("Market data messages serialize");
+        self.session.text(text).await
+    }
+}
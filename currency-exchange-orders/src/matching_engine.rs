@@ -0,0 +1,313 @@
+use sqlx::PgPool;
+use currency_exchange_data::datasource::api_models::MarketOrderRequest;
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::{BuyOrder, MatchOutcome, MatchReport, OrderSide, OrderStatus, SelfTradePrevention, SellOrder, TimeInForce};
+use currency_exchange_data::datasource::repository::currency_amount_repository::CurrencyAmountRepository;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::{counter_amount, Repository};
+
+#[async_trait::async_trait]
+pub trait OrderMatcher {
+    async fn match_buy_order(&self, buy_order: &BuyOrder) -> Result<MatchReport, DataError>;
+
+    async fn match_sell_order(&self, sell_order: &SellOrder) -> Result<MatchReport, DataError>;
+
+    ///
+    /// Matches a newly created buy order as its time in force requires.
+    /// IOC and FOK orders never rest, whatever they leave unfilled is cancelled
+    async fn place_buy_order(&self, buy_order: &BuyOrder) -> Result<MatchReport, DataError>;
+
+    ///
+    /// Matches a newly created sell order as its time in force requires.
+    /// IOC and FOK orders never rest, whatever they leave unfilled is cancelled
+    async fn place_sell_order(&self, sell_order: &SellOrder) -> Result<MatchReport, DataError>;
+
+    ///
+    /// Buys `request.amount` for `taker_id` from resting sell orders, best price first,
+    /// stopping at the slippage bound, once `max_spend` is used up or at a self-trade that cancels the order
+    async fn execute_market_buy(&self, taker_id: i32, request: &MarketOrderRequest) -> Result<MatchReport, DataError>;
+
+    ///
+    /// Sells `request.amount` for `taker_id` into resting buy orders, best price first,
+    /// stopping at the slippage bound or at a self-trade that cancels the order
+    async fn execute_market_sell(&self, taker_id: i32, request: &MarketOrderRequest) -> Result<MatchReport, DataError>;
+}
+
+///
+/// Largest amount whose counter amount at `exchange_rate` fits in `budget`
+pub fn affordable_amount(budget: i32, exchange_rate: f64) -> i32 {
+    if budget <= 0 || exchange_rate <= 0.0 {
+        return 0;
+    }
+    let mut amount = (budget as f64 / exchange_rate).floor() as i32;
+    while counter_amount(amount + 1, exchange_rate) <= budget {
+        amount += 1;
+    }
+    while amount > 0 && counter_amount(amount, exchange_rate) > budget {
+        amount -= 1;
+    }
+    amount
+}
+
+///
+/// What is left for the incoming order to match once a self-trade of `amount` was prevented:
+/// nothing when its mode cancels it, `amount` less when both orders were decremented
+pub fn remaining_after_prevention(remaining: i32, mode: SelfTradePrevention, amount: i32) -> i32 {
+    if mode.cancels_taker() {
+        0
+    } else if mode == SelfTradePrevention::Decrement {
+        remaining - amount
+    } else {
+        remaining
+    }
+}
+
+///
+/// Pairs a freshly placed order with resting orders on the opposite side of the same
+/// currency pair and settles each match through `CurrencyAmountRepository`.
+/// Matches execute at the resting order's limit price, matches between orders of the same user
+/// are resolved by the self-trade prevention mode of the incoming order instead
+pub struct MatchingEngine {
+    pool: PgPool,
+}
+
+impl MatchingEngine {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl OrderMatcher for MatchingEngine {
+    async fn match_buy_order(&self, buy_order: &BuyOrder) -> Result<MatchReport, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+        let mut remaining = buy_order.remaining_amount.unwrap_or(0);
+        let mut report = MatchReport::default();
+        // Iceberg orders refresh behind their price level, so the book is read again until a pass matches nothing
+        loop {
+            let matched = report.matches();
+            for sell_order in repository.find_crossing_sell_orders(buy_order).await? {
+                if remaining <= 0 {
+                    break;
+                }
+                let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+                let exchange_rate = sell_order.limit_price.unwrap_or_default();
+                match repository.settle_exchange(buy_order_id, sell_order_id, exchange_rate, OrderSide::Buy).await? {
+                    Some(MatchOutcome::Traded(exchange)) => {
+                        remaining -= exchange.amount;
+                        report.exchanges.push(exchange);
+                    }
+                    Some(MatchOutcome::Prevented(prevented)) => {
+                        remaining = remaining_after_prevention(remaining, prevented.mode, prevented.amount);
+                        report.prevented_trades.push(prevented);
+                    }
+                    None => {}
+                }
+            }
+            if remaining <= 0 || report.matches() == matched {
+                return Ok(report);
+            }
+        }
+    }
+
+    async fn match_sell_order(&self, sell_order: &SellOrder) -> Result<MatchReport, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+        let mut remaining = sell_order.remaining_amount.unwrap_or(0);
+        let mut report = MatchReport::default();
+        // Iceberg orders refresh behind their price level, so the book is read again until a pass matches nothing
+        loop {
+            let matched = report.matches();
+            for buy_order in repository.find_crossing_buy_orders(sell_order).await? {
+                if remaining <= 0 {
+                    break;
+                }
+                let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+                let exchange_rate = buy_order.limit_price.unwrap_or_default();
+                match repository.settle_exchange(buy_order_id, sell_order_id, exchange_rate, OrderSide::Sell).await? {
+                    Some(MatchOutcome::Traded(exchange)) => {
+                        remaining -= exchange.amount;
+                        report.exchanges.push(exchange);
+                    }
+                    Some(MatchOutcome::Prevented(prevented)) => {
+                        remaining = remaining_after_prevention(remaining, prevented.mode, prevented.amount);
+                        report.prevented_trades.push(prevented);
+                    }
+                    None => {}
+                }
+            }
+            if remaining <= 0 || report.matches() == matched {
+                return Ok(report);
+            }
+        }
+    }
+
+    async fn place_buy_order(&self, buy_order: &BuyOrder) -> Result<MatchReport, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+        let time_in_force = buy_order.time_in_force.unwrap_or(TimeInForce::Gtc);
+        let report = match time_in_force {
+            TimeInForce::Fok => match repository.fill_or_kill_buy_order(buy_order_id).await {
+                Ok(report) => report,
+                Err(DataError::FillOrKillError(message)) => {
+                    tracing::info!("{}", message);
+                    MatchReport::default()
+                }
+                Err(e) => return Err(e),
+            },
+            _ => self.match_buy_order(buy_order).await?,
+        };
+        if !time_in_force.rests() {
+            let live = repository.find_buy_order(&buy_order_id)
+                .await?
+                .is_some_and(|order| order.status.is_some_and(|s| s.is_live()));
+            if live {
+                repository.update_buy_order_status(&buy_order_id, OrderStatus::Cancelled).await?;
+            }
+        }
+        Ok(report)
+    }
+
+    async fn place_sell_order(&self, sell_order: &SellOrder) -> Result<MatchReport, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+        let time_in_force = sell_order.time_in_force.unwrap_or(TimeInForce::Gtc);
+        let report = match time_in_force {
+            TimeInForce::Fok => match repository.fill_or_kill_sell_order(sell_order_id).await {
+                Ok(report) => report,
+                Err(DataError::FillOrKillError(message)) => {
+                    tracing::info!("{}", message);
+                    MatchReport::default()
+                }
+                Err(e) => return Err(e),
+            },
+            _ => self.match_sell_order(sell_order).await?,
+        };
+        if !time_in_force.rests() {
+            let live = repository.find_sell_order(&sell_order_id)
+                .await?
+                .is_some_and(|order| order.status.is_some_and(|s| s.is_live()));
+            if live {
+                repository.update_sell_order_status(&sell_order_id, OrderStatus::Cancelled).await?;
+            }
+        }
+        Ok(report)
+    }
+
+    async fn execute_market_buy(&self, taker_id: i32, request: &MarketOrderRequest) -> Result<MatchReport, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let mut sell_orders = repository.find_resting_sell_orders(&request.buy_currency_id, &request.sell_currency_id, None).await?;
+        let max_price = sell_orders
+            .first()
+            .and_then(|best| request.price_bound(best.limit_price.unwrap_or_default(), OrderSide::Buy));
+        let mut remaining = request.amount;
+        let mut budget = request.max_spend;
+        let mut report = MatchReport::default();
+        // Iceberg orders refresh behind their price level, so the book is read again until a pass matches nothing
+        loop {
+            let matched = report.matches();
+            for sell_order in sell_orders {
+                let sell_order_id = sell_order.sell_order_id.unwrap_or_default();
+                let exchange_rate = sell_order.limit_price.unwrap_or_default();
+                if remaining <= 0 || max_price.is_some_and(|max_price| exchange_rate > max_price) {
+                    break;
+                }
+                let amount = match budget {
+                    Some(budget) => remaining.min(affordable_amount(budget, exchange_rate)),
+                    None => remaining,
+                };
+                if amount <= 0 {
+                    break;
+                }
+                match repository.fill_sell_order(sell_order_id, taker_id, amount, request.self_trade_prevention).await {
+                    Ok(MatchOutcome::Traded(exchange)) => {
+                        remaining -= exchange.amount;
+                        budget = budget.map(|budget| budget - exchange.income.unwrap_or_default() as i32);
+                        report.exchanges.push(exchange);
+                    }
+                    Ok(MatchOutcome::Prevented(prevented)) => {
+                        remaining = remaining_after_prevention(remaining, prevented.mode, prevented.amount);
+                        report.prevented_trades.push(prevented);
+                    }
+                    // The order left the book after it was read
+                    Err(DataError::OrderStatusError(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if remaining <= 0 || report.matches() == matched {
+                return Ok(report);
+            }
+            sell_orders = repository.find_resting_sell_orders(&request.buy_currency_id, &request.sell_currency_id, None).await?;
+        }
+    }
+
+    async fn execute_market_sell(&self, taker_id: i32, request: &MarketOrderRequest) -> Result<MatchReport, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let mut buy_orders = repository.find_resting_buy_orders(&request.sell_currency_id, &request.buy_currency_id, None).await?;
+        let min_price = buy_orders
+            .first()
+            .and_then(|best| request.price_bound(best.limit_price.unwrap_or_default(), OrderSide::Sell));
+        let mut remaining = request.amount;
+        let mut report = MatchReport::default();
+        // Iceberg orders refresh behind their price level, so the book is read again until a pass matches nothing
+        loop {
+            let matched = report.matches();
+            for buy_order in buy_orders {
+                let buy_order_id = buy_order.buy_order_id.unwrap_or_default();
+                let exchange_rate = buy_order.limit_price.unwrap_or_default();
+                if remaining <= 0 || min_price.is_some_and(|min_price| exchange_rate < min_price) {
+                    break;
+                }
+                match repository.fill_buy_order(buy_order_id, taker_id, remaining, request.self_trade_prevention).await {
+                    Ok(MatchOutcome::Traded(exchange)) => {
+                        remaining -= exchange.amount;
+                        report.exchanges.push(exchange);
+                    }
+                    Ok(MatchOutcome::Prevented(prevented)) => {
+                        remaining = remaining_after_prevention(remaining, prevented.mode, prevented.amount);
+                        report.prevented_trades.push(prevented);
+                    }
+                    // The order left the book after it was read
+                    Err(DataError::OrderStatusError(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if remaining <= 0 || report.matches() == matched {
+                return Ok(report);
+            }
+            buy_orders = repository.find_resting_buy_orders(&request.sell_currency_id, &request.buy_currency_id, None).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod matching_engine_spec {
+    use currency_exchange_data::datasource::models::SelfTradePrevention;
+    use crate::matching_engine::{affordable_amount, remaining_after_prevention};
+
+    #[test]
+    fn should_fit_counter_amount_in_budget() {
+        assert_eq!(affordable_amount(100, 1.0), 100);
+        assert_eq!(affordable_amount(100, 3.0), 33);
+        assert_eq!(affordable_amount(10, 1.1), 9);
+    }
+
+    #[test]
+    fn should_afford_nothing_without_budget() {
+        assert_eq!(affordable_amount(0, 1.0), 0);
+        assert_eq!(affordable_amount(-5, 1.0), 0);
+    }
+
+    #[test]
+    fn should_stop_orders_their_mode_cancels() {
+        assert_eq!(remaining_after_prevention(10, SelfTradePrevention::CancelNewest, 4), 0);
+        assert_eq!(remaining_after_prevention(10, SelfTradePrevention::CancelBoth, 4), 0);
+    }
+
+    #[test]
+    fn should_keep_matching_after_cancelling_or_decrementing_the_resting_order() {
+        assert_eq!(remaining_after_prevention(10, SelfTradePrevention::CancelOldest, 4), 10);
+        assert_eq!(remaining_after_prevention(10, SelfTradePrevention::Decrement, 4), 6);
+    }
+}
@@ -29,4 +29,29 @@ pub struct OrderIssuerWithWallet {
 pub struct CurrencyExchange {
     buy_orders: Vec<BuyOrder>,
     sell_orders: Vec<SellOrder>,
-}
\ No newline at end of file
+}
+
+///
+/// Per-user limits applied when orders are placed
+#[derive(Clone, Copy, Debug)]
+pub struct OrderLimits {
+    pub max_open_orders_per_user: i64,
+    pub max_order_batch_size: usize,
+}
+
+///
+/// Users allowed to halt and resume trading on a currency pair
+#[derive(Clone, Debug, Default)]
+pub struct TradingAdmins {
+    user_ids: Vec<i32>,
+}
+
+impl TradingAdmins {
+    pub fn new(user_ids: Vec<i32>) -> Self {
+        Self { user_ids }
+    }
+
+    pub fn is_admin(&self, user_id: i32) -> bool {
+        self.user_ids.contains(&user_id)
+    }
+}
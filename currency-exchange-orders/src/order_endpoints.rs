@@ -1,9 +1,78 @@
 pub const GET_BUY_ORDERS: &str = "/api/v1/orders/buy";
 pub const GET_SELL_ORDERS: &str = "/api/v1/orders/sell";
+pub const GET_BUY_ORDER_FILLS: &str = "/api/v1/orders/buy/{id}/fills";
+pub const GET_SELL_ORDER_FILLS: &str = "/api/v1/orders/sell/{id}/fills";
 pub const GET_MY_BALANCE: &str = "/api/v1/me/balance";
+pub const GET_ORDER_BOOK: &str = "/api/v1/orderbook/{base}/{quote}";
+pub const GET_TRADES: &str = "/api/v1/trades/{base}/{quote}";
+pub const GET_MY_TRADES: &str = "/api/v1/me/trades";
+pub const GET_CANDLES: &str = "/api/v1/candles/{base}/{quote}";
+pub const GET_TICKERS: &str = "/api/v1/ticker";
+pub const GET_TICKER: &str = "/api/v1/ticker/{base}/{quote}";
+
+///
+/// Trading halts in force
+pub const GET_TRADING_HALTS: &str = "/api/v1/halts";
+
+///
+/// Admin halt (POST) and resume (DELETE) of trading on a currency pair
+pub const TRADING_HALT_BY_PAIR: &str = "/api/v1/admin/halts/{base}/{quote}";
+
+///
+/// Caller's own stop orders
+pub const GET_STOP_ORDERS: &str = "/api/v1/orders/stop";
+
+///
+/// Caller's live orders and pending stop orders (GET), or cancel them all, optionally per pair (DELETE)
+pub const GET_MY_ORDERS: &str = "/api/v1/me/orders";
+
+///
+/// Caller's buy or sell order placed under a client order id
+pub const GET_MY_ORDER_BY_CLIENT_ID: &str = "/api/v1/me/orders/by-client-id/{client_order_id}";
+
+///
+/// Caller's trading settings, GET reads and PUT updates them
+pub const MY_TRADING_SETTINGS: &str = "/api/v1/me/settings";
+
+///
+/// Matches between the caller's own orders that self-trade prevention stopped, newest first
+pub const GET_MY_PREVENTED_TRADES: &str = "/api/v1/me/prevented-trades";
+
+///
+/// WebSocket with order book snapshots, sequenced book diffs and trades per subscribed pair
+pub const WS_MARKET_DATA: &str = "/api/v1/ws/market";
+
+///
+/// WebSocket with the caller's own order, fill and balance changes
+pub const WS_USER_DATA: &str = "/api/v1/ws/me";
 pub const POST_NEW_BUY_ORDER: &str = "/api/v1/orders/buy/new";
 pub const POST_NEW_SELL_ORDER: &str = "/api/v1/orders/sell/new";
+pub const POST_BACKFILL_CANDLES: &str = "/api/v1/candles/{base}/{quote}/backfill";
+pub const POST_MARKET_BUY: &str = "/api/v1/orders/buy/market";
+pub const POST_MARKET_SELL: &str = "/api/v1/orders/sell/market";
+pub const POST_NEW_STOP_ORDER: &str = "/api/v1/orders/stop/new";
+pub const POST_NEW_OCO_ORDER: &str = "/api/v1/orders/oco/new";
+
+///
+/// Creates and cancels several of the caller's limit orders in one request
+pub const POST_ORDER_BATCH: &str = "/api/v1/orders/batch";
 
 pub const PUT_SELL_CURRENCY: &str = "/api/v1/orders/sell/execute";
 
-pub const PUT_BUY_CURRENCY: &str = "/api/v1/orders/buy/execute";
\ No newline at end of file
+pub const PUT_BUY_CURRENCY: &str = "/api/v1/orders/buy/execute";
+
+///
+/// DELETE cancels, PATCH amends
+pub const BUY_ORDER_BY_ID: &str = "/api/v1/orders/buy/{id}";
+
+///
+/// DELETE cancels, PATCH amends
+pub const SELL_ORDER_BY_ID: &str = "/api/v1/orders/sell/{id}";
+
+///
+/// DELETE cancels a pending stop order
+pub const STOP_ORDER_BY_ID: &str = "/api/v1/orders/stop/{id}";
+
+///
+/// DELETE cancels every working leg of the order group
+pub const ORDER_GROUP_BY_ID: &str = "/api/v1/orders/oco/{id}";
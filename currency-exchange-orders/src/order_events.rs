@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use currency_exchange_data::datasource::models::{BuyOrder, MatchReport, OrderGroup, PreventedTrade, SellOrder, StopOrder, Trade};
+
+///
+/// Order lifecycle changes published to anyone listening on the `OrderEventBus`
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderEvent {
+    BuyOrderExpired { buy_order_id: i32, issuer_id: i32 },
+    SellOrderExpired { sell_order_id: i32, issuer_id: i32 },
+    ///
+    /// Resting liquidity for the pair may have changed
+    BookChanged { base_currency_id: i32, quote_currency_id: i32 },
+    TradeExecuted { trade: Trade, buyer_id: Option<i32>, seller_id: Option<i32> },
+    ///
+    /// State of the order right after a request created, amended or cancelled it
+    BuyOrderUpdated { order: BuyOrder },
+    SellOrderUpdated { order: SellOrder },
+    ///
+    /// Stop order was placed, cancelled, triggered or rejected
+    StopOrderUpdated { order: StopOrder },
+    ///
+    /// Two orders of the same user crossed and were resolved by its self-trade prevention mode
+    SelfTradePrevented { prevented: PreventedTrade },
+}
+
+///
+/// In-process fan-out of `OrderEvent`s. Events published with no subscriber are dropped
+#[derive(Clone)]
+pub struct OrderEventBus {
+    sender: broadcast::Sender<OrderEvent>,
+}
+
+impl OrderEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: OrderEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn book_changed(&self, base_currency_id: i32, quote_currency_id: i32) {
+        self.publish(OrderEvent::BookChanged { base_currency_id, quote_currency_id });
+    }
+
+    pub fn buy_order_changed(&self, order: &BuyOrder) {
+        self.publish(OrderEvent::BuyOrderUpdated { order: order.clone() });
+        self.book_changed(order.buy_currency_id.unwrap_or_default(), order.sell_currency_id.unwrap_or_default());
+    }
+
+    pub fn sell_order_changed(&self, order: &SellOrder) {
+        self.publish(OrderEvent::SellOrderUpdated { order: order.clone() });
+        self.book_changed(order.sell_currency_id.unwrap_or_default(), order.buy_currency_id.unwrap_or_default());
+    }
+
+    pub fn stop_order_changed(&self, order: &StopOrder) {
+        self.publish(OrderEvent::StopOrderUpdated { order: order.clone() });
+    }
+
+    ///
+    /// Publishes the current state of every leg of the group
+    pub fn order_group_changed(&self, group: &OrderGroup) {
+        group.buy_orders.iter().for_each(|order| self.buy_order_changed(order));
+        group.sell_orders.iter().for_each(|order| self.sell_order_changed(order));
+        group.stop_orders.iter().for_each(|order| self.stop_order_changed(order));
+    }
+
+    ///
+    /// Publishes every exchange as a trade and every prevented self-trade,
+    /// then one book change per pair they touched
+    pub fn orders_matched(&self, report: &MatchReport) {
+        let mut pairs = HashSet::new();
+        for exchange in &report.exchanges {
+            self.publish(OrderEvent::TradeExecuted {
+                trade: Trade::from(exchange),
+                buyer_id: exchange.buyer_id,
+                seller_id: exchange.seller_id,
+            });
+            if let (Some(base), Some(quote)) = (exchange.base_currency_id, exchange.quote_currency_id) {
+                pairs.insert((base, quote));
+            }
+        }
+        for prevented in &report.prevented_trades {
+            self.publish(OrderEvent::SelfTradePrevented { prevented: prevented.clone() });
+            pairs.insert((prevented.base_currency_id, prevented.quote_currency_id));
+        }
+        for (base, quote) in pairs {
+            self.book_changed(base, quote);
+        }
+    }
+}
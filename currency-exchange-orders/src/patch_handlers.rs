@@ -0,0 +1,71 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::web::{Data, Json, Path};
+use sqlx::PgPool;
+use currency_exchange_data::datasource::api_models::AmendOrderRequest;
+use currency_exchange_data::datasource::error_responses::OrderUpdateFailedResponse;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_middleware::jwt::Claims;
+use crate::delete_handlers::order_update_response;
+use crate::order_events::OrderEventBus;
+use crate::post_handlers::{match_stored_buy_order, match_stored_sell_order};
+
+///
+/// Amends a buy order owned by the caller and matches it again if it now crosses
+pub async fn amend_buy_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    path: Path<i32>,
+    body: Json<AmendOrderRequest>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let request = body.into_inner();
+        if request.is_empty() {
+            return HttpResponse::BadRequest().json(OrderUpdateFailedResponse::new("Nothing to amend"));
+        }
+        let repo = Repository::new(pool.get_ref().clone());
+        match repo.amend_buy_order(&path.into_inner(), &uid, &request).await {
+            Ok(order) => match match_stored_buy_order(&repo, pool.get_ref(), &events, order).await {
+                Ok(order) => HttpResponse::Ok().json(order),
+                Err(e) => HttpResponse::InternalServerError().json(OrderUpdateFailedResponse::new(e.to_string())),
+            },
+            amended => order_update_response(amended),
+        }
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Amends a sell order owned by the caller and matches it again if it now crosses
+pub async fn amend_sell_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    path: Path<i32>,
+    body: Json<AmendOrderRequest>
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let request = body.into_inner();
+        if request.is_empty() {
+            return HttpResponse::BadRequest().json(OrderUpdateFailedResponse::new("Nothing to amend"));
+        }
+        let repo = Repository::new(pool.get_ref().clone());
+        match repo.amend_sell_order(&path.into_inner(), &uid, &request).await {
+            Ok(order) => match match_stored_sell_order(&repo, pool.get_ref(), &events, order).await {
+                Ok(order) => HttpResponse::Ok().json(order),
+                Err(e) => HttpResponse::InternalServerError().json(OrderUpdateFailedResponse::new(e.to_string())),
+            },
+            amended => order_update_response(amended),
+        }
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
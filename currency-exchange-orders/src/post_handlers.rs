@@ -1,29 +1,61 @@
 use actix_web::{web, HttpRequest, HttpResponse};
-use actix_web::web::{Data, Json};
+use actix_web::web::{Data, Json, Path};
 use sqlx::PgPool;
-use currency_exchange_data::datasource::api_models::{CreateBuyOrderRequest, CreateSellOrderRequest};
-use currency_exchange_data::datasource::error_responses::{CreateBuyOrderResponse, CreateSellOrderResponse};
+use time::OffsetDateTime;
+use currency_exchange_data::datasource::api_models::{BackfillCandlesRequest, BackfillCandlesResponse, BatchOrderItem, BatchOrderRequest, CreateBuyOrderRequest, CreateOcoOrderRequest, CreateSellOrderRequest, CreateStopOrderRequest, MarketOrderRequest, TradingHaltRequest};
+use currency_exchange_data::datasource::error_responses::{CandleBackfillFailedResponse, CreateBuyOrderResponse, CreateOcoOrderResponse, CreateSellOrderResponse, CreateStopOrderResponse, ExchangeFailedResponse, OrderBatchFailedResponse, TradingHaltFailedResponse};
+use currency_exchange_data::datasource::models::{BatchOrderResponse, BatchOrderResult, BuyOrder, ClientOrder, MarketOrderResult, MatchReport, OrderSide, SellOrder, TimeInForce};
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::repository::batch_order_repository::BatchOrderRepository;
+use currency_exchange_data::datasource::repository::market_data_repository::MarketDataRepository;
+use currency_exchange_data::datasource::repository::order_group_repository::OrderGroupRepository;
 use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
 use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_data::datasource::repository::stop_order_repository::StopOrderRepository;
+use currency_exchange_data::datasource::repository::trading_halt_repository::TradingHaltRepository;
 use currency_exchange_middleware::jwt::Claims;
+use crate::delete_handlers::{publish_cancelled_orders, publish_order_group};
+use crate::errors::ValidationErrors;
+use crate::get_handlers::currency_pair;
+use crate::matching_engine::{MatchingEngine, OrderMatcher};
+use crate::models::{OrderLimits, TradingAdmins};
+use crate::order_events::OrderEventBus;
 
 pub async fn create_buy_order(
     claims: web::ReqData<Claims>,
     req: HttpRequest,
     pool: Data<PgPool>,
+    limits: Data<OrderLimits>,
+    events: Data<OrderEventBus>,
     body: Json<CreateBuyOrderRequest>,
 ) -> HttpResponse {
     let headers = req.headers();
-    if let Some(header) = headers.get("Authorization") {
+    if headers.get("Authorization").is_some() {
         let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
         let json = body.into_inner();
-        if uid == json.issuer_id {
+        if let Err(message) = json.check(OffsetDateTime::now_utc()) {
+            HttpResponse::BadRequest().json(CreateBuyOrderResponse::new(message))
+        } else if uid == json.issuer_id {
             let repo = Repository::new(pool.get_ref().clone());
-            let resp = repo.create_buy_order(&json)
-                .await
-                .map_err(|_e| HttpResponse::InternalServerError().finish());
+            let resp = repo.create_buy_order(&json, limits.max_open_orders_per_user)
+                .await;
             if let Ok(data) = resp {
-                HttpResponse::Created().json(data)
+                match match_stored_buy_order(&repo, pool.get_ref(), &events, data).await {
+                    Ok(order) => HttpResponse::Created().json(order),
+                    Err(e) => HttpResponse::InternalServerError().json(CreateBuyOrderResponse::new(e.to_string())),
+                }
+            } else if let Err(DataError::DuplicateClientOrderIdError(message)) = resp {
+                let client_order_id = json.client_order_id.as_deref().unwrap_or_default();
+                match repo.find_client_order(&uid, client_order_id).await {
+                    Ok(ClientOrder { buy_order: Some(order), .. }) => HttpResponse::Ok().json(order),
+                    _ => HttpResponse::Conflict().json(CreateBuyOrderResponse::new(message)),
+                }
+            } else if let Err(DataError::OpenOrderLimitError(message)) = resp {
+                HttpResponse::Conflict().json(CreateBuyOrderResponse::new(message))
+            } else if let Err(DataError::TradingHaltedError(message)) = resp {
+                HttpResponse::Conflict().json(CreateBuyOrderResponse::new(message))
+            } else if let Err(DataError::InsufficientFundsError(_)) = resp {
+                HttpResponse::BadRequest().json(CreateBuyOrderResponse::new(ValidationErrors::InsufficientProvidedCurrencyError.to_string()))
             } else {
                 HttpResponse::BadRequest().json(CreateBuyOrderResponse::new("Failed to create order"))
             }
@@ -39,19 +71,37 @@ pub async fn create_sell_order(
     claims: web::ReqData<Claims>,
     req: HttpRequest,
     pool: Data<PgPool>,
+    limits: Data<OrderLimits>,
+    events: Data<OrderEventBus>,
     body: Json<CreateSellOrderRequest>,
 ) -> HttpResponse {
     let headers = req.headers();
-    if let Some(header) = headers.get("Authorization") {
+    if headers.get("Authorization").is_some() {
         let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
         let json = body.into_inner();
-        if uid == json.issuer_id {
+        if let Err(message) = json.check(OffsetDateTime::now_utc()) {
+            HttpResponse::BadRequest().json(CreateSellOrderResponse::new(message))
+        } else if uid == json.issuer_id {
             let repo = Repository::new(pool.get_ref().clone());
-            let resp = repo.create_sell_order(&json)
-                .await
-                .map_err(|_e| HttpResponse::InternalServerError().finish());
+            let resp = repo.create_sell_order(&json, limits.max_open_orders_per_user)
+                .await;
             if let Ok(data) = resp {
-                HttpResponse::Created().json(data)
+                match match_stored_sell_order(&repo, pool.get_ref(), &events, data).await {
+                    Ok(order) => HttpResponse::Created().json(order),
+                    Err(e) => HttpResponse::InternalServerError().json(CreateSellOrderResponse::new(e.to_string())),
+                }
+            } else if let Err(DataError::DuplicateClientOrderIdError(message)) = resp {
+                let client_order_id = json.client_order_id.as_deref().unwrap_or_default();
+                match repo.find_client_order(&uid, client_order_id).await {
+                    Ok(ClientOrder { sell_order: Some(order), .. }) => HttpResponse::Ok().json(order),
+                    _ => HttpResponse::Conflict().json(CreateSellOrderResponse::new(message)),
+                }
+            } else if let Err(DataError::OpenOrderLimitError(message)) = resp {
+                HttpResponse::Conflict().json(CreateSellOrderResponse::new(message))
+            } else if let Err(DataError::TradingHaltedError(message)) = resp {
+                HttpResponse::Conflict().json(CreateSellOrderResponse::new(message))
+            } else if let Err(DataError::InsufficientFundsError(_)) = resp {
+                HttpResponse::BadRequest().json(CreateSellOrderResponse::new(ValidationErrors::InsufficientProvidedCurrencyError.to_string()))
             } else {
                 HttpResponse::BadRequest().json(CreateSellOrderResponse::new("Failed to create order"))
             }
@@ -61,4 +111,420 @@ pub async fn create_sell_order(
     } else {
         HttpResponse::BadRequest().body("No Authorization Header")
     }
-}
\ No newline at end of file
+}
+
+///
+/// Matches a freshly stored or amended buy order and returns its state afterwards
+pub(crate) async fn match_stored_buy_order(repo: &Repository, pool: &PgPool, events: &OrderEventBus, order: BuyOrder) -> Result<BuyOrder, DataError> {
+    events.buy_order_changed(&order);
+    let engine = MatchingEngine::new(pool.clone());
+    let report = engine.place_buy_order(&order).await?;
+    events.orders_matched(&report);
+    let order_id = order.buy_order_id.unwrap_or_default();
+    match repo.find_buy_order(&order_id).await {
+        Ok(Some(matched)) => {
+            if !matched.time_in_force.unwrap_or(TimeInForce::Gtc).rests() {
+                events.buy_order_changed(&matched);
+            }
+            Ok(matched)
+        }
+        _ => Ok(order),
+    }
+}
+
+///
+/// Matches a freshly stored or amended sell order and returns its state afterwards
+pub(crate) async fn match_stored_sell_order(repo: &Repository, pool: &PgPool, events: &OrderEventBus, order: SellOrder) -> Result<SellOrder, DataError> {
+    events.sell_order_changed(&order);
+    let engine = MatchingEngine::new(pool.clone());
+    let report = engine.place_sell_order(&order).await?;
+    events.orders_matched(&report);
+    let order_id = order.sell_order_id.unwrap_or_default();
+    match repo.find_sell_order(&order_id).await {
+        Ok(Some(matched)) => {
+            if !matched.time_in_force.unwrap_or(TimeInForce::Gtc).rests() {
+                events.sell_order_changed(&matched);
+            }
+            Ok(matched)
+        }
+        _ => Ok(order),
+    }
+}
+
+///
+/// Places a stop order for the caller that stays dormant until its stop price trades
+pub async fn create_stop_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    body: Json<CreateStopOrderRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let json = body.into_inner();
+        let repo = Repository::new(pool.get_ref().clone());
+        let last_price = match repo.last_trade_price(&json.base_currency_id, &json.quote_currency_id).await {
+            Ok(last_price) => last_price,
+            Err(e) => return HttpResponse::InternalServerError().json(CreateStopOrderResponse::new(e.to_string())),
+        };
+        if let Err(message) = json.check(last_price) {
+            return HttpResponse::BadRequest().json(CreateStopOrderResponse::new(message));
+        }
+        match repo.create_stop_order(&uid, &json).await {
+            Ok(order) => {
+                events.stop_order_changed(&order);
+                HttpResponse::Created().json(order)
+            }
+            Err(DataError::TradingHaltedError(message)) => HttpResponse::Conflict().json(CreateStopOrderResponse::new(message)),
+            Err(_) => HttpResponse::BadRequest().json(CreateStopOrderResponse::new("Failed to create stop order")),
+        }
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Places a one-cancels-other pair: the limit leg goes to the book right away, the stop leg waits for its price
+pub async fn create_oco_order(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    limits: Data<OrderLimits>,
+    events: Data<OrderEventBus>,
+    body: Json<CreateOcoOrderRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let json = body.into_inner();
+        let repo = Repository::new(pool.get_ref().clone());
+        let last_price = match repo.last_trade_price(&json.base_currency_id, &json.quote_currency_id).await {
+            Ok(last_price) => last_price,
+            Err(e) => return HttpResponse::InternalServerError().json(CreateOcoOrderResponse::new(e.to_string())),
+        };
+        if let Err(message) = json.check(last_price) {
+            return HttpResponse::BadRequest().json(CreateOcoOrderResponse::new(message));
+        }
+        let group = match repo.create_oco_order(&uid, &json, limits.max_open_orders_per_user).await {
+            Ok(group) => group,
+            Err(DataError::OpenOrderLimitError(message)) => return HttpResponse::Conflict().json(CreateOcoOrderResponse::new(message)),
+            Err(DataError::TradingHaltedError(message)) => return HttpResponse::Conflict().json(CreateOcoOrderResponse::new(message)),
+            Err(DataError::InsufficientFundsError(_)) => return HttpResponse::BadRequest()
+                .json(CreateOcoOrderResponse::new(ValidationErrors::InsufficientProvidedCurrencyError.to_string())),
+            Err(_) => return HttpResponse::BadRequest().json(CreateOcoOrderResponse::new("Failed to create order group")),
+        };
+        events.order_group_changed(&group);
+        let engine = MatchingEngine::new(pool.get_ref().clone());
+        for order in &group.buy_orders {
+            match engine.place_buy_order(order).await {
+                Ok(report) => events.orders_matched(&report),
+                Err(e) => return HttpResponse::InternalServerError().json(CreateOcoOrderResponse::new(e.to_string())),
+            }
+        }
+        for order in &group.sell_orders {
+            match engine.place_sell_order(order).await {
+                Ok(report) => events.orders_matched(&report),
+                Err(e) => return HttpResponse::InternalServerError().json(CreateOcoOrderResponse::new(e.to_string())),
+            }
+        }
+        match repo.find_order_group(&group.order_group_id).await {
+            Ok(Some(group)) => HttpResponse::Created().json(group),
+            _ => HttpResponse::Created().json(group),
+        }
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Creates and cancels a batch of the caller's limit orders, all or nothing when `atomic` is set
+/// and item by item otherwise. Orders are matched once the batch is stored, in request order
+pub async fn place_order_batch(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    limits: Data<OrderLimits>,
+    events: Data<OrderEventBus>,
+    body: Json<BatchOrderRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let json = body.into_inner();
+        if let Err(message) = json.check(limits.max_order_batch_size) {
+            return HttpResponse::BadRequest().json(OrderBatchFailedResponse::new(message));
+        }
+        let now = OffsetDateTime::now_utc();
+        let checks: Vec<Result<(), &'static str>> = json.items.iter()
+            .map(|item| item.check(uid, now))
+            .collect();
+        let repo = Repository::new(pool.get_ref().clone());
+        let results = if json.atomic {
+            if let Some((index, message)) = checks.iter().enumerate().find_map(|(index, check)| check.err().map(|message| (index, message))) {
+                return HttpResponse::BadRequest().json(OrderBatchFailedResponse::new(format!("Batch item {} failed: {}", index, message)));
+            }
+            match repo.apply_order_batch(&uid, &json.items, limits.max_open_orders_per_user).await {
+                Ok(results) => results,
+                Err(e) => return HttpResponse::BadRequest().json(OrderBatchFailedResponse::new(e.to_string())),
+            }
+        } else {
+            let mut results = Vec::with_capacity(json.items.len());
+            for (item, check) in json.items.iter().zip(checks) {
+                let result = match check {
+                    Ok(()) => apply_batch_item(&repo, uid, item, limits.max_open_orders_per_user).await,
+                    Err(message) => BatchOrderResult { error: Some(message.to_string()), ..Default::default() },
+                };
+                results.push(result);
+            }
+            results
+        };
+        let mut settled = Vec::with_capacity(results.len());
+        for (item, result) in json.items.iter().zip(results) {
+            settled.push(settle_batch_item(&repo, pool.get_ref(), &events, item, result).await);
+        }
+        HttpResponse::Ok().json(BatchOrderResponse { atomic: json.atomic, results: settled })
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Applies one item of a non-atomic batch in its own transaction
+async fn apply_batch_item(repo: &Repository, uid: i32, item: &BatchOrderItem, max_open_orders: i64) -> BatchOrderResult {
+    let result = match item {
+        BatchOrderItem::CreateBuy(request) => repo.create_buy_order(request, max_open_orders).await
+            .map(|order| BatchOrderResult { buy_order: Some(order), ..Default::default() }),
+        BatchOrderItem::CreateSell(request) => repo.create_sell_order(request, max_open_orders).await
+            .map(|order| BatchOrderResult { sell_order: Some(order), ..Default::default() }),
+        BatchOrderItem::CancelBuy { buy_order_id } => repo.cancel_buy_order(buy_order_id, &uid).await
+            .map(|order| BatchOrderResult { buy_order: Some(order), ..Default::default() }),
+        BatchOrderItem::CancelSell { sell_order_id } => repo.cancel_sell_order(sell_order_id, &uid).await
+            .map(|order| BatchOrderResult { sell_order: Some(order), ..Default::default() }),
+    };
+    result.unwrap_or_else(|e| BatchOrderResult { error: Some(e.to_string()), ..Default::default() })
+}
+
+///
+/// Matches orders the batch created and publishes the ones it cancelled, along with their order groups
+async fn settle_batch_item(
+    repo: &Repository,
+    pool: &PgPool,
+    events: &OrderEventBus,
+    item: &BatchOrderItem,
+    result: BatchOrderResult
+) -> BatchOrderResult {
+    match (item, result) {
+        (BatchOrderItem::CreateBuy(_), BatchOrderResult { buy_order: Some(order), .. }) => {
+            let order_id = order.buy_order_id;
+            match match_stored_buy_order(repo, pool, events, order).await {
+                Ok(order) => BatchOrderResult { buy_order: Some(order), ..Default::default() },
+                Err(e) => {
+                    let order = repo.find_buy_order(&order_id.unwrap_or_default()).await.ok().flatten();
+                    BatchOrderResult { buy_order: order, error: Some(e.to_string()), ..Default::default() }
+                }
+            }
+        }
+        (BatchOrderItem::CreateSell(_), BatchOrderResult { sell_order: Some(order), .. }) => {
+            let order_id = order.sell_order_id;
+            match match_stored_sell_order(repo, pool, events, order).await {
+                Ok(order) => BatchOrderResult { sell_order: Some(order), ..Default::default() },
+                Err(e) => {
+                    let order = repo.find_sell_order(&order_id.unwrap_or_default()).await.ok().flatten();
+                    BatchOrderResult { sell_order: order, error: Some(e.to_string()), ..Default::default() }
+                }
+            }
+        }
+        (BatchOrderItem::CancelBuy { .. }, result) => {
+            if let Some(order) = &result.buy_order {
+                events.buy_order_changed(order);
+                publish_order_group(repo, events, order.order_group_id).await;
+            }
+            result
+        }
+        (BatchOrderItem::CancelSell { .. }, result) => {
+            if let Some(order) = &result.sell_order {
+                events.sell_order_changed(order);
+                publish_order_group(repo, events, order.order_group_id).await;
+            }
+            result
+        }
+        (_, result) => result,
+    }
+}
+
+///
+/// Halts trading on `base`/`quote` in both directions, cancelling or freezing the orders on it.
+/// Only users listed in `ADMIN_USER_IDS` may halt trading
+pub async fn halt_trading(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    admins: Data<TradingAdmins>,
+    events: Data<OrderEventBus>,
+    path: Path<(String, String)>,
+    body: Json<TradingHaltRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        if !admins.is_admin(uid) {
+            return HttpResponse::Forbidden().json(TradingHaltFailedResponse::new("Only admins can halt trading"));
+        }
+        let (base_code, quote_code) = path.into_inner();
+        let repo = Repository::new(pool.get_ref().clone());
+        let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+            Ok(pair) => pair,
+            Err(response) => return response,
+        };
+        match repo.halt_trading(&uid, &base_id, &quote_id, &body.into_inner()).await {
+            Ok(result) => {
+                tracing::warn!("User {} halted trading on {}/{} ({:?})", uid, base_code, quote_code, result.halt.mode);
+                publish_cancelled_orders(&events, &result.cancelled_orders);
+                HttpResponse::Created().json(result)
+            }
+            Err(DataError::TradingHaltedError(message)) => HttpResponse::Conflict().json(TradingHaltFailedResponse::new(message)),
+            Err(e) => HttpResponse::InternalServerError().json(TradingHaltFailedResponse::new(e.to_string())),
+        }
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Rebuilds closed candles for `base`/`quote` over a historical range from recorded exchanges
+pub async fn backfill_candles(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    admins: Data<TradingAdmins>,
+    path: Path<(String, String)>,
+    body: Json<BackfillCandlesRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        if !admins.is_admin(uid) {
+            return HttpResponse::Forbidden().json(CandleBackfillFailedResponse::new("Only admins can backfill candles"));
+        }
+        let (base_code, quote_code) = path.into_inner();
+        let json = body.into_inner();
+        if let Err(message) = json.check() {
+            return HttpResponse::BadRequest().json(CandleBackfillFailedResponse::new(message));
+        }
+        let repo = Repository::new(pool.get_ref().clone());
+        let (base_id, quote_id) = match currency_pair(&repo, &base_code, &quote_code).await {
+            Ok(pair) => pair,
+            Err(response) => return response,
+        };
+        match repo.backfill_candles(&base_id, &quote_id, json.from, json.to).await {
+            Ok(candles_written) => {
+                tracing::warn!("User {} backfilled {}/{} candles from {} to {}", uid, base_code, quote_code, json.from, json.to);
+                HttpResponse::Ok().json(BackfillCandlesResponse { candles_written })
+            }
+            Err(e) => HttpResponse::InternalServerError().json(CandleBackfillFailedResponse::new(e.to_string())),
+        }
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Buys `amount` of `buy_currency_id` at the best available prices
+pub async fn market_buy(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    body: Json<MarketOrderRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let taker_id = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let json = body.into_inner();
+        if let Err(message) = check_market_order(&json, OrderSide::Buy) {
+            return HttpResponse::BadRequest().json(ExchangeFailedResponse::new(message));
+        }
+        if let Some(response) = trading_halted_response(&Repository::new(pool.get_ref().clone()), &json).await {
+            return response;
+        }
+        let engine = MatchingEngine::new(pool.get_ref().clone());
+        market_order_response(OrderSide::Buy, &json, engine.execute_market_buy(taker_id, &json).await, &events)
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Sells `amount` of `sell_currency_id` at the best available prices
+pub async fn market_sell(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    events: Data<OrderEventBus>,
+    body: Json<MarketOrderRequest>,
+) -> HttpResponse {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let taker_id = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let json = body.into_inner();
+        if let Err(message) = check_market_order(&json, OrderSide::Sell) {
+            return HttpResponse::BadRequest().json(ExchangeFailedResponse::new(message));
+        }
+        if let Some(response) = trading_halted_response(&Repository::new(pool.get_ref().clone()), &json).await {
+            return response;
+        }
+        let engine = MatchingEngine::new(pool.get_ref().clone());
+        market_order_response(OrderSide::Sell, &json, engine.execute_market_sell(taker_id, &json).await, &events)
+    } else {
+        HttpResponse::BadRequest().body("No Authorization Header")
+    }
+}
+
+///
+/// Market orders are turned away while their pair is halted instead of finding nothing to fill
+async fn trading_halted_response(repo: &Repository, request: &MarketOrderRequest) -> Option<HttpResponse> {
+    match repo.find_trading_halt(&request.buy_currency_id, &request.sell_currency_id).await {
+        Ok(None) => None,
+        Ok(Some(_)) => Some(HttpResponse::Conflict().json(ExchangeFailedResponse::new(
+            format!("Trading between currencies with id={} and id={} is halted", request.buy_currency_id, request.sell_currency_id)
+        ))),
+        Err(e) => Some(HttpResponse::InternalServerError().json(ExchangeFailedResponse::new(e.to_string()))),
+    }
+}
+
+fn check_market_order(request: &MarketOrderRequest, side: OrderSide) -> Result<(), &'static str> {
+    let message = if request.amount <= 0 {
+        "Amount must be positive"
+    } else if request.buy_currency_id == request.sell_currency_id {
+        "Buy and sell currency must differ"
+    } else if request.max_slippage_percent.is_some_and(|percent| !(0.0..=100.0).contains(&percent)) {
+        "Max slippage must be between 0 and 100 percent"
+    } else if request.max_spend.is_some_and(|spend| spend <= 0) {
+        "Max spend must be positive"
+    } else if side == OrderSide::Sell && request.max_spend.is_some() {
+        "Max spend only applies to market buys"
+    } else {
+        return Ok(());
+    };
+    Err(message)
+}
+
+fn market_order_response(
+    side: OrderSide,
+    request: &MarketOrderRequest,
+    result: Result<MatchReport, DataError>,
+    events: &OrderEventBus
+) -> HttpResponse {
+    match result {
+        Ok(report) if report.matches() == 0 => {
+            HttpResponse::Conflict().json(ExchangeFailedResponse::new("No resting orders could be filled within the requested limits"))
+        }
+        Ok(report) => {
+            events.orders_matched(&report);
+            HttpResponse::Ok().json(MarketOrderResult::new(side, request.amount, &report.exchanges).with_prevented_trades(report.prevented_trades))
+        }
+        Err(e) => HttpResponse::BadRequest().json(ExchangeFailedResponse::new(e.to_string())),
+    }
+}
@@ -1,67 +1,111 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use actix_web::web::{Data, Json};
 use sqlx::PgPool;
-use currency_exchange_data::datasource::api_models::ExchangeCurrencyRequest;
+use currency_exchange_data::datasource::api_models::{BuyCurrencyRequest, SellCurrencyRequest, TradingSettingsRequest};
+use currency_exchange_data::datasource::error_responses::{ExchangeFailedResponse, TradingSettingsFailedResponse};
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::{MatchOutcome, MatchReport};
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_data::datasource::repository::trading_settings_repository::TradingSettingsRepository;
 use currency_exchange_middleware::jwt::Claims;
+use crate::order_events::OrderEventBus;
 use crate::order_transaction_manager::{BuyTransactionManager, SellTransactionManager, Transaction, TransactionType};
 
+///
+/// Buys currency from the resting sell order given in the body
 pub async fn buy_currency(
     claims: web::ReqData<Claims>,
     req: HttpRequest,
     pool: Data<PgPool>,
-    body: Json<ExchangeCurrencyRequest>
+    events: Data<OrderEventBus>,
+    body: Json<BuyCurrencyRequest>
 ) -> HttpResponse {
     let json = body.into_inner();
     let headers = req.headers();
-    if let Some(id) = headers.get("Authorization") {
-        if claims.sub.parse::<i32>().unwrap() == json.order_issuer_id {
-            let tx = Transaction::new(
-                TransactionType::Buy,
-                pool.get_ref().clone(),
-                json.sum,
-                json.rate,
-                json.order_issuer_id,
-                json.incoming_currency_id,
-                json.outgoing_currency_id
-            );
-            let query = tx.process_buy_transaction()
-                .await
-                .expect("Transaction processing error");
-            HttpResponse::Ok().json(query)
-        } else {
-            HttpResponse::Unauthorized().finish()
+    if headers.get("Authorization").is_some() {
+        if json.amount <= 0 {
+            return HttpResponse::BadRequest().json(ExchangeFailedResponse::new("Amount must be positive"));
         }
+        let taker_id = claims.sub.parse::<i32>().unwrap();
+        let tx = Transaction::new(
+            TransactionType::Buy,
+            pool.get_ref().clone(),
+            taker_id,
+            json.sell_order_id,
+            json.amount
+        ).with_self_trade_prevention(json.self_trade_prevention);
+        exchange_response(tx.process_buy_transaction().await, &events)
     } else {
         HttpResponse::Unauthorized().finish()
     }
 }
+
+///
+/// Sells currency into the resting buy order given in the body
 pub async fn sell_currency(
     claims: web::ReqData<Claims>,
     req: HttpRequest,
     pool: Data<PgPool>,
-    body: Json<ExchangeCurrencyRequest>
+    events: Data<OrderEventBus>,
+    body: Json<SellCurrencyRequest>
+) -> HttpResponse {
+    let json = body.into_inner();
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        if json.amount <= 0 {
+            return HttpResponse::BadRequest().json(ExchangeFailedResponse::new("Amount must be positive"));
+        }
+        let taker_id = claims.sub.parse::<i32>().unwrap();
+        let tx = Transaction::new(
+            TransactionType::Sell,
+            pool.get_ref().clone(),
+            taker_id,
+            json.buy_order_id,
+            json.amount
+        ).with_self_trade_prevention(json.self_trade_prevention);
+        exchange_response(tx.process_sell_transaction().await, &events)
+    } else {
+        HttpResponse::Unauthorized().finish()
+    }
+}
+
+///
+/// Replaces the caller's trading settings. The self-trade prevention mode applies to
+/// orders placed afterwards that do not choose their own
+pub async fn update_trading_settings(
+    claims: web::ReqData<Claims>,
+    req: HttpRequest,
+    pool: Data<PgPool>,
+    body: Json<TradingSettingsRequest>
 ) -> HttpResponse {
     let json = body.into_inner();
     let headers = req.headers();
-    if let Some(id) = headers.get("Authorization") {
-        if claims.sub.parse::<i32>().unwrap() == json.order_issuer_id {
-            let tx = Transaction::new(
-                TransactionType::Buy,
-                pool.get_ref().clone(),
-                json.sum,
-                json.rate,
-                json.order_issuer_id,
-                json.incoming_currency_id,
-                json.outgoing_currency_id
-            );
-            let query = tx.process_sell_transaction()
-                .await
-                .expect("Transaction processing error");
-            HttpResponse::Ok().json(query)
-        } else {
-            HttpResponse::Unauthorized().finish()
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().unwrap();
+        let repo = Repository::new(pool.get_ref().clone());
+        match repo.update_trading_settings(&uid, &json).await {
+            Ok(settings) => HttpResponse::Ok().json(settings),
+            Err(e) => HttpResponse::BadRequest().json(TradingSettingsFailedResponse::new(e.to_string())),
         }
     } else {
         HttpResponse::Unauthorized().finish()
     }
-}
\ No newline at end of file
+}
+
+///
+/// A fill against one's own order answers with the prevented match instead of an exchange
+fn exchange_response(result: Result<MatchOutcome, DataError>, events: &OrderEventBus) -> HttpResponse {
+    match result {
+        Ok(outcome) => {
+            let mut report = MatchReport::default();
+            report.push(outcome);
+            events.orders_matched(&report);
+            match (report.exchanges.pop(), report.prevented_trades.pop()) {
+                (Some(exchange), _) => HttpResponse::Ok().json(exchange),
+                (None, prevented) => HttpResponse::Conflict().json(prevented),
+            }
+        }
+        Err(DataError::OrderNotFoundError(message)) => HttpResponse::NotFound().json(ExchangeFailedResponse::new(message)),
+        Err(e) => HttpResponse::BadRequest().json(ExchangeFailedResponse::new(e.to_string())),
+    }
+}
@@ -1,6 +1,7 @@
 use std::env;
 use std::net::TcpListener;
 use std::path::PathBuf;
+use std::time::Duration;
 use actix_cors::Cors;
 use actix_web::{web, App, HttpServer};
 use actix_web::web::Data;
@@ -8,10 +9,20 @@ use currency_exchange_middleware::database_connector::DatabaseConnector;
 use currency_exchange_middleware::env_parser::EnvParser;
 use currency_exchange_middleware::middleware::{JwtMiddleware};
 use currency_exchange_middleware::tracing_middleware::NetworkLogSpanBuilder;
-use crate::get_handlers::{buy_orders, currency_balance, sell_orders};
-use crate::order_endpoints::{GET_BUY_ORDERS, GET_MY_BALANCE, GET_SELL_ORDERS, POST_NEW_BUY_ORDER, POST_NEW_SELL_ORDER, PUT_BUY_CURRENCY, PUT_SELL_CURRENCY};
-use crate::post_handlers::{create_buy_order, create_sell_order};
-use crate::put_handlers::{buy_currency, sell_currency};
+use crate::delete_handlers::{cancel_buy_order, cancel_my_orders, cancel_order_group, cancel_sell_order, cancel_stop_order, resume_trading};
+use crate::expiry_sweeper::ExpirySweeper;
+use crate::get_handlers::{buy_order_fills, buy_orders, candles, currency_balance, my_order_by_client_id, my_orders, my_prevented_trades, my_trades, my_trading_settings, order_book, sell_order_fills, sell_orders, stop_orders, ticker, tickers, trades, trading_halts};
+use crate::market_data_feed::MarketDataFeed;
+use crate::market_data_socket::market_data_socket;
+use crate::models::{OrderLimits, TradingAdmins};
+use crate::stop_order_trigger::StopOrderTrigger;
+use crate::user_data_feed::UserDataFeed;
+use crate::user_data_socket::user_data_socket;
+use crate::order_events::OrderEventBus;
+use crate::order_endpoints::{BUY_ORDER_BY_ID, GET_BUY_ORDERS, GET_BUY_ORDER_FILLS, GET_CANDLES, GET_MY_BALANCE, GET_MY_ORDERS, GET_MY_ORDER_BY_CLIENT_ID, GET_MY_PREVENTED_TRADES, GET_MY_TRADES, GET_ORDER_BOOK, GET_SELL_ORDERS, GET_SELL_ORDER_FILLS, GET_STOP_ORDERS, GET_TICKER, GET_TICKERS, GET_TRADES, GET_TRADING_HALTS, POST_BACKFILL_CANDLES, POST_MARKET_BUY, POST_MARKET_SELL, POST_NEW_BUY_ORDER, POST_NEW_OCO_ORDER, POST_NEW_SELL_ORDER, POST_NEW_STOP_ORDER, POST_ORDER_BATCH, MY_TRADING_SETTINGS, ORDER_GROUP_BY_ID, PUT_BUY_CURRENCY, PUT_SELL_CURRENCY, SELL_ORDER_BY_ID, STOP_ORDER_BY_ID, TRADING_HALT_BY_PAIR, WS_MARKET_DATA, WS_USER_DATA};
+use crate::patch_handlers::{amend_buy_order, amend_sell_order};
+use crate::post_handlers::{backfill_candles, create_buy_order, create_oco_order, create_sell_order, create_stop_order, halt_trading, market_buy, market_sell, place_order_batch};
+use crate::put_handlers::{buy_currency, sell_currency, update_trading_settings};
 
 const ENV_DATABASE_URL: &str = "DATABASE_URL";
 const ENV_MAX_CONNECTIONS: &str = "MAX_CONNECTIONS";
@@ -19,6 +30,19 @@ const ENV_HOST: &str = "SERVER_HOST";
 
 const ENV_PORT: &str = "SERVER_PORT";
 
+const ENV_ORDER_EXPIRY_INTERVAL_SECS: &str = "ORDER_EXPIRY_INTERVAL_SECS";
+const ENV_ORDER_EXPIRY_BATCH_SIZE: &str = "ORDER_EXPIRY_BATCH_SIZE";
+const DEFAULT_ORDER_EXPIRY_INTERVAL_SECS: u64 = 30;
+const DEFAULT_ORDER_EXPIRY_BATCH_SIZE: i64 = 500;
+const ORDER_EVENTS_CAPACITY: usize = 1024;
+const MARKET_DATA_CAPACITY: usize = 1024;
+const USER_DATA_CAPACITY: usize = 1024;
+const ENV_MAX_OPEN_ORDERS_PER_USER: &str = "MAX_OPEN_ORDERS_PER_USER";
+const DEFAULT_MAX_OPEN_ORDERS_PER_USER: i64 = 200;
+const ENV_MAX_ORDER_BATCH_SIZE: &str = "MAX_ORDER_BATCH_SIZE";
+const DEFAULT_MAX_ORDER_BATCH_SIZE: usize = 50;
+const ENV_ADMIN_USER_IDS: &str = "ADMIN_USER_IDS";
+
 pub struct OrdersEnv {
     env: PathBuf,
 }
@@ -29,6 +53,49 @@ impl OrdersEnv {
             env: dotenvy::from_filename("./currency-exchange-orders/.env").expect("Cannot load env file"),
         }
     }
+
+    ///
+    /// How often expired orders are swept, 30 seconds unless `ORDER_EXPIRY_INTERVAL_SECS` is set
+    pub fn order_expiry_interval(&self) -> Duration {
+        let secs = env::var(ENV_ORDER_EXPIRY_INTERVAL_SECS)
+            .map(|v| v.parse::<u64>().expect("ORDER_EXPIRY_INTERVAL_SECS must be a number"))
+            .unwrap_or(DEFAULT_ORDER_EXPIRY_INTERVAL_SECS);
+        Duration::from_secs(secs)
+    }
+
+    ///
+    /// Orders expired per statement, 500 unless `ORDER_EXPIRY_BATCH_SIZE` is set
+    pub fn order_expiry_batch_size(&self) -> i64 {
+        env::var(ENV_ORDER_EXPIRY_BATCH_SIZE)
+            .map(|v| v.parse::<i64>().expect("ORDER_EXPIRY_BATCH_SIZE must be a number"))
+            .unwrap_or(DEFAULT_ORDER_EXPIRY_BATCH_SIZE)
+    }
+
+    ///
+    /// Live orders a user may hold across both sides, 200 unless `MAX_OPEN_ORDERS_PER_USER` is set
+    pub fn max_open_orders_per_user(&self) -> i64 {
+        env::var(ENV_MAX_OPEN_ORDERS_PER_USER)
+            .map(|v| v.parse::<i64>().expect("MAX_OPEN_ORDERS_PER_USER must be a number"))
+            .unwrap_or(DEFAULT_MAX_OPEN_ORDERS_PER_USER)
+    }
+
+    ///
+    /// Items accepted in one order batch, 50 unless `MAX_ORDER_BATCH_SIZE` is set
+    pub fn max_order_batch_size(&self) -> usize {
+        env::var(ENV_MAX_ORDER_BATCH_SIZE)
+            .map(|v| v.parse::<usize>().expect("MAX_ORDER_BATCH_SIZE must be a number"))
+            .unwrap_or(DEFAULT_MAX_ORDER_BATCH_SIZE)
+    }
+
+    ///
+    /// Comma separated ids of users who may halt trading, nobody unless `ADMIN_USER_IDS` is set
+    pub fn admin_user_ids(&self) -> Vec<i32> {
+        env::var(ENV_ADMIN_USER_IDS)
+            .map(|v| v.split(',')
+                .map(|id| id.trim().parse::<i32>().expect("ADMIN_USER_IDS must be comma separated user ids"))
+                .collect())
+            .unwrap_or_default()
+    }
 }
 
 impl EnvParser for OrdersEnv {
@@ -66,6 +133,25 @@ impl Server {
         );
 
         let pool = connector.connect().await;
+        let events = OrderEventBus::new(ORDER_EVENTS_CAPACITY);
+        let sweeper = ExpirySweeper::new(
+            pool.clone(),
+            self.env_parser.order_expiry_interval(),
+            self.env_parser.order_expiry_batch_size(),
+            events.clone()
+        );
+        actix_web::rt::spawn(sweeper.run());
+        let feed = MarketDataFeed::new(pool.clone(), MARKET_DATA_CAPACITY);
+        actix_web::rt::spawn(feed.clone().run(events.clone()));
+        let user_feed = UserDataFeed::new(pool.clone(), USER_DATA_CAPACITY);
+        actix_web::rt::spawn(user_feed.clone().run(events.clone()));
+        let limits = OrderLimits {
+            max_open_orders_per_user: self.env_parser.max_open_orders_per_user(),
+            max_order_batch_size: self.env_parser.max_order_batch_size(),
+        };
+        let admins = TradingAdmins::new(self.env_parser.admin_user_ids());
+        let stop_trigger = StopOrderTrigger::new(pool.clone(), events.clone(), limits);
+        actix_web::rt::spawn(stop_trigger.run());
         let host = self.env_parser.host();
         let port = self.env_parser.port();
         println!("Listening on {}:{}", host, port);
@@ -74,6 +160,11 @@ impl Server {
         env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
         HttpServer::new(move || App::new()
             .app_data(Data::new(pool.clone()))
+            .app_data(Data::new(events.clone()))
+            .app_data(Data::new(limits))
+            .app_data(Data::new(admins.clone()))
+            .app_data(Data::new(feed.clone()))
+            .app_data(Data::new(user_feed.clone()))
             .wrap(NetworkLogSpanBuilder::new().middleware().clone())
             .wrap(Cors::permissive())
             .service(
@@ -86,6 +177,65 @@ impl Server {
                     .wrap(JwtMiddleware)
                     .route(web::get().to(sell_orders))
             )
+            .service(
+                web::resource(GET_BUY_ORDER_FILLS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(buy_order_fills))
+            )
+            .service(
+                web::resource(GET_SELL_ORDER_FILLS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(sell_order_fills))
+            )
+            .service(
+                web::resource(GET_ORDER_BOOK)
+                    .route(web::get().to(order_book))
+            )
+            .service(
+                web::resource(GET_TRADES)
+                    .route(web::get().to(trades))
+            )
+            .service(
+                web::resource(GET_CANDLES)
+                    .route(web::get().to(candles))
+            )
+            .service(
+                web::resource(WS_USER_DATA)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(user_data_socket))
+            )
+            .service(
+                web::resource(WS_MARKET_DATA)
+                    .route(web::get().to(market_data_socket))
+            )
+            .service(
+                web::resource(GET_TICKERS)
+                    .route(web::get().to(tickers))
+            )
+            .service(
+                web::resource(GET_TRADING_HALTS)
+                    .route(web::get().to(trading_halts))
+            )
+            .service(
+                web::resource(TRADING_HALT_BY_PAIR)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(halt_trading))
+                    .route(web::delete().to(resume_trading))
+            )
+            .service(
+                web::resource(GET_TICKER)
+                    .route(web::get().to(ticker))
+            )
+            .service(
+                web::resource(POST_BACKFILL_CANDLES)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(backfill_candles))
+            )
+            .service(
+                web::resource(GET_MY_TRADES)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_trades))
+            )
             .service(
                 web::resource(GET_MY_BALANCE)
                     .wrap(JwtMiddleware)
@@ -101,6 +251,68 @@ impl Server {
                     .wrap(JwtMiddleware)
                     .route(web::post().to(create_sell_order)),
             )
+            .service(
+                web::resource(POST_MARKET_BUY)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(market_buy))
+            )
+            .service(
+                web::resource(POST_MARKET_SELL)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(market_sell))
+            )
+            .service(
+                web::resource(POST_NEW_STOP_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_stop_order))
+            )
+            .service(
+                web::resource(GET_STOP_ORDERS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(stop_orders))
+            )
+            .service(
+                web::resource(STOP_ORDER_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::delete().to(cancel_stop_order))
+            )
+            .service(
+                web::resource(POST_NEW_OCO_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_oco_order))
+            )
+            .service(
+                web::resource(POST_ORDER_BATCH)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(place_order_batch))
+            )
+            .service(
+                web::resource(ORDER_GROUP_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::delete().to(cancel_order_group))
+            )
+            .service(
+                web::resource(GET_MY_ORDERS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_orders))
+                    .route(web::delete().to(cancel_my_orders))
+            )
+            .service(
+                web::resource(GET_MY_ORDER_BY_CLIENT_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_order_by_client_id))
+            )
+            .service(
+                web::resource(MY_TRADING_SETTINGS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_trading_settings))
+                    .route(web::put().to(update_trading_settings))
+            )
+            .service(
+                web::resource(GET_MY_PREVENTED_TRADES)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_prevented_trades))
+            )
             .service(
                 web::resource(PUT_BUY_CURRENCY)
                     .wrap(JwtMiddleware)
@@ -110,6 +322,18 @@ impl Server {
                 web::resource(PUT_SELL_CURRENCY)
                     .wrap(JwtMiddleware)
                     .route(web::put().to(sell_currency))
+            )
+            .service(
+                web::resource(BUY_ORDER_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::delete().to(cancel_buy_order))
+                    .route(web::patch().to(amend_buy_order))
+            )
+            .service(
+                web::resource(SELL_ORDER_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::delete().to(cancel_sell_order))
+                    .route(web::patch().to(amend_sell_order))
             ))
             .listen(listener)?
             .run()
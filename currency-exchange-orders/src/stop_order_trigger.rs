@@ -0,0 +1,156 @@
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use currency_exchange_data::datasource::api_models::{CreateBuyOrderRequest, CreateSellOrderRequest, MarketOrderRequest};
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::{OrderSide, StopOrder};
+use currency_exchange_data::datasource::repository::order_group_repository::OrderGroupRepository;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_data::datasource::repository::stop_order_repository::StopOrderRepository;
+use crate::matching_engine::{MatchingEngine, OrderMatcher};
+use crate::models::OrderLimits;
+use crate::order_events::{OrderEvent, OrderEventBus};
+
+///
+/// Sets off pending stop orders once a trade on their pair reaches the stop price.
+/// Trades are handled one at a time in publication order and the stops one trade sets off
+/// are placed oldest first, so trades caused by a triggered stop are only looked at afterwards
+pub struct StopOrderTrigger {
+    pool: PgPool,
+    events: OrderEventBus,
+    limits: OrderLimits,
+}
+
+impl StopOrderTrigger {
+    pub fn new(pool: PgPool, events: OrderEventBus, limits: OrderLimits) -> Self {
+        Self { pool, events, limits }
+    }
+
+    pub async fn run(self) {
+        let mut receiver = self.events.subscribe();
+        if let Err(e) = self.resync().await {
+            tracing::warn!("Stop order resync failed: {}", e);
+        }
+        loop {
+            let result = match receiver.recv().await {
+                Ok(OrderEvent::TradeExecuted { trade, .. }) => match (trade.base_currency_id, trade.quote_currency_id) {
+                    (Some(base), Some(quote)) => self.trigger(base, quote, trade.price).await,
+                    _ => Ok(()),
+                },
+                Ok(_) => Ok(()),
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Stop order trigger missed {} order events", missed);
+                    self.resync().await
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if let Err(e) = result {
+                tracing::warn!("Stop order trigger failed: {}", e);
+            }
+        }
+    }
+
+    ///
+    /// Checks every pair with pending stops against its last trade price,
+    /// catching up on trades that were missed or happened while the service was down
+    pub async fn resync(&self) -> Result<(), DataError> {
+        let repository = Repository::new(self.pool.clone());
+        for (base, quote) in repository.find_stop_order_pairs().await? {
+            if let Some(price) = repository.last_trade_price(&base, &quote).await? {
+                self.trigger(base, quote, price).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn trigger(&self, base_currency_id: i32, quote_currency_id: i32, price: f64) -> Result<(), DataError> {
+        let repository = Repository::new(self.pool.clone());
+        for stop_order in repository.trigger_stop_orders(&base_currency_id, &quote_currency_id, price).await? {
+            let group = match stop_order.order_group_id {
+                Some(order_group_id) => repository.find_order_group(&order_group_id).await?,
+                None => None,
+            };
+            if let Some(group) = group {
+                group.buy_orders.iter().for_each(|order| self.events.buy_order_changed(order));
+                group.sell_orders.iter().for_each(|order| self.events.sell_order_changed(order));
+            }
+            let stop_order_id = stop_order.stop_order_id;
+            let updated = match self.place(&stop_order).await {
+                Ok(updated) => updated,
+                Err(e) => {
+                    tracing::info!("Stop order {} rejected: {}", stop_order_id, e);
+                    repository.reject_stop_order(&stop_order_id, &e.to_string()).await?
+                }
+            };
+            self.events.stop_order_changed(&updated);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Places the order a triggered stop stands for, returning the stop as it is now stored
+    async fn place(&self, stop_order: &StopOrder) -> Result<StopOrder, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let engine = MatchingEngine::new(self.pool.clone());
+        let stop_order_id = stop_order.stop_order_id;
+        match (stop_order.limit_price, stop_order.side) {
+            (Some(limit_price), OrderSide::Buy) => {
+                let request = CreateBuyOrderRequest::new(
+                    stop_order.issuer_id,
+                    stop_order.amount,
+                    stop_order.base_currency_id,
+                    stop_order.quote_currency_id,
+                    limit_price,
+                    None
+                );
+                let order = repository.create_buy_order(&request, self.limits.max_open_orders_per_user).await?;
+                self.events.buy_order_changed(&order);
+                match engine.place_buy_order(&order).await {
+                    Ok(report) => self.events.orders_matched(&report),
+                    Err(e) => tracing::warn!("Matching buy order failed: {}", e),
+                }
+                repository.link_stop_order(&stop_order_id, order.buy_order_id, None).await
+            }
+            (Some(limit_price), OrderSide::Sell) => {
+                let request = CreateSellOrderRequest::new(
+                    stop_order.issuer_id,
+                    stop_order.amount,
+                    stop_order.base_currency_id,
+                    stop_order.quote_currency_id,
+                    limit_price,
+                    None
+                );
+                let order = repository.create_sell_order(&request, self.limits.max_open_orders_per_user).await?;
+                self.events.sell_order_changed(&order);
+                match engine.place_sell_order(&order).await {
+                    Ok(report) => self.events.orders_matched(&report),
+                    Err(e) => tracing::warn!("Matching sell order failed: {}", e),
+                }
+                repository.link_stop_order(&stop_order_id, None, order.sell_order_id).await
+            }
+            (None, side) => {
+                let (buy_currency_id, sell_currency_id) = match side {
+                    OrderSide::Buy => (stop_order.base_currency_id, stop_order.quote_currency_id),
+                    OrderSide::Sell => (stop_order.quote_currency_id, stop_order.base_currency_id),
+                };
+                let request = MarketOrderRequest {
+                    buy_currency_id,
+                    sell_currency_id,
+                    amount: stop_order.amount,
+                    max_slippage_percent: None,
+                    max_spend: None,
+                    self_trade_prevention: None,
+                };
+                let report = match side {
+                    OrderSide::Buy => engine.execute_market_buy(stop_order.issuer_id, &request).await?,
+                    OrderSide::Sell => engine.execute_market_sell(stop_order.issuer_id, &request).await?,
+                };
+                self.events.orders_matched(&report);
+                match report.exchanges.first() {
+                    Some(exchange) => repository.link_stop_order_exchange(&stop_order_id, &exchange.id).await,
+                    None => Err(DataError::CurrencyExchangeError("No resting orders to fill the stop order".to_string())),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::{BuyOrder, CurrencyBalance, OrderSide, PreventedTrade, SellOrder, StopOrder, Trade, UserTrade};
+use currency_exchange_data::datasource::repository::order_group_repository::OrderGroupRepository;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_data::datasource::repository::wallet_repository::WalletRepository;
+use crate::order_events::{OrderEvent, OrderEventBus};
+
+///
+/// Messages pushed to the owner of the orders and balances they describe
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UserDataMessage {
+    BuyOrder { order: BuyOrder },
+    SellOrder { order: SellOrder },
+    StopOrder { order: StopOrder },
+    Fill { fill: UserTrade },
+    ///
+    /// Current amounts of the listed currencies, only those touched by the change
+    Balances { balances: Vec<CurrencyBalance> },
+    ///
+    /// Two of the user's orders crossed and were cancelled or decremented instead of trading
+    SelfTradePrevented { prevented: PreventedTrade },
+}
+
+#[derive(Clone, Debug)]
+pub struct UserDataUpdate {
+    pub user_id: i32,
+    pub message: UserDataMessage,
+}
+
+///
+/// Turns `OrderEvent`s into per-user order, fill and balance messages.
+/// Sessions receive every update and keep only those addressed to their user
+#[derive(Clone)]
+pub struct UserDataFeed {
+    pool: PgPool,
+    sender: broadcast::Sender<UserDataUpdate>,
+}
+
+impl UserDataFeed {
+    pub fn new(pool: PgPool, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { pool, sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UserDataUpdate> {
+        self.sender.subscribe()
+    }
+
+    pub async fn run(self, events: OrderEventBus) {
+        let mut receiver = events.subscribe();
+        loop {
+            let result = match receiver.recv().await {
+                Ok(OrderEvent::BuyOrderUpdated { order }) => {
+                    let (user_id, held_currency_id) = (order.issuer_id, order.sell_currency_id);
+                    self.publish_buy_order(order);
+                    self.publish_held_balance(user_id, held_currency_id).await
+                }
+                Ok(OrderEvent::SellOrderUpdated { order }) => {
+                    let (user_id, held_currency_id) = (order.issuer_id, order.sell_currency_id);
+                    self.publish_sell_order(order);
+                    self.publish_held_balance(user_id, held_currency_id).await
+                }
+                Ok(OrderEvent::StopOrderUpdated { order }) => {
+                    self.publish(order.issuer_id, UserDataMessage::StopOrder { order });
+                    Ok(())
+                }
+                Ok(OrderEvent::BuyOrderExpired { buy_order_id, issuer_id }) => self.publish_expired_buy_order(buy_order_id, issuer_id).await,
+                Ok(OrderEvent::SellOrderExpired { sell_order_id, issuer_id }) => self.publish_expired_sell_order(sell_order_id, issuer_id).await,
+                Ok(OrderEvent::TradeExecuted { trade, buyer_id, seller_id }) => {
+                    self.publish_trade(trade, buyer_id, seller_id).await
+                }
+                Ok(OrderEvent::SelfTradePrevented { prevented }) => self.publish_prevented_trade(prevented).await,
+                Ok(_) => Ok(()),
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("User data feed missed {} order events", missed);
+                    Ok(())
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if let Err(e) = result {
+                tracing::warn!("User data update failed: {}", e);
+            }
+        }
+    }
+
+    ///
+    /// Every balance the user holds, sent when a session starts or has to resync
+    pub async fn balances(&self, user_id: i32) -> Result<UserDataMessage, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let balances = repository.find_user_balances(&user_id).await?;
+        Ok(UserDataMessage::Balances { balances })
+    }
+
+    async fn publish_trade(&self, trade: Trade, buyer_id: Option<i32>, seller_id: Option<i32>) -> Result<(), DataError> {
+        if let Some(buyer_id) = buyer_id {
+            self.publish(buyer_id, UserDataMessage::Fill { fill: trade.for_side(OrderSide::Buy) });
+        }
+        if let Some(seller_id) = seller_id {
+            self.publish(seller_id, UserDataMessage::Fill { fill: trade.for_side(OrderSide::Sell) });
+        }
+        if let Some(buy_order_id) = trade.buy_order_id {
+            self.reload_buy_order(buy_order_id).await?;
+        }
+        if let Some(sell_order_id) = trade.sell_order_id {
+            self.reload_sell_order(sell_order_id).await?;
+        }
+        let currencies: HashSet<i32> = [trade.base_currency_id, trade.quote_currency_id].into_iter().flatten().collect();
+        let users: HashSet<i32> = [buyer_id, seller_id].into_iter().flatten().collect();
+        for user_id in users {
+            self.publish_balances(user_id, &currencies).await?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Expiry released what the order still held, so its held currency is published with it
+    async fn publish_expired_buy_order(&self, buy_order_id: i32, issuer_id: i32) -> Result<(), DataError> {
+        let held_currency_id = self.reload_buy_order(buy_order_id).await?;
+        self.publish_held_balance(Some(issuer_id), held_currency_id).await
+    }
+
+    async fn publish_expired_sell_order(&self, sell_order_id: i32, issuer_id: i32) -> Result<(), DataError> {
+        let held_currency_id = self.reload_sell_order(sell_order_id).await?;
+        self.publish_held_balance(Some(issuer_id), held_currency_id).await
+    }
+
+    ///
+    /// Placing, amending, cancelling or expiring an order moves funds in and out of `held`
+    /// for the currency the order sells
+    async fn publish_held_balance(&self, user_id: Option<i32>, held_currency_id: Option<i32>) -> Result<(), DataError> {
+        if let (Some(user_id), Some(currency_id)) = (user_id, held_currency_id) {
+            self.publish_balances(user_id, &HashSet::from([currency_id])).await?;
+        }
+        Ok(())
+    }
+
+    async fn publish_balances(&self, user_id: i32, currencies: &HashSet<i32>) -> Result<(), DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let balances = repository.find_user_balances(&user_id)
+            .await?
+            .into_iter()
+            .filter(|balance| balance.currency_id.is_some_and(|id| currencies.contains(&id)))
+            .collect();
+        self.publish(user_id, UserDataMessage::Balances { balances });
+        Ok(())
+    }
+
+    async fn publish_prevented_trade(&self, prevented: PreventedTrade) -> Result<(), DataError> {
+        let (buy_order_id, sell_order_id) = (prevented.buy_order_id, prevented.sell_order_id);
+        self.publish(prevented.user_id, UserDataMessage::SelfTradePrevented { prevented });
+        if let Some(buy_order_id) = buy_order_id {
+            self.reload_buy_order(buy_order_id).await?;
+        }
+        if let Some(sell_order_id) = sell_order_id {
+            self.reload_sell_order(sell_order_id).await?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Publishes the stored order, returning the currency it holds funds in
+    async fn reload_buy_order(&self, buy_order_id: i32) -> Result<Option<i32>, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let Some(order) = repository.find_buy_order(&buy_order_id).await? else {
+            return Ok(None);
+        };
+        let (order_group_id, held_currency_id) = (order.order_group_id, order.sell_currency_id);
+        self.publish_buy_order(order);
+        self.publish_group_stop_orders(order_group_id).await?;
+        Ok(held_currency_id)
+    }
+
+    async fn reload_sell_order(&self, sell_order_id: i32) -> Result<Option<i32>, DataError> {
+        let repository = Repository::new(self.pool.clone());
+        let Some(order) = repository.find_sell_order(&sell_order_id).await? else {
+            return Ok(None);
+        };
+        let (order_group_id, held_currency_id) = (order.order_group_id, order.sell_currency_id);
+        self.publish_sell_order(order);
+        self.publish_group_stop_orders(order_group_id).await?;
+        Ok(held_currency_id)
+    }
+
+    ///
+    /// Stop legs of the group a limit order belongs to, the first fill of the limit order cancels them
+    async fn publish_group_stop_orders(&self, order_group_id: Option<i32>) -> Result<(), DataError> {
+        let Some(order_group_id) = order_group_id else {
+            return Ok(());
+        };
+        let repository = Repository::new(self.pool.clone());
+        if let Some(group) = repository.find_order_group(&order_group_id).await? {
+            for order in group.stop_orders {
+                self.publish(order.issuer_id, UserDataMessage::StopOrder { order });
+            }
+        }
+        Ok(())
+    }
+
+    fn publish_buy_order(&self, order: BuyOrder) {
+        if let Some(user_id) = order.issuer_id {
+            self.publish(user_id, UserDataMessage::BuyOrder { order });
+        }
+    }
+
+    fn publish_sell_order(&self, order: SellOrder) {
+        if let Some(user_id) = order.issuer_id {
+            self.publish(user_id, UserDataMessage::SellOrder { order });
+        }
+    }
+
+    fn publish(&self, user_id: i32, message: UserDataMessage) {
+        let _ = self.sender.send(UserDataUpdate { user_id, message });
+    }
+}
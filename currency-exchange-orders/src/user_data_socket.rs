@@ -0,0 +1,102 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::web::{Data, ReqData};
+use actix_ws::{Closed, Message, MessageStream, Session};
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use currency_exchange_middleware::jwt::Claims;
+use crate::user_data_feed::{UserDataFeed, UserDataUpdate};
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SessionMessage {
+    ///
+    /// Updates were dropped for this session, balances follow and orders should be re-read over REST
+    Lagged {
+        missed: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+///
+/// Upgrades the request to a WebSocket streaming the caller's order, fill and balance changes.
+/// The stream starts with every balance the caller holds
+pub async fn user_data_socket(
+    req: HttpRequest,
+    claims: ReqData<Claims>,
+    body: web::Payload,
+    feed: Data<UserDataFeed>
+) -> Result<HttpResponse, actix_web::Error> {
+    let headers = req.headers();
+    if headers.get("Authorization").is_some() {
+        let uid = claims.sub.parse::<i32>().expect("Unable to parse user id");
+        let (response, session, stream) = actix_ws::handle(&req, body)?;
+        let connection = UserDataConnection {
+            user_id: uid,
+            feed: feed.get_ref().clone(),
+            session,
+        };
+        actix_web::rt::spawn(connection.run(stream));
+        Ok(response)
+    } else {
+        Ok(HttpResponse::Unauthorized().body("Authorization unauthorized"))
+    }
+}
+
+struct UserDataConnection {
+    user_id: i32,
+    feed: UserDataFeed,
+    session: Session,
+}
+
+impl UserDataConnection {
+    async fn run(mut self, mut stream: MessageStream) {
+        let mut updates = self.feed.subscribe();
+        let result: Result<(), Closed> = async {
+            self.send_balances().await?;
+            loop {
+                tokio::select! {
+                    message = stream.recv() => match message {
+                        Some(Ok(Message::Text(_))) => {
+                            self.send(&SessionMessage::Error { message: "The user stream does not accept commands".to_string() }).await?
+                        }
+                        Some(Ok(Message::Ping(bytes))) => self.session.pong(&bytes).await?,
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return Ok(()),
+                        Some(Ok(_)) => {}
+                    },
+                    update = updates.recv() => match update {
+                        Ok(update) => self.forward(update).await?,
+                        Err(RecvError::Lagged(missed)) => {
+                            self.send(&SessionMessage::Lagged { missed }).await?;
+                            self.send_balances().await?
+                        }
+                        Err(RecvError::Closed) => return Ok(()),
+                    }
+                }
+            }
+        }.await;
+        if result.is_ok() {
+            let _ = self.session.close(None).await;
+        }
+    }
+
+    async fn forward(&mut self, update: UserDataUpdate) -> Result<(), Closed> {
+        if update.user_id != self.user_id {
+            return Ok(());
+        }
+        self.send(&update.message).await
+    }
+
+    async fn send_balances(&mut self) -> Result<(), Closed> {
+        match self.feed.balances(self.user_id).await {
+            Ok(balances) => self.send(&balances).await,
+            Err(e) => self.send(&SessionMessage::Error { message: e.to_string() }).await,
+        }
+    }
+
+    async fn send<T: Serialize>(&mut self, message: &T) -> Result<(), Closed> {
+        let text = serde_json::to_string(message).expect("User data messages serialize");
+        self.session.text(text).await
+    }
+}
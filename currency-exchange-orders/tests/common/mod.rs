@@ -0,0 +1,171 @@
+#![allow(dead_code)]
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use currency_exchange_data::datasource::api_models::{CreateBuyOrderRequest, CreateSellOrderRequest};
+use currency_exchange_data::datasource::models::{BuyOrder, CurrencyExchangeRecord, SellOrder};
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_middleware::database_connector::DatabaseConnector;
+use currency_exchange_middleware::env_parser::{JwtEnvParser, MiddlewareEnv};
+use currency_exchange_middleware::jwt::get_token;
+use currency_exchange_orders::matching_engine::{MatchingEngine, OrderMatcher};
+
+pub const STARTING_BALANCE: i32 = 1000;
+pub const EXPIRY_DAYS: Option<i32> = Some(1);
+pub const MAX_OPEN_ORDERS: i64 = 200;
+pub const MAX_ORDER_BATCH_SIZE: usize = 10;
+
+///
+/// A currency pair of its own with funded traders, so tests running side by side never meet.
+/// Everything it created is removed again by `close`
+pub struct Market {
+    pub pool: PgPool,
+    pub base_currency_id: i32,
+    pub quote_currency_id: i32,
+    pub base_code: String,
+    pub quote_code: String,
+    traders: Vec<i32>,
+}
+
+impl Market {
+    pub async fn open() -> Self {
+        let parser = MiddlewareEnv::new();
+        let pool = DatabaseConnector::new(parser.database_url(), parser.max_connections())
+            .connect()
+            .await;
+        let base_code = unique_code();
+        let quote_code = unique_code();
+        let base_currency_id = create_currency(&pool, &base_code).await;
+        let quote_currency_id = create_currency(&pool, &quote_code).await;
+        Self { pool, base_currency_id, quote_currency_id, base_code, quote_code, traders: vec![] }
+    }
+
+    ///
+    /// New user holding STARTING_BALANCE of both currencies, one wallet per currency
+    pub async fn trader(&mut self) -> i32 {
+        let name = Uuid::new_v4().simple().to_string();
+        let user_id: i32 = sqlx::query_scalar(
+            "INSERT INTO users(username, email, password, firstname, lastname)
+            VALUES ($1, $2, 'password', 'Test', 'Trader') RETURNING user_id")
+            .bind(&name)
+            .bind(format!("{}@example.com", name))
+            .fetch_one(&self.pool)
+            .await
+            .unwrap();
+        for currency_id in [self.base_currency_id, self.quote_currency_id] {
+            let wallet_id: i32 = sqlx::query_scalar("INSERT INTO wallets(user_id, currency_id) VALUES ($1, $2) RETURNING wallet_id")
+                .bind(user_id)
+                .bind(currency_id)
+                .fetch_one(&self.pool)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO currency_amount(amount, currency_id, wallet_id) VALUES ($1, $2, $3)")
+                .bind(STARTING_BALANCE)
+                .bind(currency_id)
+                .bind(wallet_id)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+        }
+        self.traders.push(user_id);
+        user_id
+    }
+
+    ///
+    /// Authorization header value for the given trader
+    pub fn token(&self, user_id: i32) -> String {
+        format!("Bearer {}", get_token(&user_id, &MiddlewareEnv::new()).unwrap())
+    }
+
+    pub async fn balance(&self, user_id: i32, currency_id: i32) -> i32 {
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(ca.amount), 0)::INTEGER
+            FROM currency_amount ca JOIN wallets w ON w.wallet_id = ca.wallet_id
+            WHERE w.user_id = $1 AND ca.currency_id = $2")
+            .bind(user_id)
+            .bind(currency_id)
+            .fetch_one(&self.pool)
+            .await
+            .unwrap()
+    }
+
+    pub async fn held(&self, user_id: i32, currency_id: i32) -> i32 {
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(ca.held), 0)::INTEGER
+            FROM currency_amount ca JOIN wallets w ON w.wallet_id = ca.wallet_id
+            WHERE w.user_id = $1 AND ca.currency_id = $2")
+            .bind(user_id)
+            .bind(currency_id)
+            .fetch_one(&self.pool)
+            .await
+            .unwrap()
+    }
+
+    pub async fn buy_order(&self, buy_order_id: i32) -> BuyOrder {
+        Repository::new(self.pool.clone()).find_buy_order(&buy_order_id).await.unwrap().unwrap()
+    }
+
+    pub async fn sell_order(&self, sell_order_id: i32) -> SellOrder {
+        Repository::new(self.pool.clone()).find_sell_order(&sell_order_id).await.unwrap().unwrap()
+    }
+
+    ///
+    /// Places a buy of the base currency and matches it like the create endpoint does
+    pub async fn buy(&self, user_id: i32, amount: i32, limit_price: f64) -> (i32, Vec<CurrencyExchangeRecord>) {
+        let request = CreateBuyOrderRequest::new(user_id, amount, self.base_currency_id, self.quote_currency_id, limit_price, EXPIRY_DAYS);
+        let order = Repository::new(self.pool.clone()).create_buy_order(&request, MAX_OPEN_ORDERS).await.unwrap();
+        let exchanges = MatchingEngine::new(self.pool.clone()).match_buy_order(&order).await.unwrap().exchanges;
+        (order.buy_order_id.unwrap(), exchanges)
+    }
+
+    ///
+    /// Places a sell of the base currency and matches it like the create endpoint does
+    pub async fn sell(&self, user_id: i32, amount: i32, limit_price: f64) -> (i32, Vec<CurrencyExchangeRecord>) {
+        let request = CreateSellOrderRequest::new(user_id, amount, self.base_currency_id, self.quote_currency_id, limit_price, EXPIRY_DAYS);
+        let order = Repository::new(self.pool.clone()).create_sell_order(&request, MAX_OPEN_ORDERS).await.unwrap();
+        let exchanges = MatchingEngine::new(self.pool.clone()).match_sell_order(&order).await.unwrap().exchanges;
+        (order.sell_order_id.unwrap(), exchanges)
+    }
+
+    pub async fn close(self) {
+        let currencies = [self.base_currency_id, self.quote_currency_id];
+        for statement in [
+            "DELETE FROM stop_orders WHERE issuer_id = ANY($1)",
+            "DELETE FROM buy_orders WHERE issuer_id = ANY($1)",
+            "DELETE FROM sell_orders WHERE issuer_id = ANY($1)",
+            "DELETE FROM order_groups WHERE issuer_id = ANY($1)",
+            "DELETE FROM trading_halts WHERE halted_by = ANY($1)",
+            "DELETE FROM prevented_trades WHERE user_id = ANY($1)",
+            "DELETE FROM trading_settings WHERE user_id = ANY($1)",
+            "DELETE FROM wallets WHERE user_id = ANY($1)",
+            "DELETE FROM users WHERE user_id = ANY($1)",
+        ] {
+            sqlx::query(statement).bind(&self.traders).execute(&self.pool).await.unwrap();
+        }
+        sqlx::query("DELETE FROM candles WHERE base_currency_id = ANY($1)")
+            .bind(&currencies[..])
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM currencies WHERE currency_id = ANY($1)")
+            .bind(&currencies[..])
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+}
+
+///
+/// Currency codes hold at most 10 characters
+fn unique_code() -> String {
+    Uuid::new_v4().simple().to_string()[..10].to_uppercase()
+}
+
+async fn create_currency(pool: &PgPool, currency_code: &str) -> i32 {
+    sqlx::query_scalar("INSERT INTO currencies(currency_code) VALUES ($1) RETURNING currency_id")
+        .bind(currency_code)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
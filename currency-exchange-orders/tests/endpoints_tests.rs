@@ -0,0 +1,386 @@
+mod common;
+
+use actix_web::{test, web, App};
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use currency_exchange_data::datasource::api_models::{AmendOrderRequest, BuyCurrencyRequest, CreateBuyOrderRequest, SellCurrencyRequest};
+use currency_exchange_data::datasource::models::{BuyOrder, CurrencyExchangeRecord, Fill, OrderSide, OrderStatus, SellOrder};
+use currency_exchange_middleware::middleware::JwtMiddleware;
+use currency_exchange_orders::delete_handlers::cancel_sell_order;
+use currency_exchange_orders::get_handlers::{buy_order_fills, sell_order_fills};
+use currency_exchange_orders::models::OrderLimits;
+use currency_exchange_orders::order_endpoints::{BUY_ORDER_BY_ID, GET_BUY_ORDER_FILLS, GET_SELL_ORDER_FILLS, POST_NEW_BUY_ORDER, PUT_BUY_CURRENCY, PUT_SELL_CURRENCY, SELL_ORDER_BY_ID};
+use currency_exchange_orders::order_events::OrderEventBus;
+use currency_exchange_orders::patch_handlers::{amend_buy_order, amend_sell_order};
+use currency_exchange_orders::post_handlers::create_buy_order;
+use currency_exchange_orders::put_handlers::{buy_currency, sell_currency};
+use currency_exchange_orders::user_data_feed::{UserDataFeed, UserDataMessage};
+use common::{Market, EXPIRY_DAYS, MAX_OPEN_ORDERS, MAX_ORDER_BATCH_SIZE, STARTING_BALANCE};
+
+const EVENTS_CAPACITY: usize = 64;
+
+#[actix_web::test]
+async fn buy_currency_should_fill_order_by_id_at_its_limit_price() {
+    let mut market = Market::open().await;
+    let (seller, taker) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 1.25).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(PUT_BUY_CURRENCY)
+                    .wrap(JwtMiddleware)
+                    .route(web::put().to(buy_currency))
+            )
+    ).await;
+
+    let req = test::TestRequest::put()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(PUT_BUY_CURRENCY)
+        .set_json(BuyCurrencyRequest::new(sell_order_id, 4))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let exchange: CurrencyExchangeRecord = test::read_body_json(res).await;
+
+    assert_eq!((exchange.amount, exchange.exchange_rate, exchange.income), (4, 1.25, Some(5.0)));
+    assert_eq!((exchange.buyer_id, exchange.seller_id), (Some(taker), Some(seller)));
+    assert_eq!((exchange.sell_order_id, exchange.buy_order_id), (Some(sell_order_id), None));
+    let sell_order = market.sell_order(sell_order_id).await;
+    assert_eq!((sell_order.filled_amount, sell_order.remaining_amount), (Some(4), Some(6)));
+    assert_eq!(sell_order.status, Some(OrderStatus::PartiallyFilled));
+    assert_eq!(market.balance(taker, market.base_currency_id).await, STARTING_BALANCE + 4);
+    assert_eq!(market.balance(taker, market.quote_currency_id).await, STARTING_BALANCE - 5);
+    assert_eq!(market.balance(seller, market.base_currency_id).await, STARTING_BALANCE - 4);
+    assert_eq!(market.balance(seller, market.quote_currency_id).await, STARTING_BALANCE + 5);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn sell_currency_should_fill_at_most_what_the_order_has_left() {
+    let mut market = Market::open().await;
+    let (buyer, taker) = (market.trader().await, market.trader().await);
+    let (buy_order_id, _) = market.buy(buyer, 3, 2.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(PUT_SELL_CURRENCY)
+                    .wrap(JwtMiddleware)
+                    .route(web::put().to(sell_currency))
+            )
+    ).await;
+
+    let req = test::TestRequest::put()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(PUT_SELL_CURRENCY)
+        .set_json(SellCurrencyRequest::new(buy_order_id, 5))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let exchange: CurrencyExchangeRecord = test::read_body_json(res).await;
+
+    assert_eq!((exchange.amount, exchange.exchange_rate, exchange.income), (3, 2.0, Some(6.0)));
+    assert_eq!((exchange.buyer_id, exchange.seller_id), (Some(buyer), Some(taker)));
+    let buy_order = market.buy_order(buy_order_id).await;
+    assert_eq!((buy_order.filled_amount, buy_order.remaining_amount), (Some(3), Some(0)));
+    assert_eq!(buy_order.status, Some(OrderStatus::Filled));
+    assert_eq!(market.balance(taker, market.base_currency_id).await, STARTING_BALANCE - 3);
+    assert_eq!(market.balance(taker, market.quote_currency_id).await, STARTING_BALANCE + 6);
+
+    let req = test::TestRequest::put()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(PUT_SELL_CURRENCY)
+        .set_json(SellCurrencyRequest::new(buy_order_id, 1))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn buy_order_fills_should_list_every_partial_fill_in_order_to_its_issuer_only() {
+    let mut market = Market::open().await;
+    let (buyer, first_seller, second_seller) = (market.trader().await, market.trader().await, market.trader().await);
+    let (buy_order_id, _) = market.buy(buyer, 10, 1.2).await;
+    let (first_sell_order_id, _) = market.sell(first_seller, 3, 1.1).await;
+    let (second_sell_order_id, _) = market.sell(second_seller, 4, 1.2).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_BUY_ORDER_FILLS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(buy_order_fills))
+            )
+    ).await;
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(&GET_BUY_ORDER_FILLS.replace("{id}", &buy_order_id.to_string()))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let fills: Vec<Fill> = test::read_body_json(res).await;
+
+    let listed: Vec<(Option<i32>, i32, f64)> = fills
+        .iter()
+        .map(|fill| (fill.sell_order_id, fill.amount, fill.exchange_rate))
+        .collect();
+    assert_eq!(listed, vec![(Some(first_sell_order_id), 3, 1.2), (Some(second_sell_order_id), 4, 1.2)]);
+    let buy_order = market.buy_order(buy_order_id).await;
+    assert_eq!((buy_order.filled_amount, buy_order.remaining_amount), (Some(7), Some(3)));
+    assert_eq!(buy_order.status, Some(OrderStatus::PartiallyFilled));
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(first_seller)))
+        .uri(&GET_BUY_ORDER_FILLS.replace("{id}", &buy_order_id.to_string()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn sell_order_fills_should_be_empty_until_filled_and_not_found_for_unknown_orders() {
+    let mut market = Market::open().await;
+    let seller = market.trader().await;
+    let (sell_order_id, _) = market.sell(seller, 5, 2.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_SELL_ORDER_FILLS)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(sell_order_fills))
+            )
+    ).await;
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(seller)))
+        .uri(&GET_SELL_ORDER_FILLS.replace("{id}", &sell_order_id.to_string()))
+        .to_request();
+    let fills: Vec<Fill> = test::call_and_read_body_json(&app, req).await;
+    assert!(fills.is_empty());
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(seller)))
+        .uri(&GET_SELL_ORDER_FILLS.replace("{id}", "-1"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn cancel_sell_order_should_cancel_live_orders_of_their_owner_only() {
+    let mut market = Market::open().await;
+    let (seller, stranger, buyer) = (market.trader().await, market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 1.5).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(SELL_ORDER_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::delete().to(cancel_sell_order))
+            )
+    ).await;
+    let uri = SELL_ORDER_BY_ID.replace("{id}", &sell_order_id.to_string());
+
+    let req = test::TestRequest::delete()
+        .insert_header(("Authorization", market.token(stranger)))
+        .uri(&uri)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(market.sell_order(sell_order_id).await.status, Some(OrderStatus::Open));
+
+    let req = test::TestRequest::delete()
+        .insert_header(("Authorization", market.token(seller)))
+        .uri(&uri)
+        .to_request();
+    let cancelled: SellOrder = test::call_and_read_body_json(&app, req).await;
+    assert_eq!((cancelled.status, cancelled.remaining_amount), (Some(OrderStatus::Cancelled), Some(10)));
+    let (_, exchanges) = market.buy(buyer, 10, 1.5).await;
+    assert!(exchanges.is_empty());
+
+    let req = test::TestRequest::delete()
+        .insert_header(("Authorization", market.token(seller)))
+        .uri(&uri)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+    let req = test::TestRequest::delete()
+        .insert_header(("Authorization", market.token(seller)))
+        .uri(&SELL_ORDER_BY_ID.replace("{id}", "-1"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn amend_buy_order_should_fill_once_its_price_crosses_the_spread() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 1.5).await;
+    let (buy_order_id, exchanges) = market.buy(buyer, 10, 1.4).await;
+    assert!(exchanges.is_empty());
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(BUY_ORDER_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::patch().to(amend_buy_order))
+            )
+    ).await;
+
+    let req = test::TestRequest::patch()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(&BUY_ORDER_BY_ID.replace("{id}", &buy_order_id.to_string()))
+        .set_json(AmendOrderRequest::new(None, Some(1.5), None))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let amended: BuyOrder = test::read_body_json(res).await;
+
+    assert_eq!((amended.status, amended.filled_amount), (Some(OrderStatus::Filled), Some(10)));
+    assert_eq!(market.sell_order(sell_order_id).await.status, Some(OrderStatus::Filled));
+    assert_eq!(market.balance(buyer, market.base_currency_id).await, STARTING_BALANCE + 10);
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 15);
+    assert_eq!(market.balance(seller, market.base_currency_id).await, STARTING_BALANCE - 10);
+    assert_eq!(market.balance(seller, market.quote_currency_id).await, STARTING_BALANCE + 15);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn amend_sell_order_should_lose_priority_only_when_growing() {
+    let mut market = Market::open().await;
+    let (first_seller, second_seller, buyer) = (market.trader().await, market.trader().await, market.trader().await);
+    let (first, _) = market.sell(first_seller, 10, 1.5).await;
+    let (second, _) = market.sell(second_seller, 10, 1.5).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(SELL_ORDER_BY_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::patch().to(amend_sell_order))
+            )
+    ).await;
+
+    for (seller, sell_order_id, amount) in [(first_seller, first, 12), (second_seller, second, 6)] {
+        let req = test::TestRequest::patch()
+            .insert_header(("Authorization", market.token(seller)))
+            .uri(&SELL_ORDER_BY_ID.replace("{id}", &sell_order_id.to_string()))
+            .set_json(AmendOrderRequest::new(Some(amount), None, None))
+            .to_request();
+        let amended: SellOrder = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(amended.remaining_amount, Some(amount));
+    }
+    let (_, exchanges) = market.buy(buyer, 7, 1.5).await;
+
+    let fills: Vec<(Option<i32>, i32)> = exchanges
+        .iter()
+        .map(|exchange| (exchange.sell_order_id, exchange.amount))
+        .collect();
+    assert_eq!(fills, vec![(Some(second), 6), (Some(first), 1)]);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn create_buy_order_should_refuse_orders_beyond_the_open_order_limit() {
+    let mut market = Market::open().await;
+    let (buyer, seller) = (market.trader().await, market.trader().await);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: 3, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+    ).await;
+    let request = CreateBuyOrderRequest::new(buyer, 5, market.base_currency_id, market.quote_currency_id, 1.0, EXPIRY_DAYS);
+    let place = || test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(&request)
+        .to_request();
+
+    let mut statuses = vec![];
+    for _ in 0..4 {
+        statuses.push(test::call_service(&app, place()).await.status());
+    }
+    assert_eq!(statuses, vec![StatusCode::CREATED, StatusCode::CREATED, StatusCode::CREATED, StatusCode::CONFLICT]);
+
+    let (_, exchanges) = market.sell(seller, 5, 1.0).await;
+    assert_eq!(exchanges.len(), 1);
+    assert_eq!(test::call_service(&app, place()).await.status(), StatusCode::CREATED);
+    assert_eq!(test::call_service(&app, place()).await.status(), StatusCode::CONFLICT);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn user_data_feed_should_push_fills_orders_and_balances_to_the_maker() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 5, 1.5).await;
+    let events = OrderEventBus::new(EVENTS_CAPACITY);
+    let feed = UserDataFeed::new(market.pool.clone(), EVENTS_CAPACITY);
+    let mut receiver = feed.subscribe();
+    actix_web::rt::spawn(feed.run(events.clone()));
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(events))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+    ).await;
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(CreateBuyOrderRequest::new(buyer, 2, market.base_currency_id, market.quote_currency_id, 1.5, EXPIRY_DAYS))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let (mut fill, mut order, mut quote_balance) = (None, None, None);
+    while fill.is_none() || order.is_none() || quote_balance.is_none() {
+        let update = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+        if update.user_id != seller {
+            continue;
+        }
+        match update.message {
+            UserDataMessage::Fill { fill: trade } => fill = Some((trade.side, trade.order_id, trade.is_maker, trade.amount)),
+            UserDataMessage::SellOrder { order: sell_order } => order = Some((sell_order.status, sell_order.remaining_amount)),
+            UserDataMessage::Balances { balances } => quote_balance = balances
+                .iter()
+                .find(|balance| balance.currency_id == Some(market.quote_currency_id))
+                .and_then(|balance| balance.amount),
+            UserDataMessage::BuyOrder { .. } | UserDataMessage::StopOrder { .. } | UserDataMessage::SelfTradePrevented { .. } => {
+                panic!("Order of someone else pushed to the seller")
+            }
+        }
+    }
+    assert_eq!(fill, Some((OrderSide::Sell, Some(sell_order_id), Some(true), 2)));
+    assert_eq!(order, Some((Some(OrderStatus::PartiallyFilled), Some(3))));
+    assert_eq!(quote_balance, Some(STARTING_BALANCE + 3));
+
+    market.close().await;
+}
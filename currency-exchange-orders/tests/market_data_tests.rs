@@ -0,0 +1,339 @@
+mod common;
+
+use std::collections::BTreeMap;
+use actix_web::{test, web, App};
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use time::{Duration, OffsetDateTime};
+use time::format_description::well_known::Rfc3339;
+use currency_exchange_data::datasource::api_models::{BackfillCandlesRequest, BackfillCandlesResponse, CreateBuyOrderRequest};
+use currency_exchange_data::datasource::models::{Candle, OrderBook, OrderSide, PriceLevel, Ticker, Trade, TradePage, UserTrade};
+use currency_exchange_middleware::middleware::JwtMiddleware;
+use currency_exchange_orders::get_handlers::{candles, my_trades, order_book, ticker, trades};
+use currency_exchange_orders::order_endpoints::{GET_CANDLES, GET_MY_TRADES, GET_ORDER_BOOK, GET_TICKER, GET_TRADES, POST_BACKFILL_CANDLES, POST_NEW_BUY_ORDER};
+use currency_exchange_orders::market_data_feed::{CurrencyPair, MarketDataFeed, MarketDataMessage};
+use currency_exchange_orders::models::{OrderLimits, TradingAdmins};
+use currency_exchange_orders::order_events::OrderEventBus;
+use currency_exchange_orders::post_handlers::{backfill_candles, create_buy_order};
+use common::{Market, EXPIRY_DAYS, MAX_OPEN_ORDERS, MAX_ORDER_BATCH_SIZE};
+
+const EVENTS_CAPACITY: usize = 64;
+
+///
+/// Trades 2 at 2.0, 1 at 3.0 and 3 at 1.0 in that order
+async fn trade_three_times(market: &mut Market) {
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    for (amount, price) in [(2, 2.0), (1, 3.0), (3, 1.0)] {
+        market.sell(seller, amount, price).await;
+        market.buy(buyer, amount, price).await;
+    }
+}
+
+///
+/// Open, high, low, close, volume, quote volume and trade count over consecutive candles
+fn summary(candles: &[Candle]) -> (f64, f64, f64, f64, i64, f64, i32) {
+    (
+        candles.first().unwrap().open,
+        candles.iter().map(|candle| candle.high).fold(f64::MIN, f64::max),
+        candles.iter().map(|candle| candle.low).fold(f64::MAX, f64::min),
+        candles.last().unwrap().close,
+        candles.iter().map(|candle| candle.volume).sum(),
+        candles.iter().map(|candle| candle.quote_volume).sum(),
+        candles.iter().map(|candle| candle.trade_count).sum(),
+    )
+}
+
+///
+/// Size per price after applying book diffs, levels of size 0 are removed
+fn apply(mut book: BTreeMap<String, i64>, changed: &[PriceLevel]) -> BTreeMap<String, i64> {
+    for level in changed {
+        if level.size == 0 {
+            book.remove(&level.price.to_string());
+        } else {
+            book.insert(level.price.to_string(), level.size);
+        }
+    }
+    book
+}
+
+fn levels(levels: &[PriceLevel]) -> Vec<(f64, i64, i64)> {
+    levels.iter().map(|level| (level.price, level.size, level.order_count)).collect()
+}
+
+#[actix_web::test]
+async fn order_book_should_aggregate_live_orders_by_price_best_first() {
+    let mut market = Market::open().await;
+    let trader = market.trader().await;
+    market.sell(trader, 5, 1.6).await;
+    market.sell(trader, 5, 1.5).await;
+    market.sell(trader, 3, 1.5).await;
+    market.buy(trader, 7, 1.2).await;
+    market.buy(trader, 2, 1.3).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_ORDER_BOOK)
+                    .route(web::get().to(order_book))
+            )
+    ).await;
+    let uri = GET_ORDER_BOOK.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code);
+
+    let req = test::TestRequest::get().uri(&uri).to_request();
+    let book: OrderBook = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(levels(&book.asks), vec![(1.5, 8, 2), (1.6, 5, 1)]);
+    assert_eq!(levels(&book.bids), vec![(1.3, 2, 1), (1.2, 7, 1)]);
+
+    let req = test::TestRequest::get().uri(&format!("{}?depth=1", uri)).to_request();
+    let book: OrderBook = test::call_and_read_body_json(&app, req).await;
+    assert_eq!((book.asks.len(), book.bids.len()), (1, 1));
+
+    let req = test::TestRequest::get()
+        .uri(&GET_ORDER_BOOK.replace("{base}", &market.base_code).replace("{quote}", "NOPE"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn trades_should_page_the_tape_newest_first() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 2.0).await;
+    let mut buy_order_ids = vec![];
+    for amount in [2, 3, 1] {
+        buy_order_ids.push(market.buy(buyer, amount, 2.0).await.0);
+    }
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_TRADES)
+                    .route(web::get().to(trades))
+            )
+    ).await;
+    let uri = GET_TRADES.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code);
+
+    let req = test::TestRequest::get().uri(&format!("{}?limit=2", uri)).to_request();
+    let first: TradePage<Trade> = test::call_and_read_body_json(&app, req).await;
+    let cursor = first.next_cursor.unwrap();
+    let req = test::TestRequest::get().uri(&format!("{}?limit=2&cursor={}", uri, cursor)).to_request();
+    let second: TradePage<Trade> = test::call_and_read_body_json(&app, req).await;
+
+    let amounts: Vec<i32> = first.trades.iter().chain(&second.trades).map(|trade| trade.amount).collect();
+    assert_eq!(amounts, vec![1, 3, 2]);
+    assert_eq!(second.next_cursor, None);
+    let newest = &first.trades[0];
+    assert_eq!((newest.price, newest.quote_amount, newest.taker_side), (2.0, Some(2.0), Some(OrderSide::Buy)));
+    assert_eq!((newest.maker_order_id, newest.taker_order_id), (Some(sell_order_id), Some(buy_order_ids[2])));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn my_trades_should_show_each_trade_from_the_callers_side() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 2.0).await;
+    let (buy_order_id, _) = market.buy(buyer, 4, 2.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_MY_TRADES)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_trades))
+            )
+    ).await;
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(seller)))
+        .uri(GET_MY_TRADES)
+        .to_request();
+    let page: TradePage<UserTrade> = test::call_and_read_body_json(&app, req).await;
+    let seen: Vec<_> = page.trades.iter().map(|trade| (trade.side, trade.order_id, trade.is_maker, trade.amount)).collect();
+    assert_eq!(seen, vec![(OrderSide::Sell, Some(sell_order_id), Some(true), 4)]);
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(GET_MY_TRADES)
+        .to_request();
+    let page: TradePage<UserTrade> = test::call_and_read_body_json(&app, req).await;
+    let seen: Vec<_> = page.trades.iter().map(|trade| (trade.side, trade.order_id, trade.is_maker, trade.amount)).collect();
+    assert_eq!(seen, vec![(OrderSide::Buy, Some(buy_order_id), Some(false), 4)]);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn candles_should_be_updated_with_every_trade() {
+    let mut market = Market::open().await;
+    trade_three_times(&mut market).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_CANDLES)
+                    .route(web::get().to(candles))
+            )
+    ).await;
+    let uri = GET_CANDLES.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code);
+
+    for interval in ["1m", "5m", "1h", "1d"] {
+        let req = test::TestRequest::get().uri(&format!("{}?interval={}", uri, interval)).to_request();
+        let candles: Vec<Candle> = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(summary(&candles), (2.0, 3.0, 1.0, 1.0, 6, 10.0, 3), "{} candles", interval);
+    }
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn backfill_candles_should_rebuild_buckets_from_recorded_exchanges() {
+    let mut market = Market::open().await;
+    trade_three_times(&mut market).await;
+    let (admin, trader) = (market.trader().await, market.trader().await);
+    sqlx::query("UPDATE currency_exchange SET created_at = created_at - INTERVAL '3 days' WHERE base_currency_id = $1")
+        .bind(market.base_currency_id)
+        .execute(&market.pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM candles WHERE base_currency_id = $1")
+        .bind(market.base_currency_id)
+        .execute(&market.pool)
+        .await
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(TradingAdmins::new(vec![admin])))
+            .service(
+                web::resource(GET_CANDLES)
+                    .route(web::get().to(candles))
+            )
+            .service(
+                web::resource(POST_BACKFILL_CANDLES)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(backfill_candles))
+            )
+    ).await;
+    let now = OffsetDateTime::now_utc();
+    let backfill = BackfillCandlesRequest { from: now - Duration::days(4), to: now - Duration::days(2) };
+    let backfill_uri = POST_BACKFILL_CANDLES.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code);
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(trader)))
+        .uri(&backfill_uri)
+        .set_json(&backfill)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(admin)))
+        .uri(&backfill_uri)
+        .set_json(&backfill)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let backfilled: BackfillCandlesResponse = test::read_body_json(res).await;
+
+    assert!(backfilled.candles_written >= 4);
+    let from = (now - Duration::days(5)).format(&Rfc3339).unwrap();
+    let uri = GET_CANDLES.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code);
+    let req = test::TestRequest::get().uri(&format!("{}?interval=1m&from={}", uri, from)).to_request();
+    let candles: Vec<Candle> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(summary(&candles), (2.0, 3.0, 1.0, 1.0, 6, 10.0, 3));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn ticker_should_summarise_the_last_day_of_trades_and_the_book() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    market.sell(seller, 2, 0.8).await;
+    market.buy(buyer, 2, 0.8).await;
+    sqlx::query("UPDATE currency_exchange SET created_at = now() - INTERVAL '25 hours' WHERE base_currency_id = $1")
+        .bind(market.base_currency_id)
+        .execute(&market.pool)
+        .await
+        .unwrap();
+    market.sell(seller, 3, 1.0).await;
+    market.sell(seller, 2, 1.5).await;
+    market.sell(seller, 5, 2.0).await;
+    market.buy(buyer, 5, 1.5).await;
+    market.buy(buyer, 4, 0.9).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_TICKER)
+                    .route(web::get().to(ticker))
+            )
+    ).await;
+
+    let req = test::TestRequest::get()
+        .uri(&GET_TICKER.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code))
+        .to_request();
+    let ticker: Ticker = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!((ticker.last_price, ticker.high_24h, ticker.low_24h), (Some(1.5), Some(1.5), Some(1.0)));
+    assert_eq!((ticker.best_bid, ticker.best_ask), (Some(0.9), Some(2.0)));
+    assert_eq!((ticker.volume_24h, ticker.quote_volume_24h, ticker.trade_count_24h), (5, 6.0, 2));
+    assert_eq!(ticker.change_percent_24h, Some(50.0));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn market_data_feed_should_sequence_book_updates_and_trades_after_the_snapshot() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    market.sell(seller, 5, 1.5).await;
+    let pair = CurrencyPair { base_currency_id: market.base_currency_id, quote_currency_id: market.quote_currency_id };
+    let events = OrderEventBus::new(EVENTS_CAPACITY);
+    let feed = MarketDataFeed::new(market.pool.clone(), EVENTS_CAPACITY);
+    let mut receiver = feed.subscribe();
+    actix_web::rt::spawn(feed.clone().run(events.clone()));
+    let MarketDataMessage::BookSnapshot { seq, asks, .. } = feed.snapshot(pair).await.unwrap() else {
+        panic!("Expected a book snapshot");
+    };
+    assert_eq!((seq, levels(&asks)), (0, vec![(1.5, 5, 1)]));
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(events))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+    ).await;
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(CreateBuyOrderRequest::new(buyer, 2, market.base_currency_id, market.quote_currency_id, 1.5, EXPIRY_DAYS))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let (mut book_seq, mut bids, mut asks, mut trade) = (seq, BTreeMap::new(), apply(BTreeMap::new(), &asks), None);
+    while trade.is_none() || !bids.is_empty() || asks.get(&1.5.to_string()) != Some(&3) {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+        match message {
+            MarketDataMessage::BookUpdate { seq, bids: changed_bids, asks: changed_asks, .. } => {
+                assert_eq!(seq, book_seq + 1);
+                book_seq = seq;
+                bids = apply(bids, &changed_bids);
+                asks = apply(asks, &changed_asks);
+            }
+            MarketDataMessage::Trade { seq, trade: executed, .. } => trade = Some((seq, executed.amount, executed.price)),
+            MarketDataMessage::BookSnapshot { .. } => {}
+        }
+    }
+    assert_eq!(trade, Some((1, 2, 1.5)));
+
+    market.close().await;
+}
@@ -0,0 +1,819 @@
+mod common;
+
+use actix_web::{test, web, App};
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use time::{Duration, OffsetDateTime};
+use currency_exchange_data::datasource::api_models::{BatchOrderItem, BatchOrderRequest, CreateBuyOrderRequest, CreateOcoOrderRequest, CreateSellOrderRequest, CreateStopOrderRequest, MarketOrderRequest, TradingHaltRequest, TradingSettingsRequest};
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::{BatchOrderResponse, BuyOrder, ClientOrder, MarketOrderResult, OrderBook, OrderSide, OrderStatus, SelfTradePrevention, StopOrderStatus, TimeInForce, TradingHaltMode};
+use currency_exchange_data::datasource::repository::order_group_repository::OrderGroupRepository;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_data::datasource::repository::stop_order_repository::StopOrderRepository;
+use currency_exchange_data::datasource::repository::trading_halt_repository::TradingHaltRepository;
+use currency_exchange_data::datasource::repository::trading_settings_repository::TradingSettingsRepository;
+use currency_exchange_middleware::middleware::JwtMiddleware;
+use currency_exchange_orders::matching_engine::{MatchingEngine, OrderMatcher};
+use currency_exchange_orders::get_handlers::{my_order_by_client_id, order_book};
+use currency_exchange_orders::models::OrderLimits;
+use currency_exchange_orders::order_endpoints::{GET_MY_ORDER_BY_CLIENT_ID, GET_ORDER_BOOK, POST_MARKET_BUY, POST_MARKET_SELL, POST_NEW_BUY_ORDER, POST_NEW_SELL_ORDER, POST_ORDER_BATCH};
+use currency_exchange_orders::order_events::OrderEventBus;
+use currency_exchange_orders::post_handlers::{create_buy_order, create_sell_order, market_buy, market_sell, place_order_batch};
+use currency_exchange_orders::stop_order_trigger::StopOrderTrigger;
+use common::{Market, EXPIRY_DAYS, MAX_OPEN_ORDERS, MAX_ORDER_BATCH_SIZE, STARTING_BALANCE};
+
+const EVENTS_CAPACITY: usize = 64;
+
+fn market_buy_request(market: &Market, amount: i32, max_slippage_percent: Option<f64>, max_spend: Option<i32>) -> MarketOrderRequest {
+    MarketOrderRequest {
+        buy_currency_id: market.base_currency_id,
+        sell_currency_id: market.quote_currency_id,
+        amount,
+        max_slippage_percent,
+        max_spend,
+        self_trade_prevention: None,
+    }
+}
+
+fn stop_order(market: &Market, side: OrderSide, amount: i32, stop_price: f64, limit_price: Option<f64>) -> CreateStopOrderRequest {
+    CreateStopOrderRequest {
+        side,
+        base_currency_id: market.base_currency_id,
+        quote_currency_id: market.quote_currency_id,
+        amount,
+        stop_price,
+        limit_price,
+    }
+}
+
+fn oco_sell(market: &Market, amount: i32, limit_price: f64, stop_price: f64) -> CreateOcoOrderRequest {
+    CreateOcoOrderRequest {
+        side: OrderSide::Sell,
+        base_currency_id: market.base_currency_id,
+        quote_currency_id: market.quote_currency_id,
+        amount,
+        limit_price,
+        stop_price,
+        stop_limit_price: None,
+    }
+}
+
+fn batch_buy(market: &Market, user_id: i32, amount: i32, limit_price: f64) -> BatchOrderItem {
+    BatchOrderItem::CreateBuy(CreateBuyOrderRequest::new(user_id, amount, market.base_currency_id, market.quote_currency_id, limit_price, EXPIRY_DAYS))
+}
+
+async fn buy_orders_of(market: &Market, user_id: i32) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM buy_orders WHERE issuer_id = $1")
+        .bind(user_id)
+        .fetch_one(&market.pool)
+        .await
+        .unwrap()
+}
+
+///
+/// Outcome of a trader buying 10 at 1.0 into their own resting sell of 4, queued ahead of another seller's 5
+struct SelfTrade {
+    market: Market,
+    trader: i32,
+    own_sell_order_id: i32,
+    other_sell_order_id: i32,
+    buy_order_id: i32,
+    fills: Vec<(Option<i32>, i32)>,
+}
+
+async fn self_trade(self_trade_prevention: SelfTradePrevention) -> SelfTrade {
+    let mut market = Market::open().await;
+    let (trader, seller) = (market.trader().await, market.trader().await);
+    let (own_sell_order_id, _) = market.sell(trader, 4, 1.0).await;
+    let (other_sell_order_id, _) = market.sell(seller, 5, 1.0).await;
+    let request = CreateBuyOrderRequest::new(trader, 10, market.base_currency_id, market.quote_currency_id, 1.0, EXPIRY_DAYS)
+        .with_self_trade_prevention(Some(self_trade_prevention));
+    let buy_order = Repository::new(market.pool.clone()).create_buy_order(&request, MAX_OPEN_ORDERS).await.unwrap();
+    let exchanges = MatchingEngine::new(market.pool.clone()).match_buy_order(&buy_order).await.unwrap().exchanges;
+    let fills = exchanges.iter().map(|exchange| (exchange.sell_order_id, exchange.amount)).collect();
+    SelfTrade { market, trader, own_sell_order_id, other_sell_order_id, buy_order_id: buy_order.buy_order_id.unwrap(), fills }
+}
+
+///
+/// Trades `amount` at `price` between two fresh traders, moving the pair's last price
+async fn trade_at(market: &mut Market, amount: i32, price: f64) {
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    market.sell(seller, amount, price).await;
+    let (_, exchanges) = market.buy(buyer, amount, price).await;
+    assert_eq!(exchanges.len(), 1);
+}
+
+#[actix_web::test]
+async fn market_buy_should_stop_at_the_slippage_bound() {
+    let mut market = Market::open().await;
+    let (seller, taker) = (market.trader().await, market.trader().await);
+    market.sell(seller, 3, 1.0).await;
+    market.sell(seller, 20, 1.05).await;
+    let (far, _) = market.sell(seller, 10, 1.5).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(POST_MARKET_BUY)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(market_buy))
+            )
+    ).await;
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(POST_MARKET_BUY)
+        .set_json(market_buy_request(&market, 30, Some(10.0), None))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let result: MarketOrderResult = test::read_body_json(res).await;
+
+    assert_eq!((result.requested_amount, result.filled_amount, result.quote_amount), (30, 23, 24.0));
+    let prices: Vec<f64> = result.trades.iter().map(|trade| trade.price).collect();
+    assert_eq!(prices, vec![1.0, 1.05]);
+    assert_eq!(market.sell_order(far).await.status, Some(OrderStatus::Open));
+    assert_eq!(market.balance(taker, market.base_currency_id).await, STARTING_BALANCE + 23);
+    assert_eq!(market.balance(taker, market.quote_currency_id).await, STARTING_BALANCE - 24);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn market_buy_should_spend_at_most_its_budget() {
+    let mut market = Market::open().await;
+    let (seller, taker) = (market.trader().await, market.trader().await);
+    market.sell(seller, 3, 1.0).await;
+    market.sell(seller, 10, 2.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(POST_MARKET_BUY)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(market_buy))
+            )
+    ).await;
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(POST_MARKET_BUY)
+        .set_json(market_buy_request(&market, 10, None, Some(5)))
+        .to_request();
+    let result: MarketOrderResult = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!((result.filled_amount, result.quote_amount), (4, 5.0));
+    assert_eq!(market.balance(taker, market.quote_currency_id).await, STARTING_BALANCE - 5);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn market_sell_should_fill_best_bids_first_and_refuse_an_empty_book() {
+    let mut market = Market::open().await;
+    let (buyer, taker) = (market.trader().await, market.trader().await);
+    market.buy(buyer, 5, 1.0).await;
+    market.buy(buyer, 2, 2.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .service(
+                web::resource(POST_MARKET_BUY)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(market_buy))
+            )
+            .service(
+                web::resource(POST_MARKET_SELL)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(market_sell))
+            )
+    ).await;
+    let request = MarketOrderRequest {
+        buy_currency_id: market.quote_currency_id,
+        sell_currency_id: market.base_currency_id,
+        amount: 5,
+        max_slippage_percent: None,
+        max_spend: None,
+        self_trade_prevention: None,
+    };
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(POST_MARKET_SELL)
+        .set_json(&request)
+        .to_request();
+    let result: MarketOrderResult = test::call_and_read_body_json(&app, req).await;
+
+    let fills: Vec<(f64, i32)> = result.trades.iter().map(|trade| (trade.price, trade.amount)).collect();
+    assert_eq!(fills, vec![(2.0, 2), (1.0, 3)]);
+    assert_eq!((result.filled_amount, result.quote_amount), (5, 7.0));
+    assert_eq!(market.balance(taker, market.quote_currency_id).await, STARTING_BALANCE + 7);
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(taker)))
+        .uri(POST_MARKET_BUY)
+        .set_json(market_buy_request(&market, 5, None, None))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn ioc_buy_should_fill_what_it_can_and_cancel_the_rest() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 3, 1.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+    ).await;
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(CreateBuyOrderRequest::new(buyer, 5, market.base_currency_id, market.quote_currency_id, 1.0, None)
+            .with_time_in_force(Some(TimeInForce::Ioc), None))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let order: BuyOrder = test::read_body_json(res).await;
+
+    assert_eq!((order.status, order.filled_amount), (Some(OrderStatus::Cancelled), Some(3)));
+    assert_eq!(market.sell_order(sell_order_id).await.status, Some(OrderStatus::Filled));
+    assert_eq!(market.balance(buyer, market.base_currency_id).await, STARTING_BALANCE + 3);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn fok_buy_should_fill_completely_or_not_at_all() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 3, 1.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+    ).await;
+    let place = |amount: i32| test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(CreateBuyOrderRequest::new(buyer, amount, market.base_currency_id, market.quote_currency_id, 1.0, None)
+            .with_time_in_force(Some(TimeInForce::Fok), None))
+        .to_request();
+
+    let killed: BuyOrder = test::call_and_read_body_json(&app, place(5)).await;
+    assert_eq!((killed.status, killed.filled_amount), (Some(OrderStatus::Cancelled), Some(0)));
+    let sell_order = market.sell_order(sell_order_id).await;
+    assert_eq!((sell_order.status, sell_order.remaining_amount), (Some(OrderStatus::Open), Some(3)));
+    assert_eq!(market.balance(buyer, market.base_currency_id).await, STARTING_BALANCE);
+
+    let filled: BuyOrder = test::call_and_read_body_json(&app, place(3)).await;
+    assert_eq!((filled.status, filled.filled_amount), (Some(OrderStatus::Filled), Some(3)));
+    assert_eq!(market.sell_order(sell_order_id).await.status, Some(OrderStatus::Filled));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn gtd_buy_should_rest_until_its_expiry_and_reject_invalid_expiries() {
+    let mut market = Market::open().await;
+    let buyer = market.trader().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+    ).await;
+    let place = |request: &CreateBuyOrderRequest| test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(request)
+        .to_request();
+    let request = |expiry_days: Option<i32>, time_in_force: Option<TimeInForce>, expires_at: Option<OffsetDateTime>| {
+        CreateBuyOrderRequest::new(buyer, 5, market.base_currency_id, market.quote_currency_id, 1.0, expiry_days)
+            .with_time_in_force(time_in_force, expires_at)
+    };
+
+    let expires_at = (OffsetDateTime::now_utc() + Duration::hours(2)).replace_nanosecond(0).unwrap();
+    let order: BuyOrder = test::call_and_read_body_json(&app, place(&request(None, Some(TimeInForce::Gtd), Some(expires_at)))).await;
+    assert_eq!((order.time_in_force, order.expires_at), (Some(TimeInForce::Gtd), Some(expires_at)));
+    assert_eq!(order.status, Some(OrderStatus::Open));
+    let order: BuyOrder = test::call_and_read_body_json(&app, place(&request(None, None, None))).await;
+    assert_eq!((order.time_in_force, order.expires_at), (Some(TimeInForce::Gtc), None));
+
+    for invalid in [
+        request(None, Some(TimeInForce::Gtd), None),
+        request(None, None, Some(OffsetDateTime::now_utc() - Duration::minutes(1))),
+        request(EXPIRY_DAYS, Some(TimeInForce::Ioc), None),
+    ] {
+        assert_eq!(test::call_service(&app, place(&invalid)).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn stop_limit_buy_should_place_its_limit_order_once_the_last_trade_reaches_the_stop() {
+    let mut market = Market::open().await;
+    let (seller, stopper) = (market.trader().await, market.trader().await);
+    trade_at(&mut market, 1, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    let stop = repository.create_stop_order(&stopper, &stop_order(&market, OrderSide::Buy, 4, 1.2, Some(1.3))).await.unwrap();
+    let (sell_order_id, _) = market.sell(seller, 5, 1.25).await;
+    let trigger = StopOrderTrigger::new(market.pool.clone(), OrderEventBus::new(EVENTS_CAPACITY), OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE });
+
+    trigger.resync().await.unwrap();
+    let pending = repository.find_stop_orders(&stopper, None).await.unwrap();
+    assert_eq!(pending[0].status, StopOrderStatus::Pending);
+
+    trade_at(&mut market, 1, 1.2).await;
+    trigger.resync().await.unwrap();
+    let triggered = repository.find_stop_orders(&stopper, None).await.unwrap();
+    assert_eq!((triggered[0].stop_order_id, triggered[0].status), (stop.stop_order_id, StopOrderStatus::Triggered));
+    assert_eq!(triggered[0].trigger_price, Some(1.2));
+    let buy_order = market.buy_order(triggered[0].buy_order_id.unwrap()).await;
+    assert_eq!((buy_order.status, buy_order.limit_price), (Some(OrderStatus::Filled), Some(1.3)));
+    assert_eq!(market.sell_order(sell_order_id).await.remaining_amount, Some(1));
+    assert_eq!(market.balance(stopper, market.base_currency_id).await, STARTING_BALANCE + 4);
+    assert_eq!(market.balance(stopper, market.quote_currency_id).await, STARTING_BALANCE - 5);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn stop_market_orders_should_sweep_the_book_or_be_rejected_without_liquidity() {
+    let mut market = Market::open().await;
+    let (buyer, stopper) = (market.trader().await, market.trader().await);
+    trade_at(&mut market, 1, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    repository.create_stop_order(&stopper, &stop_order(&market, OrderSide::Sell, 3, 0.8, None)).await.unwrap();
+    trade_at(&mut market, 1, 0.8).await;
+    repository.create_stop_order(&stopper, &stop_order(&market, OrderSide::Buy, 3, 0.9, None)).await.unwrap();
+    let (buy_order_id, _) = market.buy(buyer, 3, 1.0).await;
+
+    // The sell stop trades at 1.0, which the next pass picks up for the buy stop
+    let trigger = StopOrderTrigger::new(market.pool.clone(), OrderEventBus::new(EVENTS_CAPACITY), OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE });
+    trigger.resync().await.unwrap();
+    trigger.resync().await.unwrap();
+    let stops = repository.find_stop_orders(&stopper, None).await.unwrap();
+    let sell_stop = stops.iter().find(|stop| stop.side == OrderSide::Sell).unwrap();
+    let buy_stop = stops.iter().find(|stop| stop.side == OrderSide::Buy).unwrap();
+
+    assert_eq!(sell_stop.status, StopOrderStatus::Triggered);
+    let filled_order: Option<i32> = sqlx::query_scalar("SELECT buy_order_id FROM currency_exchange WHERE id = $1")
+        .bind(sell_stop.exchange_id)
+        .fetch_one(&market.pool)
+        .await
+        .unwrap();
+    assert_eq!(filled_order, Some(buy_order_id));
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Filled));
+    assert_eq!(market.balance(stopper, market.base_currency_id).await, STARTING_BALANCE - 3);
+    assert_eq!(market.balance(stopper, market.quote_currency_id).await, STARTING_BALANCE + 3);
+    assert_eq!(buy_stop.status, StopOrderStatus::Rejected);
+    assert!(buy_stop.reject_reason.is_some());
+    assert_eq!(buy_stop.exchange_id, None);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn pending_stop_orders_should_be_cancelled_by_their_owner_only() {
+    let mut market = Market::open().await;
+    let (stopper, stranger) = (market.trader().await, market.trader().await);
+    let repository = Repository::new(market.pool.clone());
+    let stop = repository.create_stop_order(&stopper, &stop_order(&market, OrderSide::Buy, 3, 1.2, Some(1.3))).await.unwrap();
+
+    assert!(matches!(repository.cancel_stop_order(&stop.stop_order_id, &stranger).await, Err(DataError::OrderOwnershipError(_))));
+    let cancelled = repository.cancel_stop_order(&stop.stop_order_id, &stopper).await.unwrap();
+    assert_eq!(cancelled.status, StopOrderStatus::Cancelled);
+    trade_at(&mut market, 1, 1.2).await;
+    let trigger = StopOrderTrigger::new(market.pool.clone(), OrderEventBus::new(EVENTS_CAPACITY), OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE });
+    trigger.resync().await.unwrap();
+    assert_eq!(repository.find_stop_orders(&stopper, None).await.unwrap()[0].status, StopOrderStatus::Cancelled);
+    assert!(matches!(repository.cancel_stop_order(&stop.stop_order_id, &stopper).await, Err(DataError::OrderStatusError(_))));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn oco_limit_fill_should_cancel_the_stop_leg() {
+    let mut market = Market::open().await;
+    let (trader, buyer) = (market.trader().await, market.trader().await);
+    trade_at(&mut market, 1, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    let group = repository.create_oco_order(&trader, &oco_sell(&market, 3, 1.2, 0.9), MAX_OPEN_ORDERS).await.unwrap();
+    let limit_leg = group.sell_orders[0].sell_order_id.unwrap();
+    assert_eq!(group.stop_orders[0].status, StopOrderStatus::Pending);
+
+    market.buy(buyer, 1, 1.2).await;
+    let group = repository.find_order_group(&group.order_group_id).await.unwrap().unwrap();
+    assert_eq!(group.sell_orders[0].status, Some(OrderStatus::PartiallyFilled));
+    assert_eq!(group.stop_orders[0].status, StopOrderStatus::Cancelled);
+
+    trade_at(&mut market, 1, 0.9).await;
+    let trigger = StopOrderTrigger::new(market.pool.clone(), OrderEventBus::new(EVENTS_CAPACITY), OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE });
+    trigger.resync().await.unwrap();
+    assert_eq!(market.sell_order(limit_leg).await.remaining_amount, Some(2));
+    assert_eq!(repository.find_order_group(&group.order_group_id).await.unwrap().unwrap().stop_orders[0].status, StopOrderStatus::Cancelled);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn oco_stop_trigger_should_cancel_the_limit_leg() {
+    let mut market = Market::open().await;
+    let (trader, buyer) = (market.trader().await, market.trader().await);
+    trade_at(&mut market, 1, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    let group = repository.create_oco_order(&trader, &oco_sell(&market, 3, 1.2, 0.9), MAX_OPEN_ORDERS).await.unwrap();
+    trade_at(&mut market, 1, 0.9).await;
+    let (buy_order_id, _) = market.buy(buyer, 3, 1.0).await;
+
+    let trigger = StopOrderTrigger::new(market.pool.clone(), OrderEventBus::new(EVENTS_CAPACITY), OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE });
+    trigger.resync().await.unwrap();
+    let group = repository.find_order_group(&group.order_group_id).await.unwrap().unwrap();
+    assert_eq!(group.stop_orders[0].status, StopOrderStatus::Triggered);
+    assert_eq!((group.sell_orders[0].status, group.sell_orders[0].filled_amount), (Some(OrderStatus::Cancelled), Some(0)));
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Filled));
+    assert_eq!(market.balance(trader, market.base_currency_id).await, STARTING_BALANCE - 3);
+    assert_eq!(market.balance(trader, market.quote_currency_id).await, STARTING_BALANCE + 3);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn oco_group_should_be_cancelled_as_a_whole_by_its_owner_only() {
+    let mut market = Market::open().await;
+    let (trader, stranger) = (market.trader().await, market.trader().await);
+    trade_at(&mut market, 1, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    let group = repository.create_oco_order(&trader, &oco_sell(&market, 3, 1.2, 0.9), MAX_OPEN_ORDERS).await.unwrap();
+
+    assert!(matches!(repository.cancel_order_group(&group.order_group_id, &stranger).await, Err(DataError::OrderOwnershipError(_))));
+    let cancelled = repository.cancel_order_group(&group.order_group_id, &trader).await.unwrap();
+    assert_eq!(cancelled.sell_orders[0].status, Some(OrderStatus::Cancelled));
+    assert_eq!(cancelled.stop_orders[0].status, StopOrderStatus::Cancelled);
+    assert_eq!(market.balance(trader, market.base_currency_id).await, STARTING_BALANCE);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn iceberg_sell_should_show_one_slice_and_requeue_it_once_filled() {
+    let mut market = Market::open().await;
+    let (iceberg_seller, seller, buyer) = (market.trader().await, market.trader().await, market.trader().await);
+    let request = CreateSellOrderRequest::new(iceberg_seller, 10, market.base_currency_id, market.quote_currency_id, 1.0, EXPIRY_DAYS)
+        .with_display_amount(Some(3));
+    let iceberg = Repository::new(market.pool.clone()).create_sell_order(&request, MAX_OPEN_ORDERS).await.unwrap();
+    let iceberg_id = iceberg.sell_order_id.unwrap();
+    let (sell_order_id, _) = market.sell(seller, 4, 1.0).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .service(
+                web::resource(GET_ORDER_BOOK)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(order_book))
+            )
+    ).await;
+    let uri = GET_ORDER_BOOK.replace("{base}", &market.base_code).replace("{quote}", &market.quote_code);
+    let book = || test::TestRequest::get()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(&uri)
+        .to_request();
+
+    let asks = test::call_and_read_body_json::<_, _, OrderBook>(&app, book()).await.asks;
+    assert_eq!(asks.iter().map(|level| (level.price, level.size, level.order_count)).collect::<Vec<_>>(), vec![(1.0, 7, 2)]);
+
+    // The first slice fills, the next one goes behind the order that was resting after it
+    let (_, exchanges) = market.buy(buyer, 5, 1.0).await;
+    let fills: Vec<(Option<i32>, i32)> = exchanges.iter().map(|exchange| (exchange.sell_order_id, exchange.amount)).collect();
+    assert_eq!(fills, vec![(Some(iceberg_id), 3), (Some(sell_order_id), 2)]);
+    let iceberg = market.sell_order(iceberg_id).await;
+    assert_eq!((iceberg.remaining_amount, iceberg.visible_amount), (Some(7), Some(3)));
+
+    let asks = test::call_and_read_body_json::<_, _, OrderBook>(&app, book()).await.asks;
+    assert_eq!(asks.iter().map(|level| (level.price, level.size, level.order_count)).collect::<Vec<_>>(), vec![(1.0, 5, 2)]);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn resubmitted_client_order_id_should_return_the_original_order() {
+    let mut market = Market::open().await;
+    let buyer = market.trader().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_NEW_BUY_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_buy_order))
+            )
+            .service(
+                web::resource(POST_NEW_SELL_ORDER)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(create_sell_order))
+            )
+            .service(
+                web::resource(GET_MY_ORDER_BY_CLIENT_ID)
+                    .wrap(JwtMiddleware)
+                    .route(web::get().to(my_order_by_client_id))
+            )
+    ).await;
+    let buy = |amount: i32, client_order_id: &str| test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_BUY_ORDER)
+        .set_json(CreateBuyOrderRequest::new(buyer, amount, market.base_currency_id, market.quote_currency_id, 1.0, EXPIRY_DAYS)
+            .with_client_order_id(Some(client_order_id.to_string())))
+        .to_request();
+
+    let res = test::call_service(&app, buy(5, "bot-1:42")).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let original: BuyOrder = test::read_body_json(res).await;
+    let res = test::call_service(&app, buy(7, "bot-1:42")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let resubmitted: BuyOrder = test::read_body_json(res).await;
+    assert_eq!((resubmitted.buy_order_id, resubmitted.buy_currency_amount), (original.buy_order_id, Some(5)));
+    assert_eq!(buy_orders_of(&market, buyer).await, 1);
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(POST_NEW_SELL_ORDER)
+        .set_json(CreateSellOrderRequest::new(buyer, 5, market.base_currency_id, market.quote_currency_id, 2.0, EXPIRY_DAYS)
+            .with_client_order_id(Some("bot-1:42".to_string())))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+    assert_eq!(test::call_service(&app, buy(5, "bot/42")).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .insert_header(("Authorization", market.token(buyer)))
+        .uri(&GET_MY_ORDER_BY_CLIENT_ID.replace("{client_order_id}", "bot-1:42"))
+        .to_request();
+    let found: ClientOrder = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(found.buy_order.and_then(|order| order.buy_order_id), original.buy_order_id);
+    assert!(found.sell_order.is_none());
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn atomic_batch_should_apply_every_item_or_none() {
+    let mut market = Market::open().await;
+    let (trader, seller) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 2, 1.0).await;
+    let (stranger_order_id, _) = market.buy(seller, 2, 0.5).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_ORDER_BATCH)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(place_order_batch))
+            )
+    ).await;
+    let place = |items: Vec<BatchOrderItem>| test::TestRequest::post()
+        .insert_header(("Authorization", market.token(trader)))
+        .uri(POST_ORDER_BATCH)
+        .set_json(BatchOrderRequest { atomic: true, items })
+        .to_request();
+
+    let invalid = vec![batch_buy(&market, trader, 2, 1.0), batch_buy(&market, trader, 2, 0.0)];
+    assert_eq!(test::call_service(&app, place(invalid)).await.status(), StatusCode::BAD_REQUEST);
+    let foreign_cancel = vec![batch_buy(&market, trader, 2, 1.0), BatchOrderItem::CancelBuy { buy_order_id: stranger_order_id }];
+    assert_eq!(test::call_service(&app, place(foreign_cancel)).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(buy_orders_of(&market, trader).await, 0);
+    assert_eq!(market.buy_order(stranger_order_id).await.status, Some(OrderStatus::Open));
+
+    let valid = vec![batch_buy(&market, trader, 2, 1.0), batch_buy(&market, trader, 3, 0.8)];
+    let response: BatchOrderResponse = test::call_and_read_body_json(&app, place(valid)).await;
+    let statuses: Vec<Option<OrderStatus>> = response.results.iter()
+        .map(|result| result.buy_order.as_ref().and_then(|order| order.status))
+        .collect();
+    assert_eq!(statuses, vec![Some(OrderStatus::Filled), Some(OrderStatus::Open)]);
+    assert_eq!(market.sell_order(sell_order_id).await.status, Some(OrderStatus::Filled));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn partial_batch_should_apply_each_item_on_its_own() {
+    let mut market = Market::open().await;
+    let (trader, stranger) = (market.trader().await, market.trader().await);
+    let (own_order_id, _) = market.buy(trader, 2, 0.5).await;
+    let (stranger_order_id, _) = market.buy(stranger, 2, 0.5).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(market.pool.clone()))
+            .app_data(Data::new(OrderEventBus::new(EVENTS_CAPACITY)))
+            .app_data(Data::new(OrderLimits { max_open_orders_per_user: MAX_OPEN_ORDERS, max_order_batch_size: MAX_ORDER_BATCH_SIZE }))
+            .service(
+                web::resource(POST_ORDER_BATCH)
+                    .wrap(JwtMiddleware)
+                    .route(web::post().to(place_order_batch))
+            )
+    ).await;
+    let items = vec![
+        batch_buy(&market, trader, 2, 1.0),
+        batch_buy(&market, trader, 2, 0.0),
+        BatchOrderItem::CancelBuy { buy_order_id: stranger_order_id },
+        BatchOrderItem::CancelBuy { buy_order_id: own_order_id },
+    ];
+
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(trader)))
+        .uri(POST_ORDER_BATCH)
+        .set_json(BatchOrderRequest { atomic: false, items })
+        .to_request();
+    let response: BatchOrderResponse = test::call_and_read_body_json(&app, req).await;
+
+    let outcomes: Vec<(Option<OrderStatus>, bool)> = response.results.iter()
+        .map(|result| (result.buy_order.as_ref().and_then(|order| order.status), result.error.is_some()))
+        .collect();
+    assert_eq!(outcomes, vec![
+        (Some(OrderStatus::Open), false),
+        (None, true),
+        (None, true),
+        (Some(OrderStatus::Cancelled), false),
+    ]);
+    assert_eq!(market.buy_order(stranger_order_id).await.status, Some(OrderStatus::Open));
+
+    let items = (0..=MAX_ORDER_BATCH_SIZE).map(|_| batch_buy(&market, trader, 1, 0.5)).collect();
+    let req = test::TestRequest::post()
+        .insert_header(("Authorization", market.token(trader)))
+        .uri(POST_ORDER_BATCH)
+        .set_json(BatchOrderRequest { atomic: false, items })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn cancel_halt_should_cancel_live_orders_and_refuse_new_ones_until_resumed() {
+    let mut market = Market::open().await;
+    let (admin, buyer, seller) = (market.trader().await, market.trader().await, market.trader().await);
+    let (buy_order_id, _) = market.buy(buyer, 2, 1.0).await;
+    let (sell_order_id, _) = market.sell(seller, 2, 1.5).await;
+    let repository = Repository::new(market.pool.clone());
+    let (base, quote) = (market.base_currency_id, market.quote_currency_id);
+
+    let halt = TradingHaltRequest { mode: TradingHaltMode::Cancel, reason: None };
+    let result = repository.halt_trading(&admin, &base, &quote, &halt).await.unwrap();
+    assert_eq!((result.cancelled_orders.buy_orders.len(), result.cancelled_orders.sell_orders.len()), (1, 1));
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Cancelled));
+    assert_eq!(market.sell_order(sell_order_id).await.status, Some(OrderStatus::Cancelled));
+    assert_eq!(market.balance(buyer, quote).await, STARTING_BALANCE);
+    let request = CreateBuyOrderRequest::new(buyer, 2, base, quote, 1.0, EXPIRY_DAYS);
+    assert!(matches!(repository.create_buy_order(&request, MAX_OPEN_ORDERS).await, Err(DataError::TradingHaltedError(_))));
+    assert!(matches!(repository.halt_trading(&admin, &quote, &base, &halt).await, Err(DataError::TradingHaltedError(_))));
+
+    repository.resume_trading(&base, &quote).await.unwrap();
+    assert!(repository.find_trading_halt(&base, &quote).await.unwrap().is_none());
+    assert!(repository.create_buy_order(&request, MAX_OPEN_ORDERS).await.is_ok());
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn freeze_halt_should_keep_orders_resting_until_trading_resumes() {
+    let mut market = Market::open().await;
+    let (admin, buyer, seller) = (market.trader().await, market.trader().await, market.trader().await);
+    let (buy_order_id, _) = market.buy(buyer, 2, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    let (base, quote) = (market.base_currency_id, market.quote_currency_id);
+
+    let halt = TradingHaltRequest { mode: TradingHaltMode::Freeze, reason: Some("Maintenance".to_string()) };
+    let result = repository.halt_trading(&admin, &base, &quote, &halt).await.unwrap();
+    assert!(result.cancelled_orders.buy_orders.is_empty());
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Open));
+    let request = CreateSellOrderRequest::new(seller, 2, base, quote, 1.0, EXPIRY_DAYS);
+    assert!(matches!(repository.create_sell_order(&request, MAX_OPEN_ORDERS).await, Err(DataError::TradingHaltedError(_))));
+
+    repository.resume_trading(&base, &quote).await.unwrap();
+    let (_, exchanges) = market.sell(seller, 2, 1.0).await;
+    assert_eq!(exchanges.iter().map(|exchange| exchange.buy_order_id).collect::<Vec<_>>(), vec![Some(buy_order_id)]);
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Filled));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn mass_cancel_should_cancel_the_users_orders_on_the_pair() {
+    let mut market = Market::open().await;
+    let (trader, other) = (market.trader().await, market.trader().await);
+    let (buy_order_id, _) = market.buy(trader, 2, 1.0).await;
+    let (sell_order_id, _) = market.sell(trader, 2, 1.5).await;
+    let (other_order_id, _) = market.buy(other, 2, 1.0).await;
+    let repository = Repository::new(market.pool.clone());
+    let stop = repository.create_stop_order(&trader, &stop_order(&market, OrderSide::Buy, 2, 2.0, None)).await.unwrap();
+
+    let cancelled = repository.cancel_user_orders(&trader, Some((market.base_currency_id, -1))).await.unwrap();
+    assert!(cancelled.buy_orders.is_empty() && cancelled.sell_orders.is_empty() && cancelled.stop_orders.is_empty());
+
+    let cancelled = repository.cancel_user_orders(&trader, Some((market.quote_currency_id, market.base_currency_id))).await.unwrap();
+    assert_eq!(cancelled.buy_orders.iter().map(|order| order.buy_order_id).collect::<Vec<_>>(), vec![Some(buy_order_id)]);
+    assert_eq!(cancelled.sell_orders.iter().map(|order| order.sell_order_id).collect::<Vec<_>>(), vec![Some(sell_order_id)]);
+    assert_eq!(cancelled.stop_orders.iter().map(|order| order.stop_order_id).collect::<Vec<_>>(), vec![stop.stop_order_id]);
+    assert_eq!(market.buy_order(other_order_id).await.status, Some(OrderStatus::Open));
+    assert_eq!(market.balance(trader, market.quote_currency_id).await, STARTING_BALANCE);
+    assert_eq!(market.balance(trader, market.base_currency_id).await, STARTING_BALANCE);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn cancel_newest_should_cancel_the_incoming_order() {
+    let SelfTrade { market, trader, own_sell_order_id, other_sell_order_id, buy_order_id, fills } = self_trade(SelfTradePrevention::CancelNewest).await;
+
+    assert!(fills.is_empty());
+    let buy_order = market.buy_order(buy_order_id).await;
+    assert_eq!((buy_order.status, buy_order.filled_amount), (Some(OrderStatus::Cancelled), Some(0)));
+    assert_eq!(market.sell_order(own_sell_order_id).await.status, Some(OrderStatus::Open));
+    assert_eq!(market.sell_order(other_sell_order_id).await.status, Some(OrderStatus::Open));
+    let prevented = Repository::new(market.pool.clone()).find_prevented_trades(&trader).await.unwrap();
+    assert_eq!(prevented.iter().map(|trade| (trade.mode, trade.amount)).collect::<Vec<_>>(), vec![(SelfTradePrevention::CancelNewest, 4)]);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn cancel_oldest_should_cancel_the_resting_order_and_keep_matching() {
+    let SelfTrade { market, own_sell_order_id, other_sell_order_id, buy_order_id, fills, .. } = self_trade(SelfTradePrevention::CancelOldest).await;
+
+    assert_eq!(fills, vec![(Some(other_sell_order_id), 5)]);
+    assert_eq!(market.sell_order(own_sell_order_id).await.status, Some(OrderStatus::Cancelled));
+    let buy_order = market.buy_order(buy_order_id).await;
+    assert_eq!((buy_order.status, buy_order.remaining_amount), (Some(OrderStatus::PartiallyFilled), Some(5)));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn cancel_both_should_cancel_both_orders() {
+    let SelfTrade { market, own_sell_order_id, other_sell_order_id, buy_order_id, fills, .. } = self_trade(SelfTradePrevention::CancelBoth).await;
+
+    assert!(fills.is_empty());
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Cancelled));
+    assert_eq!(market.sell_order(own_sell_order_id).await.status, Some(OrderStatus::Cancelled));
+    assert_eq!(market.sell_order(other_sell_order_id).await.status, Some(OrderStatus::Open));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn decrement_should_shrink_both_orders_and_keep_matching() {
+    let SelfTrade { market, trader, own_sell_order_id, other_sell_order_id, buy_order_id, fills } = self_trade(SelfTradePrevention::Decrement).await;
+
+    assert_eq!(fills, vec![(Some(other_sell_order_id), 5)]);
+    let own_sell_order = market.sell_order(own_sell_order_id).await;
+    assert_eq!((own_sell_order.status, own_sell_order.remaining_amount), (Some(OrderStatus::Cancelled), Some(0)));
+    let buy_order = market.buy_order(buy_order_id).await;
+    assert_eq!((buy_order.filled_amount, buy_order.remaining_amount), (Some(5), Some(1)));
+    assert_eq!(market.balance(trader, market.base_currency_id).await, STARTING_BALANCE + 5);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn orders_without_a_mode_should_take_the_account_setting() {
+    let mut market = Market::open().await;
+    let trader = market.trader().await;
+    let repository = Repository::new(market.pool.clone());
+    assert_eq!(repository.find_trading_settings(&trader).await.unwrap().self_trade_prevention, SelfTradePrevention::CancelNewest);
+
+    let settings = TradingSettingsRequest { self_trade_prevention: SelfTradePrevention::Decrement };
+    repository.update_trading_settings(&trader, &settings).await.unwrap();
+    let (buy_order_id, _) = market.buy(trader, 2, 1.0).await;
+    assert_eq!(market.buy_order(buy_order_id).await.self_trade_prevention, Some(SelfTradePrevention::Decrement));
+
+    market.close().await;
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use std::time::Duration;
+use currency_exchange_data::datasource::api_models::CreateBuyOrderRequest;
+use currency_exchange_data::datasource::errors::DataError;
+use currency_exchange_data::datasource::models::OrderStatus;
+use currency_exchange_data::datasource::repository::order_repository::OrderRepository;
+use currency_exchange_data::datasource::repository::repository::Repository;
+use currency_exchange_orders::expiry_sweeper::ExpirySweeper;
+use currency_exchange_orders::matching_engine::{MatchingEngine, OrderMatcher};
+use currency_exchange_orders::order_events::{OrderEvent, OrderEventBus};
+use common::{Market, EXPIRY_DAYS, MAX_OPEN_ORDERS, STARTING_BALANCE};
+
+#[actix_web::test]
+async fn matching_should_fill_best_price_then_earliest_order_and_record_exchanges() {
+    let mut market = Market::open().await;
+    let (expensive_seller, earliest_seller, latest_seller, buyer) =
+        (market.trader().await, market.trader().await, market.trader().await, market.trader().await);
+    let (expensive, _) = market.sell(expensive_seller, 10, 1.2).await;
+    let (earliest, _) = market.sell(earliest_seller, 10, 1.1).await;
+    let (latest, _) = market.sell(latest_seller, 10, 1.1).await;
+
+    let (buy_order_id, exchanges) = market.buy(buyer, 15, 1.3).await;
+
+    let fills: Vec<(Option<i32>, i32, f64)> = exchanges
+        .iter()
+        .map(|exchange| (exchange.sell_order_id, exchange.amount, exchange.exchange_rate))
+        .collect();
+    assert_eq!(fills, vec![(Some(earliest), 10, 1.1), (Some(latest), 5, 1.1)]);
+    let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM currency_exchange WHERE buy_order_id = $1")
+        .bind(buy_order_id)
+        .fetch_one(&market.pool)
+        .await
+        .unwrap();
+    assert_eq!(recorded, 2);
+    let buy_order = market.buy_order(buy_order_id).await;
+    assert_eq!((buy_order.remaining_amount, buy_order.status), (Some(0), Some(OrderStatus::Filled)));
+    let earliest = market.sell_order(earliest).await;
+    assert_eq!((earliest.remaining_amount, earliest.status), (Some(0), Some(OrderStatus::Filled)));
+    let latest = market.sell_order(latest).await;
+    assert_eq!((latest.remaining_amount, latest.status), (Some(5), Some(OrderStatus::PartiallyFilled)));
+    let expensive = market.sell_order(expensive).await;
+    assert_eq!((expensive.remaining_amount, expensive.status), (Some(10), Some(OrderStatus::Open)));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn matching_should_trade_at_resting_price_and_move_both_currencies() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    market.sell(seller, 10, 1.5).await;
+
+    let (_, exchanges) = market.buy(buyer, 4, 1.6).await;
+
+    assert_eq!(exchanges.len(), 1);
+    assert_eq!((exchanges[0].amount, exchanges[0].exchange_rate, exchanges[0].income), (4, 1.5, Some(6.0)));
+    assert_eq!(market.balance(buyer, market.base_currency_id).await, STARTING_BALANCE + 4);
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 6);
+    assert_eq!(market.balance(seller, market.base_currency_id).await, STARTING_BALANCE - 4);
+    assert_eq!(market.balance(seller, market.quote_currency_id).await, STARTING_BALANCE + 6);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn partial_fills_should_cost_the_buyer_what_one_whole_fill_would() {
+    let mut market = Market::open().await;
+    let (buyer, first_seller, second_seller) = (market.trader().await, market.trader().await, market.trader().await);
+    market.buy(buyer, 2, 1.5).await;
+
+    let (_, first) = market.sell(first_seller, 1, 1.5).await;
+    let (_, second) = market.sell(second_seller, 1, 1.5).await;
+
+    let costs: Vec<Option<f64>> = first.iter().chain(&second).map(|exchange| exchange.income).collect();
+    assert_eq!(costs, vec![Some(2.0), Some(1.0)]);
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 3);
+    assert_eq!(market.balance(first_seller, market.quote_currency_id).await, STARTING_BALANCE + 2);
+    assert_eq!(market.balance(second_seller, market.quote_currency_id).await, STARTING_BALANCE + 1);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn matching_should_leave_orders_that_do_not_cross() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 1.5).await;
+
+    let (buy_order_id, exchanges) = market.buy(buyer, 10, 1.4).await;
+
+    assert!(exchanges.is_empty());
+    assert_eq!(market.buy_order(buy_order_id).await.remaining_amount, Some(10));
+    assert_eq!(market.sell_order(sell_order_id).await.remaining_amount, Some(10));
+    assert_eq!(market.balance(buyer, market.base_currency_id).await, STARTING_BALANCE);
+    assert_eq!(market.balance(seller, market.quote_currency_id).await, STARTING_BALANCE);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn matching_should_return_settlement_errors_and_leave_both_orders_untouched() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (sell_order_id, _) = market.sell(seller, 10, 1.5).await;
+    sqlx::query(
+        "UPDATE currency_amount SET amount = 0 FROM wallets
+        WHERE wallets.wallet_id = currency_amount.wallet_id AND wallets.user_id = $1 AND currency_amount.currency_id = $2")
+        .bind(seller)
+        .bind(market.base_currency_id)
+        .execute(&market.pool)
+        .await
+        .unwrap();
+    let request = CreateBuyOrderRequest::new(buyer, 10, market.base_currency_id, market.quote_currency_id, 1.5, EXPIRY_DAYS);
+    let buy_order = Repository::new(market.pool.clone()).create_buy_order(&request, MAX_OPEN_ORDERS).await.unwrap();
+
+    let matched = MatchingEngine::new(market.pool.clone()).match_buy_order(&buy_order).await;
+
+    assert!(matches!(matched, Err(DataError::WalletBalanceError(_))));
+    assert_eq!(market.buy_order(buy_order.buy_order_id.unwrap()).await.remaining_amount, Some(10));
+    assert_eq!(market.sell_order(sell_order_id).await.remaining_amount, Some(10));
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn listings_should_show_live_orders_by_default_and_filter_by_status() {
+    let mut market = Market::open().await;
+    let (seller, buyer, resting_seller) = (market.trader().await, market.trader().await, market.trader().await);
+    let (filled, _) = market.sell(seller, 5, 1.5).await;
+    market.buy(buyer, 5, 1.5).await;
+    let (resting, _) = market.sell(resting_seller, 5, 9.5).await;
+    let repo = Repository::new(market.pool.clone());
+
+    let live = repo.find_sell_orders(i64::MAX, None).await.unwrap();
+    assert!(live.iter().any(|order| order.sell_order_id == Some(resting)));
+    assert!(live.iter().all(|order| order.sell_order_id != Some(filled)));
+    let done = repo.find_sell_orders(i64::MAX, Some(OrderStatus::Filled)).await.unwrap();
+    assert!(done.iter().any(|order| order.sell_order_id == Some(filled)));
+    assert!(done.iter().all(|order| order.status == Some(OrderStatus::Filled)));
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn expiry_sweeper_should_expire_due_orders_only() {
+    let mut market = Market::open().await;
+    let (due_seller, later_seller, buyer) = (market.trader().await, market.trader().await, market.trader().await);
+    let (due_order_id, _) = market.sell(due_seller, 10, 1.5).await;
+    let (later_order_id, _) = market.sell(later_seller, 5, 2.0).await;
+    market.buy(buyer, 4, 1.5).await;
+    sqlx::query("UPDATE sell_orders SET expires_at = now() - INTERVAL '1 minute' WHERE sell_order_id = $1")
+        .bind(due_order_id)
+        .execute(&market.pool)
+        .await
+        .unwrap();
+    let events = OrderEventBus::new(64);
+    let mut receiver = events.subscribe();
+
+    ExpirySweeper::new(market.pool.clone(), Duration::from_secs(60), 10, events).sweep().await.unwrap();
+
+    let expired = market.sell_order(due_order_id).await;
+    assert_eq!((expired.status, expired.filled_amount), (Some(OrderStatus::Expired), Some(4)));
+    assert_eq!(market.sell_order(later_order_id).await.status, Some(OrderStatus::Open));
+    let mut published = false;
+    while let Ok(event) = receiver.try_recv() {
+        published |= matches!(event, OrderEvent::SellOrderExpired { sell_order_id, .. } if sell_order_id == due_order_id);
+    }
+    assert!(published);
+    assert_eq!(market.balance(due_seller, market.base_currency_id).await, STARTING_BALANCE - 4);
+    assert_eq!(market.balance(due_seller, market.quote_currency_id).await, STARTING_BALANCE + 6);
+    assert_eq!(market.held(due_seller, market.base_currency_id).await, 0);
+    assert_eq!(market.held(later_seller, market.base_currency_id).await, 5);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn limit_orders_should_hold_funds_and_be_refused_beyond_the_available_balance() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+
+    market.buy(buyer, 10, 2.0).await;
+    market.sell(seller, 5, 3.0).await;
+
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE);
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 20);
+    assert_eq!(market.held(seller, market.base_currency_id).await, 5);
+    let request = CreateBuyOrderRequest::new(buyer, 495, market.base_currency_id, market.quote_currency_id, 2.0, EXPIRY_DAYS);
+    let refused = Repository::new(market.pool.clone()).create_buy_order(&request, MAX_OPEN_ORDERS).await;
+    assert!(matches!(refused, Err(DataError::InsufficientFundsError(_))));
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 20);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn filling_below_the_limit_price_should_release_the_unspent_hold() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    market.sell(seller, 10, 1.0).await;
+
+    let (buy_order_id, _) = market.buy(buyer, 10, 2.0).await;
+
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Filled));
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 10);
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 0);
+    assert_eq!(market.held(seller, market.base_currency_id).await, 0);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn fills_one_unit_at_a_time_should_settle_within_the_hold_rounded_up() {
+    let mut market = Market::open().await;
+    let (buyer, first_seller, second_seller) = (market.trader().await, market.trader().await, market.trader().await);
+    market.buy(buyer, 2, 1.25).await;
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 3);
+
+    let (_, first) = market.sell(first_seller, 1, 1.25).await;
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 2);
+    let (_, second) = market.sell(second_seller, 1, 1.25).await;
+
+    let costs: Vec<Option<f64>> = first.iter().chain(&second).map(|exchange| exchange.income).collect();
+    assert_eq!(costs, vec![Some(1.0), Some(2.0)]);
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 3);
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 0);
+
+    market.close().await;
+}
+
+#[actix_web::test]
+async fn cancelling_a_partially_filled_buy_should_release_the_rest_of_its_hold() {
+    let mut market = Market::open().await;
+    let (seller, buyer) = (market.trader().await, market.trader().await);
+    let (buy_order_id, _) = market.buy(buyer, 3, 2.0).await;
+    market.sell(seller, 1, 2.0).await;
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 2);
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 4);
+
+    Repository::new(market.pool.clone()).cancel_buy_order(&buy_order_id, &buyer).await.unwrap();
+
+    assert_eq!(market.buy_order(buy_order_id).await.status, Some(OrderStatus::Cancelled));
+    assert_eq!(market.balance(buyer, market.quote_currency_id).await, STARTING_BALANCE - 2);
+    assert_eq!(market.held(buyer, market.quote_currency_id).await, 0);
+
+    market.close().await;
+}
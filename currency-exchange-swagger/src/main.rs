@@ -13,14 +13,44 @@ use swagger::__path_add_currency_to_wallet;
 use swagger::__path_sell_currency;
 use swagger::__path_create_sell_order;
 use swagger::__path_create_buy_order;
+use swagger::__path_market_buy;
+use swagger::__path_market_sell;
+use swagger::__path_create_stop_order;
+use swagger::__path_stop_orders;
+use swagger::__path_cancel_stop_order;
+use swagger::__path_create_oco_order;
+use swagger::__path_place_order_batch;
+use swagger::__path_cancel_order_group;
+use swagger::__path_my_orders;
+use swagger::__path_my_order_by_client_id;
+use swagger::__path_cancel_my_orders;
+use swagger::__path_trading_settings;
+use swagger::__path_update_trading_settings;
+use swagger::__path_prevented_trades;
 use swagger::__path_create_new_wallet;
+use swagger::__path_buy_order_fills;
+use swagger::__path_sell_order_fills;
+use swagger::__path_cancel_buy_order;
+use swagger::__path_cancel_sell_order;
+use swagger::__path_amend_buy_order;
+use swagger::__path_amend_sell_order;
+use swagger::__path_order_book;
+use swagger::__path_trades;
+use swagger::__path_my_trades;
+use swagger::__path_candles;
+use swagger::__path_backfill_candles;
+use swagger::__path_tickers;
+use swagger::__path_ticker;
+use swagger::__path_trading_halts;
+use swagger::__path_halt_trading;
+use swagger::__path_resume_trading;
 use utoipa::{Modify, OpenApi};
 use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(Default, OpenApi)]
 #[openapi(
-    paths(buy_orders, sell_orders, register, login, currencies, create_currency, add_currency_to_wallet, sell_currency, create_sell_order, create_buy_order, create_new_wallet),
+    paths(buy_orders, sell_orders, register, login, currencies, create_currency, add_currency_to_wallet, sell_currency, create_sell_order, create_buy_order, market_buy, market_sell, create_stop_order, stop_orders, cancel_stop_order, create_oco_order, place_order_batch, cancel_order_group, my_orders, my_order_by_client_id, cancel_my_orders, trading_settings, update_trading_settings, prevented_trades, create_new_wallet, buy_order_fills, sell_order_fills, cancel_buy_order, cancel_sell_order, amend_buy_order, amend_sell_order, order_book, trades, my_trades, candles, backfill_candles, tickers, ticker, trading_halts, halt_trading, resume_trading),
     servers(
         (url="http://localhost:8081", description="Order server"),
         (url="http://localhost:8080", description="Auth server"),
@@ -10,7 +10,7 @@ use crate::swagger::swagger_models::SellOrderBadRequest;
 use crate::swagger::swagger_models::SellOrderNotFound;
 use crate::swagger::swagger_models::SignupRequest;
 use crate::swagger::swagger_models::SignupResponse;
-use crate::swagger::swagger_models::{AddCurrencyToWalletRequest, BuyCurrencyRequest, BuyOrder, CreateBuyOrderRequest, CreateSellOrderRequest, CurrencyAmount, Wallet};
+use crate::swagger::swagger_models::{AddCurrencyToWalletRequest, BatchOrderRequest, BatchOrderResponse, BuyCurrencyRequest, BuyOrder, CreateBuyOrderRequest, CreateSellOrderRequest, AmendOrderRequest, BackfillCandlesRequest, BackfillCandlesResponse, Candle, ClientOrder, CreateOcoOrderRequest, CreateStopOrderRequest, CurrencyExchange, Fill, MarketOrderRequest, MarketOrderResult, OrderBook, OrderGroup, OrderUpdateFailed, PreventedTrade, StopOrder, Ticker, TradePage, TradingHalt, TradingHaltFailed, TradingHaltRequest, TradingHaltResult, TradingSettings, TradingSettingsFailed, TradingSettingsRequest, UserOrders, UserTradePage, Wallet};
 use crate::swagger::utoipa_endpoints::GET_CURRENCY_LIST;
 use crate::swagger::utoipa_endpoints::GET_SELL_ORDERS;
 use crate::swagger::utoipa_endpoints::POST_CREATE_BUY_ORDER;
@@ -21,6 +21,12 @@ use crate::swagger::utoipa_endpoints::POST_LOGIN;
 use crate::swagger::utoipa_endpoints::PUT_BUY_CURRENCY;
 use crate::swagger::utoipa_endpoints::PUT_SELL_CURRENCY;
 use crate::swagger::utoipa_endpoints::{GET_BUY_ORDERS, POST_CREATE_WALLET, PUT_ADD_WALLET_CURRENCY};
+use crate::swagger::utoipa_endpoints::{POST_MARKET_BUY, POST_MARKET_SELL};
+use crate::swagger::utoipa_endpoints::{DELETE_MY_ORDERS, DELETE_ORDER_GROUP, DELETE_STOP_ORDER, GET_MY_ORDERS, GET_MY_ORDER_BY_CLIENT_ID, GET_STOP_ORDERS, POST_CREATE_OCO_ORDER, POST_CREATE_STOP_ORDER, POST_ORDER_BATCH};
+use crate::swagger::utoipa_endpoints::{GET_BUY_ORDER_FILLS, GET_CANDLES, GET_MY_TRADES, POST_BACKFILL_CANDLES, GET_ORDER_BOOK, GET_SELL_ORDER_FILLS, GET_TICKER, GET_TICKERS, GET_TRADES};
+use crate::swagger::utoipa_endpoints::{DELETE_TRADING_HALT, GET_TRADING_HALTS, POST_TRADING_HALT};
+use crate::swagger::utoipa_endpoints::{GET_PREVENTED_TRADES, GET_TRADING_SETTINGS, PUT_TRADING_SETTINGS};
+use crate::swagger::utoipa_endpoints::{DELETE_BUY_ORDER, DELETE_SELL_ORDER, PATCH_BUY_ORDER, PATCH_SELL_ORDER};
 use crate::swagger_env::SwaggerEnv;
 use actix_web::{get, post, HttpRequest, HttpResponse};
 use currency_exchange_data::datasource::api_models::CreateUserRequest;
@@ -36,9 +42,39 @@ mod utoipa_endpoints {
     pub const POST_CREATE_CURRENCY: &str = "/api/v1/currencies/create";
     pub const PUT_SELL_CURRENCY: &str = "/api/v1/orders/sell/execute";
     pub const PUT_BUY_CURRENCY: &str = "/api/v1/orders/buy/execute";
+    pub const GET_BUY_ORDER_FILLS: &str = "/api/v1/orders/buy/{id}/fills";
+    pub const GET_SELL_ORDER_FILLS: &str = "/api/v1/orders/sell/{id}/fills";
+    pub const GET_ORDER_BOOK: &str = "/api/v1/orderbook/{base}/{quote}";
+    pub const GET_TRADES: &str = "/api/v1/trades/{base}/{quote}";
+    pub const GET_MY_TRADES: &str = "/api/v1/me/trades";
+    pub const GET_CANDLES: &str = "/api/v1/candles/{base}/{quote}";
+    pub const GET_TICKERS: &str = "/api/v1/ticker";
+    pub const GET_TICKER: &str = "/api/v1/ticker/{base}/{quote}";
+    pub const POST_BACKFILL_CANDLES: &str = "/api/v1/candles/{base}/{quote}/backfill";
+    pub const DELETE_BUY_ORDER: &str = "/api/v1/orders/buy/{id}";
+    pub const DELETE_SELL_ORDER: &str = "/api/v1/orders/sell/{id}";
+    pub const PATCH_BUY_ORDER: &str = "/api/v1/orders/buy/{id}";
+    pub const PATCH_SELL_ORDER: &str = "/api/v1/orders/sell/{id}";
 
     pub const POST_CREATE_SELL_ORDER: &str = "/api/v1/orders/sell/new";
     pub const POST_CREATE_BUY_ORDER: &str = "/api/v1/orders/buy/new";
+    pub const POST_MARKET_BUY: &str = "/api/v1/orders/buy/market";
+    pub const POST_MARKET_SELL: &str = "/api/v1/orders/sell/market";
+    pub const POST_CREATE_STOP_ORDER: &str = "/api/v1/orders/stop/new";
+    pub const GET_STOP_ORDERS: &str = "/api/v1/orders/stop";
+    pub const DELETE_STOP_ORDER: &str = "/api/v1/orders/stop/{id}";
+    pub const POST_CREATE_OCO_ORDER: &str = "/api/v1/orders/oco/new";
+    pub const POST_ORDER_BATCH: &str = "/api/v1/orders/batch";
+    pub const DELETE_ORDER_GROUP: &str = "/api/v1/orders/oco/{id}";
+    pub const GET_MY_ORDERS: &str = "/api/v1/me/orders";
+    pub const GET_MY_ORDER_BY_CLIENT_ID: &str = "/api/v1/me/orders/by-client-id/{client_order_id}";
+    pub const DELETE_MY_ORDERS: &str = "/api/v1/me/orders";
+    pub const GET_TRADING_HALTS: &str = "/api/v1/halts";
+    pub const POST_TRADING_HALT: &str = "/api/v1/admin/halts/{base}/{quote}";
+    pub const DELETE_TRADING_HALT: &str = "/api/v1/admin/halts/{base}/{quote}";
+    pub const GET_TRADING_SETTINGS: &str = "/api/v1/me/settings";
+    pub const PUT_TRADING_SETTINGS: &str = "/api/v1/me/settings";
+    pub const GET_PREVENTED_TRADES: &str = "/api/v1/me/prevented-trades";
     
     pub const PUT_ADD_WALLET_CURRENCY: &str = "/api/v1/wallet/currencies/add";
 
@@ -55,6 +91,7 @@ mod utoipa_endpoints {
     ),
     params(
         ("count" = String, Query, description = "Displays number of orders"),
+        ("status" = Option<String>, Query, description = "open, partially_filled, filled, cancelled or expired; live orders when omitted"),
     )
 )]
 #[get("/api/v1/orders/buy")]
@@ -97,6 +134,7 @@ async fn buy_orders(req: HttpRequest) -> HttpResponse {
     ),
     params(
         ("count" = String, Query, description = "Displays number of orders"),
+        ("status" = Option<String>, Query, description = "open, partially_filled, filled, cancelled or expired; live orders when omitted"),
     )
 )]
 #[get("/api/v1/orders/sell")]
@@ -342,6 +380,7 @@ pub async fn add_currency_to_wallet(req: HttpRequest, args: AddCurrencyToWalletR
     responses(
         (status = 200, body = BuyOrder),
         (status = 400, body = String),
+        (status = 409, description = "Open order limit reached"),
     )
 )]
 pub async fn create_buy_order(req: HttpRequest, args: CreateBuyOrderRequest) -> HttpResponse {
@@ -378,6 +417,7 @@ pub async fn create_buy_order(req: HttpRequest, args: CreateBuyOrderRequest) ->
     responses(
         (status = 200, body = SellOrder),
         (status = 400, body = String),
+        (status = 409, description = "Open order limit reached"),
     )
 )]
 pub async fn create_sell_order(req: HttpRequest, args: CreateSellOrderRequest) -> HttpResponse {
@@ -407,16 +447,917 @@ pub async fn create_sell_order(req: HttpRequest, args: CreateSellOrderRequest) -
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/orders/buy/market",
+    request_body = MarketOrderRequest,
+    responses(
+        (status = 200, body = MarketOrderResult),
+        (status = 400, body = String),
+        (status = 409, description = "No resting orders within the requested limits"),
+    )
+)]
+pub async fn market_buy(req: HttpRequest, args: MarketOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.post(POST_MARKET_BUY)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<MarketOrderResult>().await {
+            Ok(result) => HttpResponse::Ok().json(result),
+            Err(_) => HttpResponse::BadRequest().json("Failed to execute market buy order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to execute market buy order"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/orders/sell/market",
+    request_body = MarketOrderRequest,
+    responses(
+        (status = 200, body = MarketOrderResult),
+        (status = 400, body = String),
+        (status = 409, description = "No resting orders within the requested limits"),
+    )
+)]
+pub async fn market_sell(req: HttpRequest, args: MarketOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.post(POST_MARKET_SELL)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<MarketOrderResult>().await {
+            Ok(result) => HttpResponse::Ok().json(result),
+            Err(_) => HttpResponse::BadRequest().json("Failed to execute market sell order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to execute market sell order"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/orders/stop/new",
+    request_body = CreateStopOrderRequest,
+    responses(
+        (status = 201, body = StopOrder),
+        (status = 400, body = String, description = "Malformed stop or stop price already reached"),
+    )
+)]
+pub async fn create_stop_order(req: HttpRequest, args: CreateStopOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.post(POST_CREATE_STOP_ORDER)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<StopOrder>().await {
+            Ok(order) => HttpResponse::Created().json(order),
+            Err(_) => HttpResponse::BadRequest().json("Failed to create stop order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to create stop order"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/orders/stop",
+    responses(
+        (status = 200, body = Vec<StopOrder>),
+        (status = 404, body = String),
+        (status = 401, body = String)
+    ),
+    params(
+        ("status" = Option<String>, Query, description = "pending, triggered, cancelled or rejected; every stop order when omitted"),
+    )
+)]
+pub async fn stop_orders(req: HttpRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_STOP_ORDERS)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<StopOrder>>().await {
+            Ok(orders) => HttpResponse::Ok().json(orders),
+            Err(_) => HttpResponse::NotFound().json("Failed to get stop orders"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get stop orders"),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/orders/stop/{id}",
+    responses(
+        (status = 200, body = StopOrder),
+        (status = 403, body = OrderUpdateFailed),
+        (status = 404, body = OrderUpdateFailed),
+        (status = 409, body = OrderUpdateFailed, description = "Stop order is no longer pending")
+    ),
+    params(
+        ("id" = i32, Path, description = "Stop order id"),
+    )
+)]
+pub async fn cancel_stop_order(req: HttpRequest, id: i32) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.delete(DELETE_STOP_ORDER.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<StopOrder>().await {
+            Ok(order) => HttpResponse::Ok().json(order),
+            Err(_) => HttpResponse::BadRequest().json("Failed to cancel stop order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to cancel stop order"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/orders/oco/new",
+    request_body = CreateOcoOrderRequest,
+    responses(
+        (status = 201, body = OrderGroup),
+        (status = 400, body = String, description = "Legs on the wrong side of each other or stop price already reached"),
+        (status = 409, body = String, description = "Open order limit reached"),
+    )
+)]
+pub async fn create_oco_order(req: HttpRequest, args: CreateOcoOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.post(POST_CREATE_OCO_ORDER)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<OrderGroup>().await {
+            Ok(group) => HttpResponse::Created().json(group),
+            Err(_) => HttpResponse::BadRequest().json("Failed to create OCO order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to create OCO order"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/orders/batch",
+    request_body = BatchOrderRequest,
+    responses(
+        (status = 200, body = BatchOrderResponse, description = "One result per item, failed items carry an error unless the batch is atomic"),
+        (status = 400, body = String, description = "Empty or oversized batch, or an atomic batch that was rolled back"),
+    )
+)]
+pub async fn place_order_batch(req: HttpRequest, args: BatchOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.post(POST_ORDER_BATCH)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<BatchOrderResponse>().await {
+            Ok(batch) => HttpResponse::Ok().json(batch),
+            Err(_) => HttpResponse::BadRequest().json("Failed to place order batch"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to place order batch"),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/orders/oco/{id}",
+    responses(
+        (status = 200, body = OrderGroup),
+        (status = 403, body = OrderUpdateFailed),
+        (status = 404, body = OrderUpdateFailed),
+        (status = 409, body = OrderUpdateFailed, description = "No leg of the group is still working")
+    ),
+    params(
+        ("id" = i32, Path, description = "Order group id"),
+    )
+)]
+pub async fn cancel_order_group(req: HttpRequest, id: i32) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.delete(DELETE_ORDER_GROUP.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<OrderGroup>().await {
+            Ok(group) => HttpResponse::Ok().json(group),
+            Err(_) => HttpResponse::BadRequest().json("Failed to cancel order group"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to cancel order group"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/orders",
+    responses(
+        (status = 200, body = UserOrders),
+        (status = 404, body = String),
+        (status = 401, body = String)
+    )
+)]
+pub async fn my_orders(req: HttpRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_MY_ORDERS)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<UserOrders>().await {
+            Ok(orders) => HttpResponse::Ok().json(orders),
+            Err(_) => HttpResponse::NotFound().json("Failed to get orders"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get orders"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/orders/by-client-id/{client_order_id}",
+    responses(
+        (status = 200, body = ClientOrder),
+        (status = 404, body = String),
+        (status = 401, body = String)
+    ),
+    params(
+        ("client_order_id" = String, Path, description = "Client order id the order was placed under"),
+    )
+)]
+pub async fn my_order_by_client_id(req: HttpRequest, client_order_id: String) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_MY_ORDER_BY_CLIENT_ID.replace("{client_order_id}", &client_order_id))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<ClientOrder>().await {
+            Ok(order) => HttpResponse::Ok().json(order),
+            Err(_) => HttpResponse::NotFound().json("Failed to get order"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get order"),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me/orders",
+    responses(
+        (status = 200, body = UserOrders, description = "Every order the call cancelled"),
+        (status = 400, body = OrderUpdateFailed, description = "Malformed currency pair"),
+        (status = 404, body = String, description = "Unknown currency"),
+        (status = 401, body = String)
+    ),
+    params(
+        ("pair" = Option<String>, Query, description = "Currency pair as BASE/QUOTE, matched in either direction; every pair when omitted"),
+    )
+)]
+pub async fn cancel_my_orders(req: HttpRequest, pair: Option<String>) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let mut request = network_client.delete(DELETE_MY_ORDERS)
+        .header("Authorization", format!("Bearer {}", token));
+    if let Some(pair) = pair {
+        request = request.query(&[("pair", pair)]);
+    }
+    match request.send().await {
+        Ok(res) => match res.json::<UserOrders>().await {
+            Ok(orders) => HttpResponse::Ok().json(orders),
+            Err(_) => HttpResponse::BadRequest().json("Failed to cancel orders"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to cancel orders"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/settings",
+    responses(
+        (status = 200, body = TradingSettings, description = "Defaults until the settings are first updated"),
+        (status = 500, body = TradingSettingsFailed),
+        (status = 401, body = String)
+    )
+)]
+pub async fn trading_settings(req: HttpRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_TRADING_SETTINGS)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<TradingSettings>().await {
+            Ok(settings) => HttpResponse::Ok().json(settings),
+            Err(_) => HttpResponse::NotFound().json("Failed to get trading settings"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get trading settings"),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/me/settings",
+    request_body = TradingSettingsRequest,
+    responses(
+        (status = 200, body = TradingSettings, description = "The self-trade prevention mode applies to later orders that do not choose their own"),
+        (status = 400, body = TradingSettingsFailed),
+        (status = 401, body = String)
+    )
+)]
+pub async fn update_trading_settings(req: HttpRequest, args: TradingSettingsRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.put(PUT_TRADING_SETTINGS)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<TradingSettings>().await {
+            Ok(settings) => HttpResponse::Ok().json(settings),
+            Err(_) => HttpResponse::BadRequest().json("Failed to update trading settings"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to update trading settings"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/prevented-trades",
+    responses(
+        (status = 200, body = Vec<PreventedTrade>, description = "Matches between the caller's own orders that were not executed, newest first"),
+        (status = 404, body = String),
+        (status = 401, body = String)
+    )
+)]
+pub async fn prevented_trades(req: HttpRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_PREVENTED_TRADES)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<PreventedTrade>>().await {
+            Ok(prevented_trades) => HttpResponse::Ok().json(prevented_trades),
+            Err(_) => HttpResponse::NotFound().json("Failed to get prevented trades"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get prevented trades"),
+    }
+}
+
 #[utoipa::path(
     put,
     path = "/api/v1/orders/buy/execute",
     request_body = BuyCurrencyRequest,
     responses(
-        (status = 200, body = CurrencyAmount),
+        (status = 200, body = CurrencyExchange),
+        (status = 409, body = PreventedTrade, description = "The order is the caller's own, self-trade prevention applied instead"),
+        (status = 400, body = String),
+    )
+)]
+pub async fn buy_currency(req: HttpRequest, args: BuyCurrencyRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.put(PUT_BUY_CURRENCY)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    if res.is_ok() {
+        let result = res.unwrap().json::<CurrencyExchange>().await;
+        if result.is_ok() {
+            HttpResponse::Ok().json(result.unwrap())
+        } else {
+            HttpResponse::BadRequest().json("Failed to buy currency")
+        }
+    } else {
+        HttpResponse::BadRequest().json("Failed to put currency")
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/orders/sell/execute",
+    request_body = SellCurrencyRequest,
+    responses(
+        (status = 200, body = CurrencyExchange),
+        (status = 409, body = PreventedTrade, description = "The order is the caller's own, self-trade prevention applied instead"),
+        (status = 400, body = String),
+    )
+)]
+pub async fn sell_currency(req: HttpRequest, body: SellCurrencyRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.put(PUT_SELL_CURRENCY)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&body)
+        .send()
+        .await;
+    if res.is_ok() {
+        let result = res.unwrap().json::<CurrencyExchange>().await;
+        if result.is_ok() {
+            HttpResponse::Ok().json(result.unwrap())
+        } else {
+            HttpResponse::BadRequest().json("Error")
+        }
+    } else {
+        HttpResponse::BadRequest().json("Error")
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/orders/buy/{id}/fills",
+    responses(
+        (status = 200, body = Vec<Fill>),
+        (status = 404, body = BuyOrderNotFound),
+        (status = 401, body = String)
+    ),
+    params(
+        ("id" = i32, Path, description = "Buy order id"),
+    )
+)]
+pub async fn buy_order_fills(req: HttpRequest, id: i32) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_BUY_ORDER_FILLS.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<Fill>>().await {
+            Ok(fills) => HttpResponse::Ok().json(fills),
+            Err(_) => HttpResponse::NotFound().json("Failed to get buy order fills"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get buy order fills"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/orders/sell/{id}/fills",
+    responses(
+        (status = 200, body = Vec<Fill>),
+        (status = 404, body = SellOrderNotFound),
+        (status = 401, body = String)
+    ),
+    params(
+        ("id" = i32, Path, description = "Sell order id"),
+    )
+)]
+pub async fn sell_order_fills(req: HttpRequest, id: i32) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_SELL_ORDER_FILLS.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<Fill>>().await {
+            Ok(fills) => HttpResponse::Ok().json(fills),
+            Err(_) => HttpResponse::NotFound().json("Failed to get sell order fills"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get sell order fills"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/orderbook/{base}/{quote}",
+    responses(
+        (status = 200, body = OrderBook),
+        (status = 404, body = String)
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
+        ("depth" = Option<i64>, Query, description = "Price levels per side, 20 by default and at most 100"),
+    ),
+    security(())
+)]
+pub async fn order_book(base: String, quote: String) -> HttpResponse {
+    let network_client = Client::new();
+    let res = network_client.get(GET_ORDER_BOOK.replace("{base}", &base).replace("{quote}", &quote))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<OrderBook>().await {
+            Ok(book) => HttpResponse::Ok().json(book),
+            Err(_) => HttpResponse::NotFound().json("Failed to get order book"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get order book"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/trades/{base}/{quote}",
+    responses(
+        (status = 200, body = TradePage),
+        (status = 404, body = String)
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
+        ("from" = Option<String>, Query, description = "Earliest execution time (RFC 3339), inclusive"),
+        ("to" = Option<String>, Query, description = "Latest execution time (RFC 3339), exclusive"),
+        ("cursor" = Option<i32>, Query, description = "next_cursor of the previous page"),
+        ("limit" = Option<i64>, Query, description = "Trades per page, 50 by default and at most 500"),
+    ),
+    security(())
+)]
+pub async fn trades(base: String, quote: String) -> HttpResponse {
+    let network_client = Client::new();
+    let res = network_client.get(GET_TRADES.replace("{base}", &base).replace("{quote}", &quote))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<TradePage>().await {
+            Ok(page) => HttpResponse::Ok().json(page),
+            Err(_) => HttpResponse::NotFound().json("Failed to get trades"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get trades"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/trades",
+    responses(
+        (status = 200, body = UserTradePage),
+        (status = 404, body = String),
+        (status = 401, body = String)
+    ),
+    params(
+        ("from" = Option<String>, Query, description = "Earliest execution time (RFC 3339), inclusive"),
+        ("to" = Option<String>, Query, description = "Latest execution time (RFC 3339), exclusive"),
+        ("cursor" = Option<i32>, Query, description = "next_cursor of the previous page"),
+        ("limit" = Option<i64>, Query, description = "Trades per page, 50 by default and at most 500"),
+    )
+)]
+pub async fn my_trades(req: HttpRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.get(GET_MY_TRADES)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<UserTradePage>().await {
+            Ok(page) => HttpResponse::Ok().json(page),
+            Err(_) => HttpResponse::NotFound().json("Failed to get trades"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get trades"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/candles/{base}/{quote}",
+    responses(
+        (status = 200, body = Vec<Candle>),
+        (status = 404, body = String)
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
+        ("interval" = String, Query, description = "1m, 5m, 1h or 1d"),
+        ("from" = Option<String>, Query, description = "Earliest bucket start (RFC 3339), inclusive"),
+        ("to" = Option<String>, Query, description = "Latest bucket start (RFC 3339), exclusive"),
+    ),
+    security(())
+)]
+pub async fn candles(base: String, quote: String) -> HttpResponse {
+    let network_client = Client::new();
+    let res = network_client.get(GET_CANDLES.replace("{base}", &base).replace("{quote}", &quote))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<Candle>>().await {
+            Ok(candles) => HttpResponse::Ok().json(candles),
+            Err(_) => HttpResponse::NotFound().json("Failed to get candles"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get candles"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/ticker",
+    responses(
+        (status = 200, body = Vec<Ticker>),
+        (status = 404, body = String)
+    ),
+    security(())
+)]
+pub async fn tickers() -> HttpResponse {
+    let network_client = Client::new();
+    let res = network_client.get(GET_TICKERS)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<Ticker>>().await {
+            Ok(tickers) => HttpResponse::Ok().json(tickers),
+            Err(_) => HttpResponse::NotFound().json("Failed to get tickers"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get tickers"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/ticker/{base}/{quote}",
+    responses(
+        (status = 200, body = Ticker),
+        (status = 404, body = String)
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
+    ),
+    security(())
+)]
+pub async fn ticker(base: String, quote: String) -> HttpResponse {
+    let network_client = Client::new();
+    let res = network_client.get(GET_TICKER.replace("{base}", &base).replace("{quote}", &quote))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Ticker>().await {
+            Ok(ticker) => HttpResponse::Ok().json(ticker),
+            Err(_) => HttpResponse::NotFound().json("Failed to get ticker"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get ticker"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/halts",
+    responses(
+        (status = 200, body = Vec<TradingHalt>, description = "Currency pairs trading is currently halted on"),
+    ),
+    security(())
+)]
+pub async fn trading_halts() -> HttpResponse {
+    let network_client = Client::new();
+    let res = network_client.get(GET_TRADING_HALTS)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<Vec<TradingHalt>>().await {
+            Ok(halts) => HttpResponse::Ok().json(halts),
+            Err(_) => HttpResponse::NotFound().json("Failed to get trading halts"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to get trading halts"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/halts/{base}/{quote}",
+    request_body = TradingHaltRequest,
+    responses(
+        (status = 201, body = TradingHaltResult, description = "Trading halted in both directions, with the orders the halt cancelled"),
+        (status = 403, body = TradingHaltFailed, description = "Caller is not listed in ADMIN_USER_IDS"),
+        (status = 404, body = String, description = "Unknown currency"),
+        (status = 409, body = TradingHaltFailed, description = "Trading on the pair is already halted")
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
+    )
+)]
+pub async fn halt_trading(req: HttpRequest, base: String, quote: String, args: TradingHaltRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.post(POST_TRADING_HALT.replace("{base}", &base).replace("{quote}", &quote))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&args)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<TradingHaltResult>().await {
+            Ok(result) => HttpResponse::Created().json(result),
+            Err(_) => HttpResponse::BadRequest().json("Failed to halt trading"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to halt trading"),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/halts/{base}/{quote}",
+    responses(
+        (status = 200, body = TradingHalt, description = "The halt that was lifted"),
+        (status = 403, body = TradingHaltFailed, description = "Caller is not listed in ADMIN_USER_IDS"),
+        (status = 404, body = TradingHaltFailed, description = "Trading on the pair is not halted")
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
+    )
+)]
+pub async fn resume_trading(req: HttpRequest, base: String, quote: String) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.delete(DELETE_TRADING_HALT.replace("{base}", &base).replace("{quote}", &quote))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<TradingHalt>().await {
+            Ok(halt) => HttpResponse::Ok().json(halt),
+            Err(_) => HttpResponse::NotFound().json("Failed to resume trading"),
+        },
+        Err(_) => HttpResponse::NotFound().json("Failed to resume trading"),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/candles/{base}/{quote}/backfill",
+    request_body = BackfillCandlesRequest,
+    responses(
+        (status = 200, body = BackfillCandlesResponse),
         (status = 400, body = String),
+        (status = 403, body = String, description = "Caller is not listed in ADMIN_USER_IDS"),
+        (status = 404, body = String)
+    ),
+    params(
+        ("base" = String, Path, description = "Base currency code"),
+        ("quote" = String, Path, description = "Quote currency code"),
     )
 )]
-pub async fn buy_currency(req: HttpRequest, args: BuyCurrencyRequest) -> HttpResponse {
+pub async fn backfill_candles(req: HttpRequest, base: String, quote: String, args: BackfillCandlesRequest) -> HttpResponse {
     let token = match req.headers().get("Authorization") {
         Some(h) => h.to_str().ok(),
         None => None,
@@ -426,33 +1367,34 @@ pub async fn buy_currency(req: HttpRequest, args: BuyCurrencyRequest) -> HttpRes
         _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
     };
     let network_client = Client::new();
-    let res = network_client.put(PUT_BUY_CURRENCY)
+    let res = network_client.post(POST_BACKFILL_CANDLES.replace("{base}", &base).replace("{quote}", &quote))
         .header("Authorization", format!("Bearer {}", token))
         .json(&args)
         .send()
         .await;
-    if res.is_ok() {
-        let result = res.unwrap().json::<CurrencyAmount>().await;
-        if result.is_ok() {
-            HttpResponse::Ok().json(result.unwrap())
-        } else {
-            HttpResponse::BadRequest().json("Failed to buy currency")
-        }
-    } else {
-        HttpResponse::BadRequest().json("Failed to put currency")
+    match res {
+        Ok(res) => match res.json::<BackfillCandlesResponse>().await {
+            Ok(response) => HttpResponse::Ok().json(response),
+            Err(_) => HttpResponse::BadRequest().json("Failed to backfill candles"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to backfill candles"),
     }
 }
 
 #[utoipa::path(
-    put,
-    path = "/api/v1/orders/sell/execute",
-    request_body = SellCurrencyRequest,
+    delete,
+    path = "/api/v1/orders/buy/{id}",
     responses(
-        (status = 200, body = CurrencyAmount),
-        (status = 400, body = String),
+        (status = 200, body = BuyOrder),
+        (status = 403, body = OrderUpdateFailed),
+        (status = 404, body = OrderUpdateFailed),
+        (status = 409, body = OrderUpdateFailed)
+    ),
+    params(
+        ("id" = i32, Path, description = "Buy order id"),
     )
 )]
-pub async fn sell_currency(req: HttpRequest, body: SellCurrencyRequest) -> HttpResponse {
+pub async fn cancel_buy_order(req: HttpRequest, id: i32) -> HttpResponse {
     let token = match req.headers().get("Authorization") {
         Some(h) => h.to_str().ok(),
         None => None,
@@ -462,20 +1404,130 @@ pub async fn sell_currency(req: HttpRequest, body: SellCurrencyRequest) -> HttpR
         _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
     };
     let network_client = Client::new();
-    let res = network_client.put(PUT_SELL_CURRENCY)
+    let res = network_client.delete(DELETE_BUY_ORDER.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<BuyOrder>().await {
+            Ok(order) => HttpResponse::Ok().json(order),
+            Err(_) => HttpResponse::BadRequest().json("Failed to cancel buy order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to cancel buy order"),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/orders/sell/{id}",
+    responses(
+        (status = 200, body = SellOrder),
+        (status = 403, body = OrderUpdateFailed),
+        (status = 404, body = OrderUpdateFailed),
+        (status = 409, body = OrderUpdateFailed)
+    ),
+    params(
+        ("id" = i32, Path, description = "Sell order id"),
+    )
+)]
+pub async fn cancel_sell_order(req: HttpRequest, id: i32) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.delete(DELETE_SELL_ORDER.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<SellOrder>().await {
+            Ok(order) => HttpResponse::Ok().json(order),
+            Err(_) => HttpResponse::BadRequest().json("Failed to cancel sell order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to cancel sell order"),
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/orders/buy/{id}",
+    request_body = AmendOrderRequest,
+    responses(
+        (status = 200, body = BuyOrder),
+        (status = 400, body = OrderUpdateFailed),
+        (status = 403, body = OrderUpdateFailed),
+        (status = 404, body = OrderUpdateFailed),
+        (status = 409, body = OrderUpdateFailed)
+    ),
+    params(
+        ("id" = i32, Path, description = "Buy order id"),
+    )
+)]
+pub async fn amend_buy_order(req: HttpRequest, id: i32, body: AmendOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.patch(PATCH_BUY_ORDER.replace("{id}", &id.to_string()))
         .header("Authorization", format!("Bearer {}", token))
         .json(&body)
         .send()
         .await;
-    if res.is_ok() {
-        let result = res.unwrap().json::<CurrencyAmount>().await;
-        if result.is_ok() {
-            HttpResponse::Ok().json(result.unwrap())
-        } else {
-            HttpResponse::BadRequest().json("Error")
-        }
-    } else {
-        HttpResponse::BadRequest().json("Error")
+    match res {
+        Ok(res) => match res.json::<BuyOrder>().await {
+            Ok(order) => HttpResponse::Ok().json(order),
+            Err(_) => HttpResponse::BadRequest().json("Failed to amend buy order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to amend buy order"),
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/orders/sell/{id}",
+    request_body = AmendOrderRequest,
+    responses(
+        (status = 200, body = SellOrder),
+        (status = 400, body = OrderUpdateFailed),
+        (status = 403, body = OrderUpdateFailed),
+        (status = 404, body = OrderUpdateFailed),
+        (status = 409, body = OrderUpdateFailed)
+    ),
+    params(
+        ("id" = i32, Path, description = "Sell order id"),
+    )
+)]
+pub async fn amend_sell_order(req: HttpRequest, id: i32, body: AmendOrderRequest) -> HttpResponse {
+    let token = match req.headers().get("Authorization") {
+        Some(h) => h.to_str().ok(),
+        None => None,
+    };
+    let token = match token {
+        Some(t) if t.starts_with("Bearer ") => t.trim_start_matches("Bearer ").to_string(),
+        _ => return HttpResponse::BadRequest().json("Invalid Bearer Token"),
+    };
+    let network_client = Client::new();
+    let res = network_client.patch(PATCH_SELL_ORDER.replace("{id}", &id.to_string()))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&body)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<SellOrder>().await {
+            Ok(order) => HttpResponse::Ok().json(order),
+            Err(_) => HttpResponse::BadRequest().json("Failed to amend sell order"),
+        },
+        Err(_) => HttpResponse::BadRequest().json("Failed to amend sell order"),
     }
 }
 
@@ -500,11 +1552,23 @@ mod swagger_models {
         pub buy_order_id: Option<i32>,
         pub issuer_id: Option<i32>,
         pub buy_currency_amount: Option<i32>,
+        pub filled_amount: Option<i32>,
+        pub remaining_amount: Option<i32>,
+        pub status: Option<String>,
         pub created_at: String,
         pub updated_at: String,
         pub expires_at: String,
+        pub priority_at: String,
         pub buy_currency_id: Option<i32>,
         pub sell_currency_id: Option<i32>,
+        pub limit_price: Option<f64>,
+        pub time_in_force: Option<String>,
+        pub order_group_id: Option<i32>,
+        /// Slice size of iceberg orders, shown to their issuer only
+        pub display_amount: Option<i32>,
+        pub visible_amount: Option<i32>,
+        pub client_order_id: Option<String>,
+        pub self_trade_prevention: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, Debug, ToSchema)]
@@ -512,11 +1576,23 @@ mod swagger_models {
         pub sell_order_id: Option<i32>,
         pub issuer_id: Option<i32>,
         pub sell_currency_amount: Option<i32>,
+        pub filled_amount: Option<i32>,
+        pub remaining_amount: Option<i32>,
+        pub status: Option<String>,
         pub created_at: String,
         pub updated_at: String,
         pub expires_at: String,
+        pub priority_at: String,
         pub sell_currency_id: Option<i32>,
-        pub buy_currency_id: Option<i32>
+        pub buy_currency_id: Option<i32>,
+        pub limit_price: Option<f64>,
+        pub time_in_force: Option<String>,
+        pub order_group_id: Option<i32>,
+        /// Slice size of iceberg orders, shown to their issuer only
+        pub display_amount: Option<i32>,
+        pub visible_amount: Option<i32>,
+        pub client_order_id: Option<String>,
+        pub self_trade_prevention: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, ToSchema)]
@@ -587,25 +1663,177 @@ mod swagger_models {
 
     #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     pub struct SellCurrencyRequest {
-        pub sum: i32,
-        pub rate: f32,
-        pub order_issuer_id: i32,
-        pub incoming_currency_id: i32,
-        pub outgoing_currency_id: i32
+        pub buy_order_id: i32,
+        pub amount: i32,
+        /// cancel_newest, cancel_oldest, cancel_both or decrement, the account setting when omitted
+        pub self_trade_prevention: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
     pub struct BuyCurrencyRequest {
-        pub sum: i32,
-        pub rate: f32,
-        pub order_issuer_id: i32,
-        pub incoming_currency_id: i32,
-        pub outgoing_currency_id: i32
+        pub sell_order_id: i32,
+        pub amount: i32,
+        /// cancel_newest, cancel_oldest, cancel_both or decrement, the account setting when omitted
+        pub self_trade_prevention: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
-    pub struct CurrencyAmount {
+    pub struct CurrencyExchange {
+        pub id: i32,
+        pub income: Option<f64>,
+        pub sell_order_id: Option<i32>,
+        pub buy_order_id: Option<i32>,
+        pub amount: i32,
+        pub exchange_rate: f64,
+        pub created_at: String,
+        pub buyer_id: Option<i32>,
+        pub seller_id: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct AmendOrderRequest {
         pub amount: Option<i32>,
+        pub limit_price: Option<f64>,
+        pub expires_at: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct OrderUpdateFailed {
+        pub message: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct Fill {
+        pub fill_id: i32,
+        pub exchange_id: i32,
+        pub buy_order_id: Option<i32>,
+        pub sell_order_id: Option<i32>,
+        pub amount: i32,
+        pub exchange_rate: f64,
+        pub created_at: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct PriceLevel {
+        pub price: f64,
+        pub size: i64,
+        pub order_count: i64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct OrderBook {
+        pub base_currency: String,
+        pub quote_currency: String,
+        pub bids: Vec<PriceLevel>,
+        pub asks: Vec<PriceLevel>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct Trade {
+        pub trade_id: i32,
+        pub base_currency_id: Option<i32>,
+        pub quote_currency_id: Option<i32>,
+        pub price: f64,
+        pub amount: i32,
+        pub quote_amount: Option<f64>,
+        pub buy_order_id: Option<i32>,
+        pub sell_order_id: Option<i32>,
+        pub maker_order_id: Option<i32>,
+        pub taker_order_id: Option<i32>,
+        pub taker_side: Option<String>,
+        pub created_at: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct TradePage {
+        pub trades: Vec<Trade>,
+        pub next_cursor: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct UserTrade {
+        pub trade_id: i32,
+        pub base_currency_id: Option<i32>,
+        pub quote_currency_id: Option<i32>,
+        pub price: f64,
+        pub amount: i32,
+        pub quote_amount: Option<f64>,
+        pub side: String,
+        pub order_id: Option<i32>,
+        pub is_maker: Option<bool>,
+        pub created_at: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct UserTradePage {
+        pub trades: Vec<UserTrade>,
+        pub next_cursor: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct Candle {
+        pub base_currency_id: i32,
+        pub quote_currency_id: i32,
+        pub interval: String,
+        pub bucket_start: String,
+        pub open: f64,
+        pub high: f64,
+        pub low: f64,
+        pub close: f64,
+        pub volume: i64,
+        pub quote_volume: f64,
+        pub trade_count: i32,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct BackfillCandlesRequest {
+        pub from: String,
+        pub to: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct BackfillCandlesResponse {
+        pub candles_written: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct Ticker {
+        pub base_currency_id: i32,
+        pub base_currency: Option<String>,
+        pub quote_currency_id: i32,
+        pub quote_currency: Option<String>,
+        pub last_price: Option<f64>,
+        pub best_bid: Option<f64>,
+        pub best_ask: Option<f64>,
+        pub high_24h: Option<f64>,
+        pub low_24h: Option<f64>,
+        pub volume_24h: i64,
+        pub quote_volume_24h: f64,
+        pub trade_count_24h: i64,
+        pub change_percent_24h: Option<f64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct MarketOrderRequest {
+        pub buy_currency_id: i32,
+        pub sell_currency_id: i32,
+        pub amount: i32,
+        pub max_slippage_percent: Option<f64>,
+        pub max_spend: Option<i32>,
+        /// cancel_newest, cancel_oldest, cancel_both or decrement, the account setting when omitted
+        pub self_trade_prevention: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct MarketOrderResult {
+        pub side: String,
+        pub requested_amount: i32,
+        pub filled_amount: i32,
+        pub quote_amount: f64,
+        pub average_price: Option<f64>,
+        pub trades: Vec<Trade>,
+        /// Matches with the taker's own orders that were not executed
+        pub prevented_trades: Vec<PreventedTrade>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
@@ -614,7 +1842,185 @@ mod swagger_models {
         pub sell_amount: i32,
         pub sell_currency_id: i32,
         pub buy_currency_id: i32,
-        pub expiry_days: i32,
+        pub limit_price: f64,
+        pub expiry_days: Option<i32>,
+        /// gtc, gtd, ioc or fok
+        pub time_in_force: Option<String>,
+        pub expires_at: Option<String>,
+        /// Makes it an iceberg order showing only this much at a time
+        pub display_amount: Option<i32>,
+        /// Resubmitting an id already used returns the original order
+        pub client_order_id: Option<String>,
+        /// What happens when the order would match one of the issuer's own orders:
+        /// cancel_newest, cancel_oldest, cancel_both or decrement, the account setting when omitted
+        pub self_trade_prevention: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct CreateStopOrderRequest {
+        /// buy or sell
+        pub side: String,
+        pub base_currency_id: i32,
+        pub quote_currency_id: i32,
+        pub amount: i32,
+        pub stop_price: f64,
+        /// Places a limit order once triggered, a market order when omitted
+        pub limit_price: Option<f64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct StopOrder {
+        pub stop_order_id: i32,
+        pub issuer_id: i32,
+        pub side: String,
+        pub base_currency_id: i32,
+        pub quote_currency_id: i32,
+        pub amount: i32,
+        pub stop_price: f64,
+        pub limit_price: Option<f64>,
+        /// pending, triggered, cancelled or rejected
+        pub status: String,
+        pub created_at: String,
+        pub updated_at: String,
+        pub triggered_at: Option<String>,
+        pub trigger_price: Option<f64>,
+        pub buy_order_id: Option<i32>,
+        pub sell_order_id: Option<i32>,
+        /// First trade of a triggered stop-market order
+        pub exchange_id: Option<i32>,
+        pub reject_reason: Option<String>,
+        pub order_group_id: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct CreateOcoOrderRequest {
+        /// buy or sell
+        pub side: String,
+        pub base_currency_id: i32,
+        pub quote_currency_id: i32,
+        pub amount: i32,
+        /// Price of the limit leg, above the stop for sells and below it for buys
+        pub limit_price: f64,
+        pub stop_price: f64,
+        /// Limit price of the order the stop leg places, a market order when omitted
+        pub stop_limit_price: Option<f64>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct OrderGroup {
+        pub order_group_id: i32,
+        pub buy_orders: Vec<BuyOrder>,
+        pub sell_orders: Vec<SellOrder>,
+        pub stop_orders: Vec<StopOrder>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct UserOrders {
+        pub buy_orders: Vec<BuyOrder>,
+        pub sell_orders: Vec<SellOrder>,
+        pub stop_orders: Vec<StopOrder>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct ClientOrder {
+        pub buy_order: Option<BuyOrder>,
+        pub sell_order: Option<SellOrder>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    #[serde(tag = "action", rename_all = "snake_case")]
+    pub enum BatchOrderItem {
+        CreateBuy(CreateBuyOrderRequest),
+        CreateSell(CreateSellOrderRequest),
+        CancelBuy { buy_order_id: i32 },
+        CancelSell { sell_order_id: i32 },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct BatchOrderRequest {
+        /// Apply every item or none of them, otherwise each item succeeds or fails on its own
+        pub atomic: Option<bool>,
+        pub items: Vec<BatchOrderItem>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct BatchOrderResult {
+        pub buy_order: Option<BuyOrder>,
+        pub sell_order: Option<SellOrder>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct BatchOrderResponse {
+        pub atomic: bool,
+        pub results: Vec<BatchOrderResult>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct TradingHaltRequest {
+        /// cancel cancels every open order on the pair, freeze keeps them resting until trading resumes
+        pub mode: String,
+        pub reason: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct TradingHalt {
+        pub trading_halt_id: i32,
+        pub base_currency_id: i32,
+        pub quote_currency_id: i32,
+        /// cancel or freeze
+        pub mode: String,
+        pub reason: Option<String>,
+        pub halted_by: i32,
+        pub halted_at: String,
+        pub resumed_at: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct TradingHaltResult {
+        pub halt: TradingHalt,
+        pub cancelled_orders: UserOrders,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct TradingHaltFailed {
+        pub message: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct PreventedTrade {
+        pub prevented_trade_id: i32,
+        pub user_id: i32,
+        /// Self-trade prevention mode that was applied
+        pub mode: String,
+        /// Side of the incoming order, buy or sell
+        pub taker_side: String,
+        pub buy_order_id: Option<i32>,
+        pub sell_order_id: Option<i32>,
+        pub base_currency_id: i32,
+        pub quote_currency_id: i32,
+        pub amount: i32,
+        pub exchange_rate: f64,
+        pub created_at: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct TradingSettingsRequest {
+        /// cancel_newest, cancel_oldest, cancel_both or decrement
+        pub self_trade_prevention: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, ToSchema)]
+    pub struct TradingSettings {
+        pub user_id: i32,
+        pub self_trade_prevention: String,
+        /// Missing until the settings are first updated
+        pub updated_at: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+    pub struct TradingSettingsFailed {
+        pub message: String,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
@@ -636,7 +2042,18 @@ mod swagger_models {
         pub buy_amount: i32,
         pub buy_currency_id: i32,
         pub sell_currency_id: i32,
-        pub expiry_days: i32,
+        pub limit_price: f64,
+        pub expiry_days: Option<i32>,
+        /// gtc, gtd, ioc or fok
+        pub time_in_force: Option<String>,
+        pub expires_at: Option<String>,
+        /// Makes it an iceberg order showing only this much at a time
+        pub display_amount: Option<i32>,
+        /// Resubmitting an id already used returns the original order
+        pub client_order_id: Option<String>,
+        /// What happens when the order would match one of the issuer's own orders:
+        /// cancel_newest, cancel_oldest, cancel_both or decrement, the account setting when omitted
+        pub self_trade_prevention: Option<String>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
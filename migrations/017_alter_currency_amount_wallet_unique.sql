@@ -0,0 +1,3 @@
+ALTER TABLE currency_amount
+DROP CONSTRAINT currency_amount_currency_id_key,
+    ADD CONSTRAINT currency_amount_wallet_currency_unique UNIQUE (wallet_id, currency_id);
\ No newline at end of file
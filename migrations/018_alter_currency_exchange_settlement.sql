@@ -0,0 +1,16 @@
+ALTER TABLE currency_exchange
+ADD COLUMN amount INTEGER,
+    ADD COLUMN exchange_rate FLOAT,
+    ADD COLUMN created_at TIMESTAMPTZ;
+
+-- Exchanges recorded before settlement took the whole buy order at the rate its income implies
+UPDATE currency_exchange AS ce
+SET amount = b.buy_currency_amount,
+    exchange_rate = COALESCE(ce.income / NULLIF(b.buy_currency_amount, 0), 0),
+    created_at = b.created_at
+FROM buy_orders AS b
+WHERE b.buy_order_id = ce.buy_order_id;
+
+ALTER TABLE currency_exchange
+ALTER COLUMN amount SET NOT NULL,
+    ALTER COLUMN exchange_rate SET NOT NULL;
@@ -0,0 +1,10 @@
+ALTER TABLE buy_orders
+ADD COLUMN limit_price FLOAT;
+
+-- Orders placed before limit prices never named one, so they are expired instead of resting at 0
+UPDATE buy_orders
+SET limit_price = 0,
+    expires_at = LEAST(COALESCE(expires_at, now()), now());
+
+ALTER TABLE buy_orders
+ALTER COLUMN limit_price SET NOT NULL;
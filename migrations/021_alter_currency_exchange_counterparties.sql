@@ -0,0 +1,5 @@
+ALTER TABLE currency_exchange
+ALTER COLUMN sell_order_id DROP NOT NULL,
+    ALTER COLUMN buy_order_id DROP NOT NULL,
+    ADD COLUMN buyer_id INTEGER REFERENCES users(user_id),
+    ADD COLUMN seller_id INTEGER REFERENCES users(user_id);
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE buy_orders
+ADD COLUMN filled_amount INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN remaining_amount INTEGER NOT NULL DEFAULT 0;
+
+UPDATE buy_orders SET remaining_amount = buy_currency_amount;
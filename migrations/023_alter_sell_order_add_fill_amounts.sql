@@ -0,0 +1,5 @@
+ALTER TABLE sell_orders
+ADD COLUMN filled_amount INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN remaining_amount INTEGER NOT NULL DEFAULT 0;
+
+UPDATE sell_orders SET remaining_amount = sell_currency_amount;
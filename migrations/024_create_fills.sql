@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS fills(
+    fill_id SERIAL PRIMARY KEY,
+    exchange_id INTEGER NOT NULL,
+    buy_order_id INTEGER,
+    sell_order_id INTEGER,
+    amount INTEGER NOT NULL,
+    exchange_rate FLOAT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (exchange_id) REFERENCES currency_exchange(id) ON DELETE CASCADE,
+    FOREIGN KEY (buy_order_id) REFERENCES buy_orders(buy_order_id) ON DELETE CASCADE,
+    FOREIGN KEY (sell_order_id) REFERENCES sell_orders(sell_order_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS fills_buy_order_id_idx ON fills(buy_order_id);
+CREATE INDEX IF NOT EXISTS fills_sell_order_id_idx ON fills(sell_order_id);
@@ -0,0 +1,21 @@
+CREATE TYPE order_status AS ENUM ('open', 'partially_filled', 'filled', 'cancelled', 'expired');
+
+ALTER TABLE buy_orders
+ADD COLUMN status order_status NOT NULL DEFAULT 'open';
+
+ALTER TABLE sell_orders
+ADD COLUMN status order_status NOT NULL DEFAULT 'open';
+
+UPDATE buy_orders SET status = CASE
+    WHEN remaining_amount = 0 THEN 'filled'::order_status
+    WHEN expires_at <= now() THEN 'expired'::order_status
+    WHEN filled_amount > 0 THEN 'partially_filled'::order_status
+    ELSE 'open'::order_status
+END;
+
+UPDATE sell_orders SET status = CASE
+    WHEN remaining_amount = 0 THEN 'filled'::order_status
+    WHEN expires_at <= now() THEN 'expired'::order_status
+    WHEN filled_amount > 0 THEN 'partially_filled'::order_status
+    ELSE 'open'::order_status
+END;
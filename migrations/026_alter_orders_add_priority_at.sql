@@ -0,0 +1,8 @@
+ALTER TABLE buy_orders
+ADD COLUMN priority_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE sell_orders
+ADD COLUMN priority_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+UPDATE buy_orders SET priority_at = created_at WHERE created_at IS NOT NULL;
+UPDATE sell_orders SET priority_at = created_at WHERE created_at IS NOT NULL;
@@ -0,0 +1,8 @@
+ALTER TABLE buy_orders
+DROP CONSTRAINT buy_orders_issuer_id_key;
+
+ALTER TABLE sell_orders
+DROP CONSTRAINT sell_orders_issuer_id_key;
+
+CREATE INDEX IF NOT EXISTS buy_orders_issuer_id_status_idx ON buy_orders(issuer_id, status);
+CREATE INDEX IF NOT EXISTS sell_orders_issuer_id_status_idx ON sell_orders(issuer_id, status);
@@ -0,0 +1,18 @@
+CREATE TYPE order_side AS ENUM ('buy', 'sell');
+
+ALTER TABLE currency_exchange
+ADD COLUMN base_currency_id INTEGER REFERENCES currencies(currency_id),
+    ADD COLUMN quote_currency_id INTEGER REFERENCES currencies(currency_id),
+    ADD COLUMN taker_side order_side;
+
+UPDATE currency_exchange e SET base_currency_id = b.buy_currency_id, quote_currency_id = b.sell_currency_id
+FROM buy_orders b
+WHERE e.buy_order_id = b.buy_order_id AND e.base_currency_id IS NULL;
+
+UPDATE currency_exchange e SET base_currency_id = s.sell_currency_id, quote_currency_id = s.buy_currency_id
+FROM sell_orders s
+WHERE e.sell_order_id = s.sell_order_id AND e.base_currency_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS currency_exchange_pair_idx ON currency_exchange(base_currency_id, quote_currency_id, id);
+CREATE INDEX IF NOT EXISTS currency_exchange_buyer_id_idx ON currency_exchange(buyer_id, id);
+CREATE INDEX IF NOT EXISTS currency_exchange_seller_id_idx ON currency_exchange(seller_id, id);
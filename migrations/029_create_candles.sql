@@ -0,0 +1,47 @@
+CREATE TYPE candle_interval AS ENUM ('1m', '5m', '1h', '1d');
+
+CREATE FUNCTION candle_step(candle_interval) RETURNS INTERVAL
+    LANGUAGE SQL IMMUTABLE AS $$
+        SELECT CASE $1
+            WHEN '1m' THEN INTERVAL '1 minute'
+            WHEN '5m' THEN INTERVAL '5 minutes'
+            WHEN '1h' THEN INTERVAL '1 hour'
+            WHEN '1d' THEN INTERVAL '1 day'
+        END
+    $$;
+
+CREATE TABLE IF NOT EXISTS candles(
+    base_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    quote_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    interval candle_interval NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    open FLOAT NOT NULL,
+    high FLOAT NOT NULL,
+    low FLOAT NOT NULL,
+    close FLOAT NOT NULL,
+    volume BIGINT NOT NULL,
+    quote_volume FLOAT NOT NULL,
+    trade_count INTEGER NOT NULL,
+    first_trade_id INTEGER NOT NULL,
+    last_trade_id INTEGER NOT NULL,
+    PRIMARY KEY (base_currency_id, quote_currency_id, interval, bucket_start)
+);
+
+INSERT INTO candles(base_currency_id, quote_currency_id, interval, bucket_start, open, high, low, close,
+    volume, quote_volume, trade_count, first_trade_id, last_trade_id)
+SELECT e.base_currency_id, e.quote_currency_id, i.interval,
+    date_bin(candle_step(i.interval), e.created_at, TIMESTAMPTZ 'epoch'),
+    (array_agg(e.exchange_rate ORDER BY e.id))[1],
+    MAX(e.exchange_rate),
+    MIN(e.exchange_rate),
+    (array_agg(e.exchange_rate ORDER BY e.id DESC))[1],
+    SUM(e.amount),
+    COALESCE(SUM(e.income), 0),
+    COUNT(*),
+    MIN(e.id),
+    MAX(e.id)
+FROM currency_exchange e
+CROSS JOIN unnest(enum_range(NULL::candle_interval)) AS i(interval)
+WHERE e.base_currency_id IS NOT NULL AND e.quote_currency_id IS NOT NULL AND e.created_at IS NOT NULL
+GROUP BY e.base_currency_id, e.quote_currency_id, i.interval, date_bin(candle_step(i.interval), e.created_at, TIMESTAMPTZ 'epoch')
+ON CONFLICT DO NOTHING;
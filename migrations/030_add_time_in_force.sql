@@ -0,0 +1,10 @@
+CREATE TYPE time_in_force AS ENUM ('gtc', 'gtd', 'ioc', 'fok');
+
+ALTER TABLE buy_orders
+ADD COLUMN time_in_force time_in_force NOT NULL DEFAULT 'gtc';
+
+ALTER TABLE sell_orders
+ADD COLUMN time_in_force time_in_force NOT NULL DEFAULT 'gtc';
+
+UPDATE buy_orders SET time_in_force = 'gtd' WHERE expires_at IS NOT NULL;
+UPDATE sell_orders SET time_in_force = 'gtd' WHERE expires_at IS NOT NULL;
@@ -0,0 +1,23 @@
+CREATE TYPE stop_order_status AS ENUM ('pending', 'triggered', 'cancelled', 'rejected');
+
+CREATE TABLE IF NOT EXISTS stop_orders(
+    stop_order_id SERIAL PRIMARY KEY,
+    issuer_id INTEGER NOT NULL REFERENCES users(user_id),
+    side order_side NOT NULL,
+    base_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    quote_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    amount INTEGER NOT NULL,
+    stop_price FLOAT NOT NULL,
+    limit_price FLOAT,
+    status stop_order_status NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    triggered_at TIMESTAMPTZ,
+    trigger_price FLOAT,
+    buy_order_id INTEGER REFERENCES buy_orders(buy_order_id),
+    sell_order_id INTEGER REFERENCES sell_orders(sell_order_id),
+    reject_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS stop_orders_pending_idx ON stop_orders(base_currency_id, quote_currency_id) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS stop_orders_issuer_id_idx ON stop_orders(issuer_id);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS order_groups(
+    order_group_id SERIAL PRIMARY KEY,
+    issuer_id INTEGER NOT NULL REFERENCES users(user_id),
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+ALTER TABLE buy_orders
+ADD COLUMN order_group_id INTEGER REFERENCES order_groups(order_group_id);
+
+ALTER TABLE sell_orders
+ADD COLUMN order_group_id INTEGER REFERENCES order_groups(order_group_id);
+
+ALTER TABLE stop_orders
+ADD COLUMN order_group_id INTEGER REFERENCES order_groups(order_group_id);
+
+CREATE INDEX IF NOT EXISTS buy_orders_order_group_id_idx ON buy_orders(order_group_id) WHERE order_group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS sell_orders_order_group_id_idx ON sell_orders(order_group_id) WHERE order_group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS stop_orders_order_group_id_idx ON stop_orders(order_group_id) WHERE order_group_id IS NOT NULL;
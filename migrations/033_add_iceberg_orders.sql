@@ -0,0 +1,7 @@
+ALTER TABLE buy_orders
+ADD COLUMN display_amount INTEGER CHECK (display_amount > 0),
+ADD COLUMN visible_amount INTEGER;
+
+ALTER TABLE sell_orders
+ADD COLUMN display_amount INTEGER CHECK (display_amount > 0),
+ADD COLUMN visible_amount INTEGER;
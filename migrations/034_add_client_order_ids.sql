@@ -0,0 +1,8 @@
+ALTER TABLE buy_orders
+ADD COLUMN client_order_id VARCHAR(64);
+
+ALTER TABLE sell_orders
+ADD COLUMN client_order_id VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS buy_orders_client_order_id_idx ON buy_orders(issuer_id, client_order_id) WHERE client_order_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS sell_orders_client_order_id_idx ON sell_orders(issuer_id, client_order_id) WHERE client_order_id IS NOT NULL;
@@ -0,0 +1,16 @@
+CREATE TYPE trading_halt_mode AS ENUM ('cancel', 'freeze');
+
+CREATE TABLE IF NOT EXISTS trading_halts(
+    trading_halt_id SERIAL PRIMARY KEY,
+    base_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    quote_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    mode trading_halt_mode NOT NULL,
+    reason TEXT,
+    halted_by INTEGER NOT NULL REFERENCES users(user_id),
+    halted_at TIMESTAMPTZ NOT NULL,
+    resumed_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS trading_halts_active_pair_idx
+    ON trading_halts(LEAST(base_currency_id, quote_currency_id), GREATEST(base_currency_id, quote_currency_id))
+    WHERE resumed_at IS NULL;
@@ -0,0 +1,29 @@
+CREATE TYPE self_trade_prevention AS ENUM ('cancel_newest', 'cancel_oldest', 'cancel_both', 'decrement');
+
+CREATE TABLE IF NOT EXISTS trading_settings(
+    user_id INTEGER PRIMARY KEY REFERENCES users(user_id),
+    self_trade_prevention self_trade_prevention NOT NULL DEFAULT 'cancel_newest',
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+ALTER TABLE buy_orders
+ADD COLUMN self_trade_prevention self_trade_prevention NOT NULL DEFAULT 'cancel_newest';
+
+ALTER TABLE sell_orders
+ADD COLUMN self_trade_prevention self_trade_prevention NOT NULL DEFAULT 'cancel_newest';
+
+CREATE TABLE IF NOT EXISTS prevented_trades(
+    prevented_trade_id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(user_id),
+    mode self_trade_prevention NOT NULL,
+    taker_side order_side NOT NULL,
+    buy_order_id INTEGER REFERENCES buy_orders(buy_order_id) ON DELETE CASCADE,
+    sell_order_id INTEGER REFERENCES sell_orders(sell_order_id) ON DELETE CASCADE,
+    base_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    quote_currency_id INTEGER NOT NULL REFERENCES currencies(currency_id),
+    amount INTEGER NOT NULL,
+    exchange_rate DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS prevented_trades_user_idx ON prevented_trades(user_id, prevented_trade_id);
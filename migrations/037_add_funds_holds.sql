@@ -0,0 +1,10 @@
+-- Orders placed before this migration hold nothing; their fills are debited
+-- from the available balance as before.
+ALTER TABLE currency_amount
+ADD COLUMN held INTEGER NOT NULL DEFAULT 0 CHECK (held >= 0);
+
+ALTER TABLE buy_orders
+ADD COLUMN held_amount INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE sell_orders
+ADD COLUMN held_amount INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Stop-market orders trade against resting orders without an order of their own,
+-- so a triggered one is linked to the first trade it made instead.
+ALTER TABLE stop_orders
+ADD COLUMN exchange_id INTEGER REFERENCES currency_exchange(id);